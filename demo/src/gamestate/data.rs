@@ -26,6 +26,10 @@ where
     pub map_renderer: MapRenderer,
     pub light_renderer: LightRenderer,
     pub mesh_renderer: Mesh2DRenderer,
+    /// Full screen effects ran after the scene is rendered.
+    pub post_process: PostProcess,
+    /// Times each render and finalize call for the FPS overlay.
+    pub profiler: GpuProfiler,
 }
 
 impl<Controls> Pass for State<Controls>
@@ -39,10 +43,12 @@ where
     ) {
         let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("render pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: renderer.frame_buffer().as_ref().expect("no frame view?"),
-                resolve_target: None,
-                ops: wgpu::Operations {
+            // The scene is drawn into the post process which then
+            // draws it with its effects into the frame buffer.
+            // With MSAA on the scene is resolved into it.
+            color_attachments: &[Some(renderer.color_attachment(
+                self.post_process.scene_view(),
+                wgpu::Operations {
                     load: wgpu::LoadOp::Clear(wgpu::Color {
                         r: 0.0,
                         g: 0.25,
@@ -51,7 +57,7 @@ where
                     }),
                     store: wgpu::StoreOp::Store,
                 },
-            })],
+            ))],
             depth_stencil_attachment: Some(
                 wgpu::RenderPassDepthStencilAttachment {
                     view: renderer.depth_buffer(),
@@ -79,16 +85,67 @@ where
             wgpu::IndexFormat::Uint32,
         );
 
-        pass.render_lower_maps(renderer, &self.map_renderer, &self.map_atlas);
+        self.profiler
+            .profile_pass(&mut pass, "render_lower_maps", |pass| {
+                pass.render_lower_maps(
+                    renderer,
+                    &self.map_renderer,
+                    &self.map_atlas,
+                    renderer.size(),
+                )
+            });
 
-        pass.render_image(renderer, &self.sprite_renderer, &self.image_atlas);
+        self.profiler
+            .profile_pass(&mut pass, "render_image", |pass| {
+                pass.render_image(
+                    renderer,
+                    &self.sprite_renderer,
+                    &self.image_atlas,
+                    renderer.size(),
+                )
+            });
 
-        pass.render_upper_maps(renderer, &self.map_renderer, &self.map_atlas);
+        self.profiler
+            .profile_pass(&mut pass, "render_upper_maps", |pass| {
+                pass.render_upper_maps(
+                    renderer,
+                    &self.map_renderer,
+                    &self.map_atlas,
+                    renderer.size(),
+                )
+            });
 
-        pass.render_lights(renderer, &self.light_renderer);
+        self.profiler
+            .profile_pass(&mut pass, "render_lights", |pass| {
+                pass.render_lights(
+                    renderer,
+                    &self.light_renderer,
+                    renderer.size(),
+                )
+            });
 
-        pass.render_text(renderer, &self.text_renderer, &self.text_atlas);
+        self.profiler
+            .profile_pass(&mut pass, "render_text", |pass| {
+                pass.render_text(
+                    renderer,
+                    &self.text_renderer,
+                    &self.text_atlas,
+                    renderer.size(),
+                )
+            });
 
-        pass.render_2dmeshs(renderer, &self.mesh_renderer);
+        self.profiler
+            .profile_pass(&mut pass, "render_2dmeshs", |pass| {
+                pass.render_2dmeshs(
+                    renderer,
+                    &self.mesh_renderer,
+                    renderer.size(),
+                )
+            });
+
+        drop(pass);
+
+        self.post_process.render(renderer, &self.system, encoder);
+        self.profiler.resolve(encoder);
     }
 }
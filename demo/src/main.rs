@@ -61,6 +61,13 @@ enum Axis {
 // creates a static global logger type for setting the logger
 static MY_LOGGER: MyLogger = MyLogger(Level::Debug);
 
+/// Mip levels of the map atlas so zoomed out tiles do not shimmer.
+const MAP_MIP_LEVELS: u32 = 4;
+
+/// Edge pixels repeated around map tiles so zooming in or out does not
+/// show seams between them. It fills the whole padding of the mip levels.
+const MAP_BORDER: u32 = 1 << (MAP_MIP_LEVELS - 1);
+
 struct MyLogger(pub Level);
 
 impl log::Log for MyLogger {
@@ -133,24 +140,31 @@ async fn main() -> Result<(), AscendingError> {
         unsafe { instance.create_surface(&window).unwrap() };
 
     print!("{:?}", &compatible_surface);
+    // High performance mode says to use Dedicated Graphics devices first.
+    // Low power is APU graphic devices First.
+    let adapter = instance
+        .request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: Some(&compatible_surface),
+            // we will never use this as this forces us to use an alternative renderer.
+            force_fallback_adapter: false,
+        })
+        .await
+        .unwrap();
+    // The profiler times the GPU when the adapter can do timestamps.
+    let features = adapter.features() & PROFILER_FEATURES;
+
     // This creates the Window Struct and Device struct that holds all the rendering information
     // we need to render to the screen. Window holds most of the window information including
     // the surface type. device includes the queue and GPU device for rendering.
     // This then adds gpu_window and gpu_device and creates our renderer type. for easy passing of window, device and font system.
-    let mut renderer = instance
-        .create_device(
+    let mut renderer = adapter
+        .create_renderer(
+            &instance,
             window,
-            &wgpu::RequestAdapterOptions {
-                // High performance mode says to use Dedicated Graphics devices first.
-                // Low power is APU graphic devices First.
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: Some(&compatible_surface),
-                // we will never use this as this forces us to use an alternative renderer.
-                force_fallback_adapter: false,
-            },
             // used to deturmine if we need special limits or features for our backends.
             &wgpu::DeviceDescriptor {
-                features: wgpu::Features::default(),
+                features,
                 limits: wgpu::Limits::default(),
                 label: None,
             },
@@ -164,6 +178,12 @@ async fn main() -> Result<(), AscendingError> {
     // we print the GPU it decided to use here for testing purposes.
     println!("{:?}", renderer.adapter().get_info());
 
+    // Smooths the edges of the meshes. Set before creating anything that
+    // renders with the pipelines so they are only built once.
+    if let Err(e) = renderer.set_sample_count(4) {
+        warn!("MSAA disabled: {e}");
+    }
+
     // We generate Texture atlases to use with out types.
     let mut atlases: Vec<AtlasGroup> = iter::from_fn(|| {
         Some(AtlasGroup::new(
@@ -174,6 +194,20 @@ async fn main() -> Result<(), AscendingError> {
     .take(4)
     .collect();
 
+    // Our images are uploaded once at start up, so the atlases keep a copy
+    // to write them again if the device is lost.
+    atlases
+        .iter_mut()
+        .for_each(|atlas| atlas.atlas.retain_uploads = true);
+
+    atlases[1] = AtlasGroup::with_border(
+        &mut renderer,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        MAP_MIP_LEVELS,
+        MAP_BORDER,
+    );
+    atlases[1].atlas.retain_uploads = true;
+
     // we generate the Text atlas seperatly since it contains a special texture that only has the red color to it.
     // and another for emojicons.
     let text_atlas = TextAtlas::new(&mut renderer).unwrap();
@@ -275,6 +309,8 @@ async fn main() -> Result<(), AscendingError> {
         },
     );
     map.pos = Vec2::new(0.0, 0.0);
+    // The tiles are padded in the mipmapped atlas.
+    map.padding = atlases[1].atlas.padding;
     map.can_render = true;
 
     let _tilesheet = Texture::from_file(format!("images/tiles/1.png"))?
@@ -298,18 +334,23 @@ async fn main() -> Result<(), AscendingError> {
     animation.animate = true;
 
     // get the Scale factor the pc currently is using for upscaling or downscaling the rendering.
-    let scale = renderer.window().current_monitor().unwrap().scale_factor();
+    let scale = renderer
+        .window()
+        .unwrap()
+        .current_monitor()
+        .unwrap()
+        .scale_factor();
 
     // create a Text rendering object.
     let mut text = Text::new(
         &mut renderer,
         Some(Metrics::new(16.0, 16.0).scale(scale as f32)),
         Vec3::new(0.0, 0.0, 1.0),
-        Vec2::new(190.0, 32.0),
+        Vec2::new(320.0, 208.0),
     );
 
     text.set_buffer_size(&mut renderer, size.width as i32, size.height as i32)
-        .set_bounds(Some(Bounds::new(0.0, 0.0, 190.0, 32.0)))
+        .set_bounds(Some(Bounds::new(0.0, 0.0, 320.0, 208.0)))
         .set_default_color(Color::rgba(255, 255, 255, 255));
 
     // Start the process of building a shape.
@@ -374,16 +415,7 @@ async fn main() -> Result<(), AscendingError> {
     let mut debug = Debug::new();
 
     // setup the renderer for iced for UI rendering.
-    let mut iced_renderer = Renderer::new(
-        Backend::new(
-            renderer.device(),
-            renderer.queue(),
-            Settings::default(),
-            renderer.surface_format(),
-        ),
-        iced_winit::core::Font::DEFAULT,
-        iced_winit::core::Pixels(16.0),
-    );
+    let mut iced_renderer = create_iced_renderer(&renderer);
 
     // start up iceds controls for keyboard etc entry.
     let iced_controls = ui::Controls::new();
@@ -462,9 +494,15 @@ async fn main() -> Result<(), AscendingError> {
         edge_fade_distance: 0.6,
         animate: true,
     });
+    // Full screen effects applied to the scene before the UI is drawn.
+    let post_process = create_post_process(&mut renderer);
+
+    // Keeps about a second of timings for each render and finalize call.
+    let profiler = GpuProfiler::new(&renderer, 60);
+
     // Allow the window to be seen. hiding it then making visible speeds up
     // load times.
-    renderer.window().set_visible(true);
+    renderer.window().unwrap().set_visible(true);
 
     // add everything into our convience type for quicker access and passing.
     let mut state = State {
@@ -483,6 +521,8 @@ async fn main() -> Result<(), AscendingError> {
         mesh_renderer,
         lights,
         light_renderer,
+        post_process,
+        profiler,
     };
 
     // Create the mouse/keyboard bindings for our stuff.
@@ -495,12 +535,23 @@ async fn main() -> Result<(), AscendingError> {
     // set bindings and create our own input handler.
     let mut input_handler = InputHandler::new(bindings);
 
+    // Set ASCENDING_SHADER_RELOAD to reload the shaders in
+    // graphics/src/shaders whenever they are saved.
+    let mut shader_watcher = std::env::var_os("ASCENDING_SHADER_RELOAD")
+        .and_then(|_| {
+            ShaderWatcher::new(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../graphics/src/shaders"
+            ))
+            .ok()
+        });
+
     let mut frame_time = FrameTime::new();
     let mut time = 0.0f32;
     let mut fps = 0u32;
 
     // this is for Copy paste stuff within Iced.
-    let mut clipboard = Clipboard::connect(renderer.window());
+    let mut clipboard = Clipboard::connect(renderer.window().unwrap());
 
     #[allow(deprecated)]
     event_loop.run(move |event, _, control_flow| {
@@ -510,7 +561,7 @@ async fn main() -> Result<(), AscendingError> {
                 ref event,
                 window_id,
                 ..
-            } if window_id == renderer.window().id() => {
+            } if window_id == renderer.window().unwrap().id() => {
                 if let WindowEvent::CloseRequested = *event {
                     *control_flow = ControlFlow::Exit;
                 }
@@ -540,18 +591,18 @@ async fn main() -> Result<(), AscendingError> {
                     );
 
                     // and request a redraw
-                    renderer.window().request_redraw();
+                    renderer.window().unwrap().request_redraw();
                     return;
                 }
 
-                renderer.window().request_redraw();
+                renderer.window().unwrap().request_redraw();
             }
             _ => {}
         }
 
         // get the current window size so we can see if we need to resize the renderer.
         let new_size = renderer.size();
-        let inner_size = renderer.window().inner_size();
+        let inner_size = renderer.window().unwrap().inner_size();
 
         // if our rendering size is zero stop rendering to avoid errors.
         if new_size.width == 0.0
@@ -563,7 +614,7 @@ async fn main() -> Result<(), AscendingError> {
         }
 
         // update our inputs.
-        input_handler.update(renderer.window(), &event, 1.0);
+        input_handler.update(renderer.window().unwrap(), &event, 1.0);
 
         // handle the GUI events here.
         if let Event::WindowEvent {
@@ -574,7 +625,7 @@ async fn main() -> Result<(), AscendingError> {
             if let Some(event) = graphics::iced_winit::conversion::window_event(
                 window::Id::MAIN,
                 event,
-                renderer.window().scale_factor(),
+                renderer.window().unwrap().scale_factor(),
                 input_handler.modifiers(),
             ) {
                 iced_state.queue_event(event);
@@ -586,6 +637,41 @@ async fn main() -> Result<(), AscendingError> {
             return;
         }
 
+        for event in renderer.take_events() {
+            match event {
+                RendererEvent::SurfaceRecovered => {
+                    info!("surface was lost and is configured again")
+                }
+                RendererEvent::DeviceRecovered => {
+                    warn!("device was lost, recreating gpu resources");
+
+                    state.system.recreate(&mut renderer);
+                    // Our atlases retain their uploads so write them again
+                    // themselves. Text rasterizes its glyphs again.
+                    state.image_atlas.recreate(&mut renderer);
+                    state.mesh_atlas.recreate(&mut renderer);
+                    state.text_atlas.recreate(&mut renderer);
+                    state.map_atlas.recreate(&mut renderer);
+                    text.set_change(true);
+                    state.sprite_renderer.recreate(&mut renderer);
+                    state.text_renderer.recreate(&renderer);
+                    state.map_renderer.recreate(&renderer);
+                    state.mesh_renderer.recreate(&renderer);
+
+                    if let Err(e) = state.light_renderer.recreate(&mut renderer)
+                    {
+                        error!("failed to recreate lights: {e}");
+                    }
+
+                    state.lights.areas_changed = true;
+                    state.lights.directionals_changed = true;
+                    state.post_process.recreate(&mut renderer);
+                    state.profiler = GpuProfiler::new(&renderer, 60);
+                    iced_renderer = create_iced_renderer(&renderer);
+                }
+            }
+        }
+
         if size != new_size {
             size = new_size;
 
@@ -598,8 +684,6 @@ async fn main() -> Result<(), AscendingError> {
                 near: 1.0,
                 far: -100.0,
             });
-
-            renderer.update_depth_texture();
         }
 
         // check if out close action was hit for esc
@@ -607,7 +691,28 @@ async fn main() -> Result<(), AscendingError> {
             *control_flow = ControlFlow::Exit;
         }
 
+        // On errors the old shaders keep rendering until the file is fixed.
+        if let Some(watcher) = &mut shader_watcher {
+            let reloads = match renderer.reload_changed_shaders(watcher) {
+                Ok(reloads) => reloads,
+                Err(e) => {
+                    error!("failed to check for changed shaders: {e}");
+                    Vec::new()
+                }
+            };
+
+            for reload in reloads {
+                match reload.result {
+                    Ok(count) => {
+                        info!("reloaded {} in {count} pipelines", reload.name)
+                    }
+                    Err(e) => warn!("failed to reload {}: {e}", reload.name),
+                }
+            }
+        }
+
         let seconds = frame_time.seconds();
+        state.profiler.begin_frame();
         // update our systems data to the gpu. this is the Camera in the shaders.
         state.system.update(&renderer, &frame_time);
 
@@ -616,6 +721,9 @@ async fn main() -> Result<(), AscendingError> {
             .system
             .update_screen(&renderer, [new_size.width, new_size.height]);
 
+        // Skip anything the camera can not see when adding to the renderers.
+        renderer.set_view_bounds(Some(state.system.view_bounds()));
+
         // This adds the Image data to the Buffer for rendering.
         state.sprites.iter_mut().for_each(|sprite| {
             state.sprite_renderer.image_update(sprite, &mut renderer);
@@ -629,20 +737,29 @@ async fn main() -> Result<(), AscendingError> {
         // and then uploading them to the GPU if they have moved or changed in any way. clears the
         // Image buffer for the next render pass. Image buffer only holds the ID's and Sortign info
         // of the finalized Indicies of each Image.
-        state.sprite_renderer.finalize(&mut renderer);
+        state.profiler.profile_cpu("sprite_renderer finalize", || {
+            state.sprite_renderer.finalize(&mut renderer)
+        });
 
         state
             .text_renderer
             .text_update(&mut text, &mut state.text_atlas, &mut renderer)
             .unwrap();
-        state.text_renderer.finalize(&mut renderer);
+        state.profiler.profile_cpu("text_renderer finalize", || {
+            state.text_renderer.finalize(&mut renderer)
+        });
         state.map_renderer.map_update(&mut state.map, &mut renderer);
-        state.map_renderer.finalize(&mut renderer);
+        state.profiler.profile_cpu("map_renderer finalize", || {
+            state.map_renderer.finalize(&mut renderer)
+        });
 
         state
             .light_renderer
             .lights_update(&mut state.lights, &mut renderer);
-        state.light_renderer.finalize(&mut renderer);
+        state.profiler.profile_cpu("light_renderer finalize", || {
+            state.light_renderer.finalize(&mut renderer)
+        });
+        state.post_process.update(&mut renderer);
         /*  state.mesh.iter_mut().for_each(|mesh| {
             state.mesh_renderer.mesh_update(mesh, &mut renderer);
         });
@@ -679,10 +796,34 @@ async fn main() -> Result<(), AscendingError> {
         // Also tells the system to begin running the commands on the GPU.
         renderer.queue().submit(std::iter::once(encoder.finish()));
 
+        if let Err(e) = state.profiler.end_frame(&renderer) {
+            warn!("failed to read profiler timings: {e}");
+        }
+
+        let stats = renderer.reset_stats();
+
         if time < seconds {
+            let timings: String = state
+                .profiler
+                .timings()
+                .iter()
+                .map(|timing| {
+                    format!(
+                        "\n{} {:?}: {:.3}ms",
+                        timing.label,
+                        timing.source,
+                        timing.average_ms()
+                    )
+                })
+                .collect();
+
             text.set_text(
                 &mut renderer,
-                &format!("生活,삶,जिंदगी 😀 FPS: {fps} \nhello"),
+                &format!(
+                    "生活,삶,जिंदगी 😀 FPS: {fps} \nhello\ndraws: {} \
+                     instances: {} uploaded: {}B{timings}",
+                    stats.draw_calls, stats.instances, stats.bytes_uploaded
+                ),
                 Attrs::new(),
             );
             fps = 0u32;
@@ -695,7 +836,7 @@ async fn main() -> Result<(), AscendingError> {
         frame_time.update();
         renderer.present().unwrap();
 
-        renderer.window_mut().set_cursor_icon(
+        renderer.window_mut().unwrap().set_cursor_icon(
             iced_winit::conversion::mouse_interaction(
                 iced_state.mouse_interaction(),
             ),
@@ -708,3 +849,30 @@ async fn main() -> Result<(), AscendingError> {
         state.text_atlas.trim();
     })
 }
+
+// Builds the full screen effects. Called again when the device is recreated.
+fn create_post_process(renderer: &mut GpuRenderer) -> PostProcess {
+    let mut post_process = PostProcess::new(renderer);
+
+    post_process.push(PostEffect::vignette(
+        renderer,
+        0.75,
+        0.35,
+        Color::rgba(0, 0, 0, 160),
+    ));
+    post_process
+}
+
+// Creates iced's renderer. Called again when the device is recreated.
+fn create_iced_renderer(renderer: &GpuRenderer) -> Renderer {
+    Renderer::new(
+        Backend::new(
+            renderer.device(),
+            renderer.queue(),
+            Settings::default(),
+            renderer.surface_format(),
+        ),
+        iced_winit::core::Font::DEFAULT,
+        iced_winit::core::Pixels(16.0),
+    )
+}
@@ -1,11 +1,19 @@
 mod allocation;
 mod allocator;
+mod debug;
 mod group;
 mod handler;
 mod layer;
+mod manager;
+mod mipmap;
 
 pub use allocation::Allocation;
 pub use allocator::Allocator;
 pub use group::AtlasGroup;
 pub use handler::Atlas;
 pub use layer::Layer;
+pub use manager::{AssetManager, TextureHandle, TextureReload};
+pub(crate) use mipmap::MipmapGenerator;
+pub use packer::{
+    AtlasManifest, AtlasPacker, PackedImage, PackerError, MAX_ATLAS_MIP_LEVELS,
+};
@@ -1,4 +1,4 @@
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Allocation<Data: Copy + Default = i32> {
     pub allocation: guillotiere::Allocation,
     pub layer: usize,
@@ -20,6 +20,7 @@ impl Allocator {
 
     pub fn clear(&mut self) {
         self.allocator.clear();
+        self.allocations = 0;
     }
 
     pub fn deallocate(&mut self, allocation: guillotiere::Allocation) {
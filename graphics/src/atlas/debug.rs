@@ -0,0 +1,156 @@
+use crate::{AscendingError, Atlas, GpuRenderer};
+use cosmic_text::{Attrs, Buffer, Color, Metrics, Shaping, SwashCache};
+use image::{Rgba, RgbaImage};
+use std::{
+    fmt::{Debug, Write},
+    hash::Hash,
+    path::{Path, PathBuf},
+};
+
+const OUTLINE: Rgba<u8> = Rgba([255, 0, 255, 255]);
+const LABEL: Color = Color::rgba(255, 0, 255, 255);
+
+impl<U: Hash + Eq + Clone + Debug, Data: Copy + Default> Atlas<U, Data> {
+    /// How much of each layer is allocated, padding included, and its
+    /// pool, followed by every key from most to least recently used with
+    /// its layer and rect. Keys used since the last trim are marked used,
+    /// pinned keys pinned.
+    pub fn debug_report(&self) -> String {
+        let capacity = self.extent.width as u64 * self.extent.height as u64;
+        let mut used = vec![(0usize, 0u64); self.layers.len()];
+        let mut report = String::new();
+
+        for (_, allocation) in self.cache.iter() {
+            let (_, _, width, height) = self.padded_rect(allocation);
+
+            used[allocation.layer].0 += 1;
+            used[allocation.layer].1 += width as u64 * height as u64;
+        }
+
+        for (layer, (count, area)) in used.iter().enumerate() {
+            let _ = writeln!(
+                report,
+                "layer {layer}: {count} allocations, {area} of {capacity} \
+                 pixels, {:.2}% used, pool {}",
+                *area as f64 * 100.0 / capacity as f64,
+                self.layers[layer].pool
+            );
+        }
+
+        let _ = writeln!(report, "\nmost recently used first:");
+
+        for (key, allocation) in self.cache.iter() {
+            let _ = writeln!(
+                report,
+                "{key:?} layer {} rect {:?}{}{}",
+                allocation.layer,
+                allocation.rect(),
+                if self.last_used.contains(key) {
+                    " used"
+                } else {
+                    ""
+                },
+                if self.is_pinned(key) { " pinned" } else { "" },
+            );
+        }
+
+        report
+    }
+
+    /// Reads back level 0 of every layer and writes it to directory as
+    /// layer_N.png with each allocation outlined and labeled by its key,
+    /// along with debug_report as report.txt. Returns the layer images'
+    /// paths. Meant for finding out if the atlas or the UVs are wrong.
+    pub fn dump_layers(
+        &self,
+        directory: impl AsRef<Path>,
+        renderer: &mut GpuRenderer,
+    ) -> Result<Vec<PathBuf>, AscendingError> {
+        let directory = directory.as_ref();
+        let mut cache = SwashCache::new();
+        let mut paths = Vec::with_capacity(self.layers.len());
+
+        std::fs::create_dir_all(directory)?;
+
+        for layer in 0..self.layers.len() {
+            let mut image =
+                renderer.read_texture_layer(&self.texture, layer as u32)?;
+
+            for (key, allocation) in self.cache.iter() {
+                if allocation.layer == layer {
+                    outline(&mut image, allocation.rect());
+                    label(
+                        &mut image,
+                        allocation.rect(),
+                        &format!("{key:?}"),
+                        &mut cache,
+                        renderer,
+                    );
+                }
+            }
+
+            let path = directory.join(format!("layer_{layer}.png"));
+
+            image.save(&path)?;
+            paths.push(path);
+        }
+
+        std::fs::write(directory.join("report.txt"), self.debug_report())?;
+        Ok(paths)
+    }
+}
+
+fn outline(image: &mut RgbaImage, rect: (u32, u32, u32, u32)) {
+    let (x, y, width, height) = rect;
+
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let (right, bottom) = (x + width - 1, y + height - 1);
+
+    for px in x..=right {
+        image.put_pixel(px, y, OUTLINE);
+        image.put_pixel(px, bottom, OUTLINE);
+    }
+
+    for py in y..=bottom {
+        image.put_pixel(x, py, OUTLINE);
+        image.put_pixel(right, py, OUTLINE);
+    }
+}
+
+/// Draws text in the top left of rect, cut off at its edges.
+fn label(
+    image: &mut RgbaImage,
+    rect: (u32, u32, u32, u32),
+    text: &str,
+    cache: &mut SwashCache,
+    renderer: &mut GpuRenderer,
+) {
+    let (x, y, width, height) = rect;
+    let font_sys = renderer.font_sys_mut();
+    let mut buffer = Buffer::new(font_sys, Metrics::new(10.0, 12.0));
+
+    buffer.set_size(font_sys, width as f32, height as f32);
+    buffer.set_text(font_sys, text, Attrs::new(), Shaping::Advanced);
+    buffer.shape_until_scroll(font_sys);
+    buffer.draw(font_sys, cache, LABEL, |gx, gy, gw, gh, color| {
+        let alpha = color.a() as u32;
+
+        for py in gy.max(1)..(gy + gh as i32).min(height as i32 - 1) {
+            for px in gx.max(1)..(gx + gw as i32).min(width as i32 - 1) {
+                let pixel = image.get_pixel_mut(x + px as u32, y + py as u32);
+                let source = [color.r(), color.g(), color.b()];
+
+                for (channel, source) in pixel.0.iter_mut().zip(source) {
+                    *channel = ((source as u32 * alpha
+                        + *channel as u32 * (255 - alpha))
+                        / 255) as u8;
+                }
+
+                pixel.0[3] = pixel.0[3].max(color.a());
+            }
+        }
+    });
+}
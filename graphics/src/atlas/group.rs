@@ -1,5 +1,12 @@
-use crate::{Allocation, Atlas, GpuRenderer, TextureGroup, TextureLayout};
-use std::hash::Hash;
+use crate::{
+    Allocation, AscendingError, Atlas, AtlasManifest, GpuRenderer,
+    TextureGroup, TextureLayout,
+};
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    path::{Path, PathBuf},
+};
 
 /// Group of a Atlas Details
 pub struct AtlasGroup<U: Hash + Eq + Clone = String, Data: Copy + Default = i32>
@@ -15,7 +22,29 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> AtlasGroup<U, Data> {
         renderer: &mut GpuRenderer,
         format: wgpu::TextureFormat,
     ) -> Self {
-        let atlas = Atlas::<U, Data>::new(renderer, format);
+        Self::with_mip_levels(renderer, format, 1)
+    }
+
+    /// Creates the group with a mipmapped Atlas. See
+    /// Atlas::with_mip_levels.
+    pub fn with_mip_levels(
+        renderer: &mut GpuRenderer,
+        format: wgpu::TextureFormat,
+        mip_levels: u32,
+    ) -> Self {
+        Self::with_border(renderer, format, mip_levels, 0)
+    }
+
+    /// Creates the group with an Atlas that extrudes the edges of its
+    /// images. See Atlas::with_border.
+    pub fn with_border(
+        renderer: &mut GpuRenderer,
+        format: wgpu::TextureFormat,
+        mip_levels: u32,
+        border: u32,
+    ) -> Self {
+        let atlas =
+            Atlas::<U, Data>::with_border(renderer, format, mip_levels, border);
 
         let texture = TextureGroup::from_view(
             renderer,
@@ -40,6 +69,58 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> AtlasGroup<U, Data> {
             .upload(hash, bytes, width, height, data, renderer)
     }
 
+    /// Runs f with the mip levels of its uploads rendered once when it
+    /// returns. See Atlas::batch_uploads.
+    pub fn batch_uploads<R>(
+        &mut self,
+        renderer: &GpuRenderer,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let deferred = std::mem::replace(&mut self.atlas.defer_mipmaps, true);
+        let result = f(self);
+
+        self.atlas.defer_mipmaps = deferred;
+        self.atlas.finish_mipmaps(renderer);
+        result
+    }
+
+    /// Uploads into the layers of pool. See Atlas::upload_to_pool.
+    #[allow(clippy::too_many_arguments)]
+    pub fn upload_to_pool(
+        &mut self,
+        pool: usize,
+        hash: U,
+        bytes: &[u8],
+        width: u32,
+        height: u32,
+        data: Data,
+        renderer: &GpuRenderer,
+    ) -> Option<Allocation<Data>> {
+        self.atlas
+            .upload_to_pool(pool, hash, bytes, width, height, data, renderer)
+    }
+
+    /// Creates the texture and bind group on a new device after the old
+    /// one was lost. See Atlas::recreate for what happens to the images.
+    pub fn recreate(&mut self, renderer: &mut GpuRenderer) {
+        self.atlas.recreate(renderer);
+        self.texture = TextureGroup::from_view(
+            renderer,
+            &self.atlas.texture_view,
+            TextureLayout,
+        );
+    }
+
+    /// Writes bytes into the allocation already made for hash.
+    pub fn reupload(
+        &mut self,
+        hash: &U,
+        bytes: &[u8],
+        renderer: &GpuRenderer,
+    ) -> Option<Allocation<Data>> {
+        self.atlas.reupload(hash, bytes, renderer)
+    }
+
     pub fn trim(&mut self) {
         self.atlas.trim();
     }
@@ -48,6 +129,94 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> AtlasGroup<U, Data> {
         self.atlas.clear();
     }
 
+    pub fn remove(&mut self, key: &U) -> Option<Allocation<Data>> {
+        self.atlas.remove(key)
+    }
+
+    /// Loads an atlas packed by AtlasPacker. See Atlas::load_packed.
+    pub fn load_packed(
+        &mut self,
+        manifest: &AtlasManifest<U, Data>,
+        layers: &[image::RgbaImage],
+        renderer: &mut GpuRenderer,
+    ) -> Result<Vec<Allocation<Data>>, AscendingError> {
+        let count = self.atlas.layers.len();
+        let allocations = self.atlas.load_packed(manifest, layers, renderer)?;
+
+        // The texture was replaced to add layers.
+        if self.atlas.layers.len() != count {
+            self.texture = TextureGroup::from_view(
+                renderer,
+                &self.atlas.texture_view,
+                TextureLayout,
+            );
+        }
+
+        Ok(allocations)
+    }
+
+    /// See Atlas::debug_report.
+    pub fn debug_report(&self) -> String
+    where
+        U: Debug,
+    {
+        self.atlas.debug_report()
+    }
+
+    /// Writes every layer with its allocations outlined to directory. See
+    /// Atlas::dump_layers.
+    pub fn dump_layers(
+        &self,
+        directory: impl AsRef<Path>,
+        renderer: &mut GpuRenderer,
+    ) -> Result<Vec<PathBuf>, AscendingError>
+    where
+        U: Debug,
+    {
+        self.atlas.dump_layers(directory, renderer)
+    }
+
+    pub fn start_compaction(&mut self) -> usize {
+        self.atlas.start_compaction()
+    }
+
+    /// Moves queued allocations. See Atlas::compact.
+    pub fn compact(
+        &mut self,
+        moves: usize,
+        renderer: &mut GpuRenderer,
+    ) -> bool {
+        let count = self.atlas.layers.len();
+        let done = self.atlas.compact(moves, renderer);
+
+        // The texture was replaced to drop emptied layers.
+        if self.atlas.layers.len() != count {
+            self.texture = TextureGroup::from_view(
+                renderer,
+                &self.atlas.texture_view,
+                TextureLayout,
+            );
+        }
+
+        done
+    }
+
+    pub fn pin(&mut self, key: &U) {
+        self.atlas.pin(key);
+    }
+
+    pub fn unpin(&mut self, key: &U) {
+        self.atlas.unpin(key);
+    }
+
+    pub fn track_evictions(&mut self) {
+        self.atlas.track_evictions();
+    }
+
+    pub fn take_evicted(&mut self) -> Vec<U> {
+        self.atlas.take_evicted()
+    }
+
     pub fn promote(&mut self, key: U) {
         self.atlas.promote(key);
     }
@@ -1,6 +1,20 @@
-use crate::{Allocation, GpuRenderer, Layer};
+use crate::{
+    Allocation, AscendingError, AtlasManifest, GpuRenderer, Layer,
+    MipmapGenerator,
+};
 use lru::LruCache;
-use std::{collections::HashSet, hash::Hash};
+use packer::{clamp_mip_levels, mip_padding, pad_pixels};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+/// Allocations start_compaction queued to move out of their layers.
+struct Compaction<U> {
+    /// The layers being emptied.
+    sources: HashSet<usize>,
+    queue: VecDeque<U>,
+}
 
 pub struct Atlas<U: Hash + Eq + Clone = String, Data: Copy + Default = i32> {
     /// Texture in GRAM
@@ -14,16 +28,51 @@ pub struct Atlas<U: Hash + Eq + Clone = String, Data: Copy + Default = i32> {
     /// File Paths or names to prevent duplicates.
     pub cache: LruCache<U, Allocation<Data>>,
     pub last_used: HashSet<U>,
+    /// How many times each key was pinned. Pinned keys are never evicted
+    /// to make room for new allocations and are moved to the front of
+    /// cache when evicting passes over them.
+    pub pinned: HashMap<U, usize>,
     /// Format the Texture uses.
     pub format: wgpu::TextureFormat,
     /// When the System will Error if reached. This is the max allowed Layers
     /// Default is 256 as Most GPU allow a max of 256.
     pub max_layers: u32,
+    /// Mip levels of the Texture. 1 unless made with with_mip_levels.
+    pub mip_levels: u32,
+    /// Pixels kept around each allocation so the lower mip levels and
+    /// linear filtering do not blend in the images next to it. 0 without
+    /// mipmaps or a border.
+    pub padding: u32,
+    /// How many of the padding pixels repeat the edge pixels of the image
+    /// instead of being transparent, so filtering at its edges does not
+    /// fade out or show seams between tiles.
+    pub border: u32,
+    /// Bumped each time compact moves allocations so anything keeping an
+    /// Allocation knows to get it again.
+    pub generation: u64,
+    /// Keys evicted to make room since the last take_evicted. None until
+    /// track_evictions is called.
+    evicted: Option<Vec<U>>,
+    /// Keeps a copy of the bytes of each upload so recreate can write
+    /// them again on a new device. Off by default as it doubles the
+    /// memory the images take. Without it their owners upload them again
+    /// with reupload after recreate.
+    pub retain_uploads: bool,
+    /// The bytes last uploaded for each key while retain_uploads is set.
+    uploads: HashMap<U, Vec<u8>>,
+    mipmaps: Option<MipmapGenerator>,
+    /// The area of each layer written since its mip levels were last
+    /// rendered, as left, top, right and bottom.
+    mip_regions: HashMap<u32, (u32, u32, u32, u32)>,
+    /// Set while uploads leave their mip levels to generate_mipmaps.
+    pub(crate) defer_mipmaps: bool,
+    compaction: Option<Compaction<U>>,
 }
 
 impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
     fn allocate(
         &mut self,
+        pool: usize,
         width: u32,
         height: u32,
         data: Data,
@@ -35,7 +84,23 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
 
         /* Try allocating from an existing layer. */
         for (i, layer) in self.layers.iter_mut().enumerate() {
+            // Layers being compacted are left to empty out.
+            if self
+                .compaction
+                .as_ref()
+                .is_some_and(|compaction| compaction.sources.contains(&i))
+            {
+                continue;
+            }
+
+            // Other pools keep their layers until they are empty.
+            if layer.pool != pool && !layer.allocator.is_empty() {
+                continue;
+            }
+
             if let Some(allocation) = layer.allocator.allocate(width, height) {
+                layer.pool = pool;
+
                 return Some(Allocation {
                     allocation,
                     layer: i,
@@ -46,19 +111,45 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
 
         /* Try to see if we can clear out unused allocations first. */
         loop {
-            let (key, _) = self.cache.peek_lru()?;
+            let mut unused = None;
+            let mut passed_pinned = Vec::new();
 
-            //Check if ID has been used yet?
-            if self.last_used.contains(key) {
-                //Failed to find any unused allocations so lets try to add a layer.
-                break;
+            // Least recently used first, skipping over pinned keys and
+            // the keys of other pools.
+            for (key, allocation) in self.cache.iter().rev() {
+                //Check if ID has been used yet?
+                if self.last_used.contains(key) {
+                    break;
+                }
+
+                if self.pinned.contains_key(key) {
+                    passed_pinned.push(key.clone());
+                } else if self.layers[allocation.layer].pool == pool {
+                    unused = Some(key.clone());
+                    break;
+                }
             }
 
-            let (_, allocation) = self.cache.pop_lru()?;
+            // Pinned keys are moved to the front so the next pass does not
+            // scan over them again.
+            for key in passed_pinned {
+                self.cache.promote(&key);
+            }
+
+            //Failed to find any unused allocations so lets try to add a layer.
+            let Some(key) = unused else {
+                break;
+            };
+
+            let allocation = self.cache.pop(&key)?;
             let layer_id = allocation.layer;
             let layer = self.layers.get_mut(layer_id).unwrap();
 
             layer.allocator.deallocate(allocation.allocation);
+            self.uploads.remove(&key);
+            if let Some(evicted) = &mut self.evicted {
+                evicted.push(key);
+            }
 
             if let Some(allocation) = layer.allocator.allocate(width, height) {
                 return Some(Allocation {
@@ -78,6 +169,8 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
 
         let mut layer = Layer::new(self.extent.width);
 
+        layer.pool = pool;
+
         if let Some(allocation) = layer.allocator.allocate(width, height) {
             self.layers.push(layer);
 
@@ -99,12 +192,64 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
 
         self.cache.clear();
         self.last_used.clear();
+        self.uploads.clear();
+        self.compaction = None;
     }
 
     pub fn trim(&mut self) {
         self.last_used.clear();
     }
 
+    /// Frees the allocation of key so its space can be used again. Pins
+    /// of key are kept.
+    pub fn remove(&mut self, key: &U) -> Option<Allocation<Data>> {
+        let allocation = self.cache.pop(key)?;
+
+        if let Some(layer) = self.layers.get_mut(allocation.layer) {
+            layer.allocator.deallocate(allocation.allocation);
+        }
+
+        self.last_used.remove(key);
+        self.uploads.remove(key);
+        Some(allocation)
+    }
+
+    /// Keeps key from being evicted until unpinned as many times.
+    pub fn pin(&mut self, key: &U) {
+        *self.pinned.entry(key.clone()).or_default() += 1;
+    }
+
+    pub fn unpin(&mut self, key: &U) {
+        if let Some(count) = self.pinned.get_mut(key) {
+            *count -= 1;
+
+            if *count == 0 {
+                self.pinned.remove(key);
+            }
+        }
+    }
+
+    pub fn is_pinned(&self, key: &U) -> bool {
+        self.pinned.contains_key(key)
+    }
+
+    /// Starts keeping the keys evicted to make room for uploads for
+    /// take_evicted. Off by default so atlases nobody takes them from do
+    /// not collect them forever.
+    pub fn track_evictions(&mut self) {
+        self.evicted.get_or_insert_with(Vec::new);
+    }
+
+    /// The keys evicted to make room for uploads since the last call,
+    /// oldest first, so their owners can upload them again if needed.
+    /// Always empty unless track_evictions was called.
+    pub fn take_evicted(&mut self) -> Vec<U> {
+        self.evicted
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
     pub fn promote(&mut self, key: U) {
         self.cache.promote(&key);
         self.last_used.insert(key);
@@ -132,27 +277,40 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
             return;
         }
 
-        let extent = wgpu::Extent3d {
-            width: self.extent.width,
-            height: self.extent.height,
-            depth_or_array_layers: self.layers.len() as u32,
-        };
+        self.resize_texture((self.layers.len() - amount) as u32, renderer);
+    }
 
-        let texture =
-            renderer.device().create_texture(&wgpu::TextureDescriptor {
-                label: Some("Texture"),
-                size: extent,
-                mip_level_count: 0,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: self.format,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING
-                    | wgpu::TextureUsages::COPY_DST
-                    | wgpu::TextureUsages::COPY_SRC,
-                view_formats: &[wgpu::TextureFormat::Bgra8Unorm],
-            });
+    /// Drops the empty layers at the end, keeping the two every atlas
+    /// starts with, and moves the rest into a texture of the new size.
+    fn shrink(&mut self, renderer: &GpuRenderer) {
+        let keep = self
+            .layers
+            .iter()
+            .rposition(|layer| !layer.allocator.is_empty())
+            .map_or(0, |layer| layer + 1)
+            .max(2);
 
-        let amount_to_copy = self.layers.len() - amount;
+        if keep >= self.layers.len() {
+            return;
+        }
+
+        self.layers.truncate(keep);
+        self.mip_regions.retain(|layer, _| (*layer as usize) < keep);
+        self.resize_texture(keep as u32, renderer);
+    }
+
+    /// Replaces the texture with one of self.layers.len() layers, copying
+    /// the first amount_to_copy layers of the old one into it.
+    fn resize_texture(&mut self, amount_to_copy: u32, renderer: &GpuRenderer) {
+        let (texture, texture_view) = Self::create_texture(
+            renderer,
+            self.format,
+            wgpu::Extent3d {
+                depth_or_array_layers: self.layers.len() as u32,
+                ..self.extent
+            },
+            self.mip_levels,
+        );
 
         let mut encoder = renderer.device().create_command_encoder(
             &wgpu::CommandEncoderDescriptor {
@@ -160,70 +318,122 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
             },
         );
 
-        for (i, _) in self.layers.iter_mut().take(amount_to_copy).enumerate() {
+        for mip_level in 0..self.mip_levels {
             encoder.copy_texture_to_texture(
                 wgpu::ImageCopyTextureBase {
                     texture: &self.texture,
-                    mip_level: 0,
-                    origin: wgpu::Origin3d {
-                        x: 0,
-                        y: 0,
-                        z: i as u32,
-                    },
+                    mip_level,
+                    origin: wgpu::Origin3d::ZERO,
                     aspect: wgpu::TextureAspect::All,
                 },
                 wgpu::ImageCopyTextureBase {
                     texture: &texture,
-                    mip_level: 0,
-                    origin: wgpu::Origin3d {
-                        x: 0,
-                        y: 0,
-                        z: i as u32,
-                    },
+                    mip_level,
+                    origin: wgpu::Origin3d::ZERO,
                     aspect: wgpu::TextureAspect::All,
                 },
                 wgpu::Extent3d {
-                    width: self.extent.width,
-                    height: self.extent.height,
-                    depth_or_array_layers: 1,
+                    width: (self.extent.width >> mip_level).max(1),
+                    height: (self.extent.height >> mip_level).max(1),
+                    depth_or_array_layers: amount_to_copy,
                 },
             );
         }
 
         self.texture = texture;
-        self.texture_view =
-            self.texture.create_view(&wgpu::TextureViewDescriptor {
-                label: Some("Texture Atlas"),
-                format: Some(self.format),
-                dimension: Some(wgpu::TextureViewDimension::D2Array),
-                aspect: wgpu::TextureAspect::All,
-                base_mip_level: 0,
-                mip_level_count: Some(1),
-                base_array_layer: 0,
-                array_layer_count: Some(self.layers.len() as u32),
-            });
+        self.texture_view = texture_view;
         renderer.queue().submit(std::iter::once(encoder.finish()));
     }
 
     pub fn new(renderer: &GpuRenderer, format: wgpu::TextureFormat) -> Self {
+        Self::with_mip_levels(renderer, format, 1)
+    }
+
+    /// Creates an Atlas whose texture has mip_levels levels, clamped to
+    /// MAX_ATLAS_MIP_LEVELS. They are rendered on the GPU after each upload
+    /// or once for many uploads with batch_uploads. Allocations are padded
+    /// by 2^(mip_levels - 1) pixels on each side so the smallest level
+    /// stays apart from the images around it. The format must be
+    /// renderable when mip_levels is above 1.
+    pub fn with_mip_levels(
+        renderer: &GpuRenderer,
+        format: wgpu::TextureFormat,
+        mip_levels: u32,
+    ) -> Self {
+        Self::with_border(renderer, format, mip_levels, 0)
+    }
+
+    /// Creates an Atlas like with_mip_levels that extrudes the edge
+    /// pixels of every upload border pixels outward. The padding grows to
+    /// fit the border. Allocation::rect stays the image's own rectangle.
+    pub fn with_border(
+        renderer: &GpuRenderer,
+        format: wgpu::TextureFormat,
+        mip_levels: u32,
+        border: u32,
+    ) -> Self {
         let limits = renderer.device().limits();
         let extent = wgpu::Extent3d {
             width: limits.max_texture_dimension_3d,
             height: limits.max_texture_dimension_3d,
             depth_or_array_layers: 2,
         };
+        let mip_levels = clamp_mip_levels(mip_levels, extent.width);
+        let (texture, texture_view) =
+            Self::create_texture(renderer, format, extent, mip_levels);
+
+        Self {
+            texture,
+            texture_view,
+            layers: vec![
+                Layer::new(limits.max_texture_dimension_3d),
+                Layer::new(limits.max_texture_dimension_3d),
+            ],
+            extent,
+            cache: LruCache::unbounded(),
+            last_used: HashSet::default(),
+            pinned: HashMap::default(),
+            format,
+            max_layers: limits.max_texture_array_layers,
+            mip_levels,
+            padding: mip_padding(mip_levels).max(border),
+            border,
+            generation: 0,
+            evicted: None,
+            retain_uploads: false,
+            uploads: HashMap::default(),
+            mipmaps: (mip_levels > 1)
+                .then(|| MipmapGenerator::new(renderer, format)),
+            mip_regions: HashMap::default(),
+            defer_mipmaps: false,
+            compaction: None,
+        }
+    }
+
+    fn create_texture(
+        renderer: &GpuRenderer,
+        format: wgpu::TextureFormat,
+        extent: wgpu::Extent3d,
+        mip_levels: u32,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let mut usage = wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::COPY_SRC;
+
+        // The mip levels are rendered into.
+        if mip_levels > 1 {
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+        }
 
         let texture =
             renderer.device().create_texture(&wgpu::TextureDescriptor {
                 label: Some("Texture"),
                 size: extent,
-                mip_level_count: 1,
+                mip_level_count: mip_levels,
                 sample_count: 1,
                 dimension: wgpu::TextureDimension::D2,
                 format,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING
-                    | wgpu::TextureUsages::COPY_DST
-                    | wgpu::TextureUsages::COPY_SRC,
+                usage,
                 view_formats: &[format],
             });
 
@@ -233,26 +443,150 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
             dimension: Some(wgpu::TextureViewDimension::D2Array),
             aspect: wgpu::TextureAspect::All,
             base_mip_level: 0,
-            mip_level_count: Some(1),
+            mip_level_count: Some(mip_levels),
             base_array_layer: 0,
-            array_layer_count: Some(1),
+            array_layer_count: Some(extent.depth_or_array_layers),
         });
 
-        Self {
-            texture,
-            texture_view,
-            layers: vec![
-                Layer::new(limits.max_texture_dimension_3d),
-                Layer::new(limits.max_texture_dimension_3d),
-            ],
-            extent,
-            cache: LruCache::unbounded(),
-            last_used: HashSet::default(),
-            format,
-            max_layers: limits.max_texture_array_layers,
+        (texture, texture_view)
+    }
+
+    /// Creates the texture on a new device after the old one was lost.
+    /// Allocations are kept so anything using them stays valid. Their
+    /// bytes are written again if retain_uploads was set when they were
+    /// uploaded, otherwise they are blank until reuploaded.
+    pub fn recreate(&mut self, renderer: &GpuRenderer) {
+        (self.texture, self.texture_view) = Self::create_texture(
+            renderer,
+            self.format,
+            wgpu::Extent3d {
+                depth_or_array_layers: self.layers.len() as u32,
+                ..self.extent
+            },
+            self.mip_levels,
+        );
+
+        if self.mipmaps.is_some() {
+            self.mipmaps = Some(MipmapGenerator::new(renderer, self.format));
+        }
+
+        let written: Vec<_> = self
+            .cache
+            .iter()
+            .filter_map(|(key, allocation)| {
+                let bytes = self.uploads.get(key)?;
+
+                Some((
+                    allocation.layer,
+                    self.write_allocation(bytes, allocation, renderer),
+                ))
+            })
+            .collect();
+
+        self.mip_regions.clear();
+        written
+            .into_iter()
+            .for_each(|(layer, rect)| self.queue_mipmaps(layer, rect));
+        self.finish_mipmaps(renderer);
+    }
+
+    /// Runs f with every upload's mip levels left to be rendered once per
+    /// layer when it returns, instead of once per upload.
+    pub fn batch_uploads<R>(
+        &mut self,
+        renderer: &GpuRenderer,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let deferred = std::mem::replace(&mut self.defer_mipmaps, true);
+        let result = f(self);
+
+        self.defer_mipmaps = deferred;
+        self.finish_mipmaps(renderer);
+        result
+    }
+
+    /// Renders the mip levels of everything written since they were last
+    /// rendered, with one submit for the whole atlas.
+    pub fn generate_mipmaps(&mut self, renderer: &GpuRenderer) {
+        let Some(mipmaps) = &self.mipmaps else {
+            return;
+        };
+
+        if self.mip_regions.is_empty() {
+            return;
+        }
+
+        let mut encoder = renderer.device().create_command_encoder(
+            &wgpu::CommandEncoderDescriptor {
+                label: Some("Mipmap command encoder"),
+            },
+        );
+
+        for (layer, (left, top, right, bottom)) in self.mip_regions.drain() {
+            mipmaps.generate(
+                renderer,
+                &mut encoder,
+                &self.texture,
+                layer,
+                (left, top, right - left, bottom - top),
+            );
+        }
+
+        renderer.queue().submit(std::iter::once(encoder.finish()));
+    }
+
+    /// Generates the mip levels unless uploads are being batched.
+    pub(crate) fn finish_mipmaps(&mut self, renderer: &GpuRenderer) {
+        if !self.defer_mipmaps {
+            self.generate_mipmaps(renderer);
+        }
+    }
+
+    /// Keeps the bytes of key for recreate if retain_uploads is set.
+    fn retain(&mut self, key: &U, bytes: impl FnOnce() -> Vec<u8>) {
+        if self.retain_uploads {
+            self.uploads.insert(key.clone(), bytes());
         }
     }
 
+    fn queue_mipmaps(&mut self, layer: usize, rect: (u32, u32, u32, u32)) {
+        if self.mipmaps.is_none() {
+            return;
+        }
+
+        let (x, y, width, height) = rect;
+        let region = self.mip_regions.entry(layer as u32).or_insert((
+            x,
+            y,
+            x + width,
+            y + height,
+        ));
+
+        *region = (
+            region.0.min(x),
+            region.1.min(y),
+            region.2.max(x + width),
+            region.3.max(y + height),
+        );
+    }
+
+    /// Writes bytes into the allocation already made for key. Returns None
+    /// if key was never uploaded.
+    pub fn reupload(
+        &mut self,
+        key: &U,
+        bytes: &[u8],
+        renderer: &GpuRenderer,
+    ) -> Option<Allocation<Data>> {
+        let allocation = *self.cache.peek(key)?;
+
+        self.upload_allocation(bytes, &allocation, renderer);
+        self.retain(key, || bytes.to_vec());
+        self.finish_mipmaps(renderer);
+        Some(allocation)
+    }
+
+    /// Uploads into pool 0. See upload_to_pool.
     #[allow(clippy::too_many_arguments)]
     pub fn upload(
         &mut self,
@@ -262,33 +596,470 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
         height: u32,
         data: Data,
         renderer: &GpuRenderer,
+    ) -> Option<Allocation<Data>> {
+        self.upload_to_pool(0, key, bytes, width, height, data, renderer)
+    }
+
+    /// Uploads into the layers of pool. Making room only evicts keys of
+    /// the same pool, so uploads that churn, like glyphs, can be kept from
+    /// evicting what other pools hold, like map tiles.
+    #[allow(clippy::too_many_arguments)]
+    pub fn upload_to_pool(
+        &mut self,
+        pool: usize,
+        key: U,
+        bytes: &[u8],
+        width: u32,
+        height: u32,
+        data: Data,
+        renderer: &GpuRenderer,
     ) -> Option<Allocation<Data>> {
         if let Some(allocation) = self.get(&key) {
             Some(allocation)
         } else {
             let allocation = {
                 let nlayers = self.layers.len();
-                let allocation = self.allocate(width, height, data)?;
+                let mut allocation = self.allocate(
+                    pool,
+                    width + self.padding * 2,
+                    height + self.padding * 2,
+                    data,
+                )?;
                 self.grow(self.layers.len() - nlayers, renderer);
 
+                allocation.allocation = self.unpadded(allocation.allocation);
                 allocation
             };
 
             self.upload_allocation(bytes, &allocation, renderer);
-            self.cache.push(key.clone(), allocation);
+            self.retain(&key, || bytes.to_vec());
+            self.cache.push(key, allocation);
+            self.finish_mipmaps(renderer);
             Some(allocation)
         }
     }
 
+    /// Loads an atlas packed by AtlasPacker with one upload per layer image
+    /// into empty layers, adding layers as needed. Images already in the
+    /// cache under the same name are replaced. Returns the allocations in
+    /// the order of manifest.images. The layers go into pool 0. Only
+    /// Rgba8UnormSrgb atlases of the size, padding and border the manifest
+    /// was packed for can load it.
+    pub fn load_packed(
+        &mut self,
+        manifest: &AtlasManifest<U, Data>,
+        layers: &[image::RgbaImage],
+        renderer: &GpuRenderer,
+    ) -> Result<Vec<Allocation<Data>>, AscendingError> {
+        if self.format != wgpu::TextureFormat::Rgba8UnormSrgb {
+            return Err(AscendingError::PackedAtlas(format!(
+                "the atlas format {:?} is not Rgba8UnormSrgb",
+                self.format
+            )));
+        }
+
+        if manifest.size != self.extent.width
+            || manifest.padding != self.padding
+            || manifest.border != self.border
+        {
+            return Err(AscendingError::PackedAtlas(format!(
+                "packed for size {}, padding {} and border {} but the atlas \
+                 has size {}, padding {} and border {}",
+                manifest.size,
+                manifest.padding,
+                manifest.border,
+                self.extent.width,
+                self.padding,
+                self.border
+            )));
+        }
+
+        if layers.len() != manifest.layers.len()
+            || layers.iter().any(|layer| {
+                layer.width() > manifest.size || layer.height() > manifest.size
+            })
+        {
+            return Err(AscendingError::PackedAtlas(
+                "the layer images do not match the manifest".to_owned(),
+            ));
+        }
+
+        // Allocating in the same order on empty layers gives back the same
+        // rectangles the packer got.
+        let mut packed: Vec<Layer> =
+            layers.iter().map(|_| Layer::new(manifest.size)).collect();
+        let mut allocations = Vec::with_capacity(manifest.images.len());
+
+        for (i, image) in manifest.images.iter().enumerate() {
+            let (x, y, width, height) = image.rect;
+            let allocation = packed
+                .get_mut(image.layer)
+                .and_then(|layer| {
+                    layer.allocator.allocate(
+                        width + self.padding * 2,
+                        height + self.padding * 2,
+                    )
+                })
+                .map(|allocation| self.unpadded(allocation))
+                .filter(|allocation| {
+                    allocation.rectangle.min.x as u32 == x
+                        && allocation.rectangle.min.y as u32 == y
+                })
+                .ok_or_else(|| {
+                    AscendingError::PackedAtlas(format!(
+                        "image {i} is not where the packer put it in layer {}",
+                        image.layer
+                    ))
+                })?;
+
+            allocations.push(allocation);
+        }
+
+        // Layers only holding images about to be replaced count as empty.
+        let names: HashSet<&U> =
+            manifest.images.iter().map(|image| &image.name).collect();
+        let mut used = vec![false; self.layers.len()];
+
+        for (key, allocation) in self.cache.iter() {
+            if !names.contains(key) {
+                used[allocation.layer] = true;
+            }
+        }
+
+        if let Some(compaction) = &self.compaction {
+            for layer in &compaction.sources {
+                used[*layer] = true;
+            }
+        }
+
+        let mut targets: Vec<usize> = (0..used.len())
+            .filter(|layer| !used[*layer])
+            .take(layers.len())
+            .collect();
+        let added = layers.len() - targets.len();
+
+        if added > 0 && self.layers.len() + added >= self.max_layers as usize {
+            return Err(AscendingError::AtlasFull);
+        }
+
+        for image in &manifest.images {
+            self.remove(&image.name);
+        }
+
+        targets.extend(self.layers.len()..self.layers.len() + added);
+        self.layers
+            .extend((0..added).map(|_| Layer::new(self.extent.width)));
+        self.grow(added, renderer);
+
+        for ((layer, image), allocator) in
+            targets.iter().zip(layers).zip(packed)
+        {
+            self.layers[*layer] = allocator;
+
+            renderer.queue().write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &self.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: *layer as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                image,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * image.width()),
+                    rows_per_image: Some(image.height()),
+                },
+                wgpu::Extent3d {
+                    width: image.width(),
+                    height: image.height(),
+                    depth_or_array_layers: 1,
+                },
+            );
+
+            self.queue_mipmaps(*layer, (0, 0, image.width(), image.height()));
+        }
+
+        self.finish_mipmaps(renderer);
+
+        Ok(manifest
+            .images
+            .iter()
+            .zip(allocations)
+            .map(|(image, allocation)| {
+                let (x, y, width, height) = image.rect;
+                let allocation = Allocation {
+                    allocation,
+                    layer: targets[image.layer],
+                    data: image.data,
+                };
+
+                self.retain(&image.name, || {
+                    image::imageops::crop_imm(
+                        &layers[image.layer],
+                        x,
+                        y,
+                        width,
+                        height,
+                    )
+                    .to_image()
+                    .into_raw()
+                });
+                self.cache.push(image.name.clone(), allocation);
+                allocation
+            })
+            .collect())
+    }
+
+    /// Queues the allocations of the sparsest layers to move into the free
+    /// space of the other layers, as long as at least as much space as is
+    /// moved stays free. Nothing is allocated in the emptied layers until
+    /// done. Returns how many were queued. Run compact until it returns
+    /// true to move them. Atlases whose tiles a Map finds by position
+    /// should not be compacted.
+    pub fn start_compaction(&mut self) -> usize {
+        let capacity = self.extent.width as u64 * self.extent.height as u64;
+        let mut used = vec![0u64; self.layers.len()];
+        let mut allocations: Vec<(U, u64, usize)> = Vec::new();
+
+        for (key, allocation) in self.cache.iter() {
+            let (_, _, width, height) = self.padded_rect(allocation);
+            let area = width as u64 * height as u64;
+
+            used[allocation.layer] += area;
+            allocations.push((key.clone(), area, allocation.layer));
+        }
+
+        let pools: HashSet<usize> =
+            self.layers.iter().map(|layer| layer.pool).collect();
+        let mut sources = HashSet::new();
+
+        // Allocations only move between layers of the same pool.
+        for pool in pools {
+            let mut layers: Vec<usize> = (0..used.len())
+                .filter(|layer| self.layers[*layer].pool == pool)
+                .collect();
+            let mut free: u64 =
+                layers.iter().map(|layer| capacity - used[*layer]).sum();
+
+            layers.retain(|layer| used[*layer] > 0);
+            layers.sort_by_key(|layer| used[*layer]);
+
+            for layer in layers {
+                // Emptying a layer takes its own free space away too.
+                if free < capacity + used[layer] {
+                    break;
+                }
+
+                free -= capacity;
+                sources.insert(layer);
+            }
+        }
+
+        // Largest first as they are the hardest to fit.
+        allocations.retain(|(_, _, layer)| sources.contains(layer));
+        allocations.sort_by_key(|(_, area, _)| std::cmp::Reverse(*area));
+
+        let queue: VecDeque<U> =
+            allocations.into_iter().map(|(key, _, _)| key).collect();
+        let count = queue.len();
+
+        self.compaction = (count > 0).then_some(Compaction { sources, queue });
+        count
+    }
+
+    pub fn is_compacting(&self) -> bool {
+        self.compaction.is_some()
+    }
+
+    /// Moves up to moves of the allocations queued by start_compaction,
+    /// copying them on the GPU. Ones that no longer fit anywhere else stay
+    /// where they are. Returns true once nothing is left to move, after
+    /// dropping the layers left empty at the end. That replaces the
+    /// texture, so bind groups made from its view need making again.
+    pub fn compact(&mut self, moves: usize, renderer: &GpuRenderer) -> bool {
+        let Some(mut compaction) = self.compaction.take() else {
+            return true;
+        };
+
+        let mut encoder = renderer.device().create_command_encoder(
+            &wgpu::CommandEncoderDescriptor {
+                label: Some("Atlas compaction encoder"),
+            },
+        );
+        let mut moved = Vec::new();
+        let count = moves.min(compaction.queue.len());
+
+        for key in compaction.queue.drain(..count) {
+            // It may have been evicted or removed since it was queued.
+            let Some(allocation) = self.cache.peek(&key).copied() else {
+                continue;
+            };
+
+            if !compaction.sources.contains(&allocation.layer) {
+                continue;
+            }
+
+            let (x, y, width, height) = self.padded_rect(&allocation);
+            let pool = self.layers[allocation.layer].pool;
+            let Some((layer, new)) = self
+                .layers
+                .iter_mut()
+                .enumerate()
+                .filter(|(i, layer)| {
+                    !compaction.sources.contains(i) && layer.pool == pool
+                })
+                .find_map(|(i, layer)| {
+                    layer.allocator.allocate(width, height).map(|new| (i, new))
+                })
+            else {
+                continue;
+            };
+
+            encoder.copy_texture_to_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &self.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x,
+                        y,
+                        z: allocation.layer as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyTexture {
+                    texture: &self.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: new.rectangle.min.x as u32,
+                        y: new.rectangle.min.y as u32,
+                        z: layer as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+
+            self.layers[allocation.layer]
+                .allocator
+                .deallocate(allocation.allocation);
+
+            let allocation = Allocation {
+                allocation: self.unpadded(new),
+                layer,
+                data: allocation.data,
+            };
+
+            if let Some(entry) = self.cache.peek_mut(&key) {
+                *entry = allocation;
+            }
+
+            moved.push(allocation);
+        }
+
+        renderer.queue().submit(std::iter::once(encoder.finish()));
+
+        // The mip levels are rendered again as the new position may round
+        // differently in them.
+        for allocation in &moved {
+            self.queue_mipmaps(allocation.layer, self.padded_rect(allocation));
+        }
+
+        self.finish_mipmaps(renderer);
+
+        if !moved.is_empty() {
+            self.generation += 1;
+        }
+
+        if compaction.queue.is_empty() {
+            self.shrink(renderer);
+            return true;
+        }
+
+        self.compaction = Some(compaction);
+        false
+    }
+
+    /// The allocated rectangle of allocation, padding included.
+    pub(crate) fn padded_rect(
+        &self,
+        allocation: &Allocation<Data>,
+    ) -> (u32, u32, u32, u32) {
+        let (x, y, width, height) = allocation.rect();
+
+        (
+            x - self.padding,
+            y - self.padding,
+            width + self.padding * 2,
+            height + self.padding * 2,
+        )
+    }
+
+    /// Deallocating goes by id so allocations only keep the rectangle the
+    /// image is in.
+    fn unpadded(
+        &self,
+        mut allocation: guillotiere::Allocation,
+    ) -> guillotiere::Allocation {
+        let padding = self.padding as i32;
+
+        allocation.rectangle = allocation.rectangle.inflate(-padding, -padding);
+        allocation
+    }
+
     fn upload_allocation(
         &mut self,
         buffer: &[u8],
         allocation: &Allocation<Data>,
         renderer: &GpuRenderer,
     ) {
-        let (x, y) = allocation.position();
-        let (width, height) = allocation.size();
+        let rect = self.write_allocation(buffer, allocation, renderer);
+
+        self.queue_mipmaps(allocation.layer, rect);
+    }
+
+    /// Writes buffer and its padding into level 0. Returns the rectangle
+    /// written.
+    fn write_allocation(
+        &self,
+        buffer: &[u8],
+        allocation: &Allocation<Data>,
+        renderer: &GpuRenderer,
+    ) -> (u32, u32, u32, u32) {
+        let (mut x, mut y) = allocation.position();
+        let (mut width, mut height) = allocation.size();
         let layer = allocation.layer;
+        let pixel_size = if self.format == wgpu::TextureFormat::Rgba8UnormSrgb {
+            4
+        } else {
+            1
+        };
+        let padded;
+        let mut buffer = buffer;
+
+        // Write the padding too so nothing left by an evicted image shows.
+        if self.padding > 0 {
+            padded = pad_pixels(
+                buffer,
+                width,
+                height,
+                pixel_size,
+                self.padding,
+                self.border,
+            );
+            buffer = &padded;
+            x -= self.padding;
+            y -= self.padding;
+            width += self.padding * 2;
+            height += self.padding * 2;
+        }
 
         renderer.queue().write_texture(
             wgpu::ImageCopyTexture {
@@ -304,13 +1075,7 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
             buffer,
             wgpu::ImageDataLayout {
                 offset: 0,
-                bytes_per_row: Some(
-                    if self.format == wgpu::TextureFormat::Rgba8UnormSrgb {
-                        4 * width
-                    } else {
-                        width
-                    },
-                ),
+                bytes_per_row: Some(pixel_size * width),
                 rows_per_image: Some(height),
             },
             wgpu::Extent3d {
@@ -319,5 +1084,7 @@ impl<U: Hash + Eq + Clone, Data: Copy + Default> Atlas<U, Data> {
                 depth_or_array_layers: 1,
             },
         );
+
+        (x, y, width, height)
     }
 }
@@ -2,12 +2,16 @@ use crate::Allocator;
 
 pub struct Layer {
     pub allocator: Allocator,
+    /// The pool of the Atlas allocating from this layer. Empty layers are
+    /// taken over by whichever pool needs them.
+    pub pool: usize,
 }
 
 impl Layer {
     pub fn new(size: u32) -> Self {
         Self {
             allocator: Allocator::new(size),
+            pool: 0,
         }
     }
 }
@@ -0,0 +1,306 @@
+use crate::{
+    Allocation, AscendingError, AtlasGroup, GpuRenderer, OtherError, Texture,
+    TextureWatcher, TileSheet,
+};
+use std::{cell::Cell, collections::HashMap, path::Path, rc::Rc};
+
+#[derive(Debug)]
+struct HandleState {
+    name: String,
+    group: usize,
+    allocation: Cell<Option<Allocation>>,
+}
+
+/// A shared texture in an AtlasGroup given out by the AssetManager.
+/// Its allocation is pinned while any clone of it is alive, and is kept up
+/// to date by AssetManager::update.
+#[derive(Clone, Debug)]
+pub struct TextureHandle(Rc<HandleState>);
+
+impl TextureHandle {
+    pub fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    /// Index of the AtlasGroup the texture is in.
+    pub fn group(&self) -> usize {
+        self.0.group
+    }
+
+    /// Where the texture currently is in the atlas. None once the atlas
+    /// was cleared.
+    pub fn allocation(&self) -> Option<Allocation> {
+        self.0.allocation.get()
+    }
+
+    /// How many clones are alive outside of the AssetManager.
+    pub fn users(&self) -> usize {
+        Rc::strong_count(&self.0) - 1
+    }
+}
+
+impl PartialEq for TextureHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for TextureHandle {}
+
+/// The result of reloading a texture or tilesheet from a changed file.
+#[derive(Debug)]
+pub struct TextureReload {
+    pub name: String,
+    pub group: usize,
+    /// If the texture changed size and was moved to a new allocation, or
+    /// why it was not reloaded.
+    pub result: Result<bool, AscendingError>,
+}
+
+/// Hands out TextureHandles keyed by AtlasGroup index and texture name so
+/// every user of a texture shares one allocation. Allocations in use are
+/// pinned so the atlas will not evict them for newer uploads.
+/// Call update once a frame to release textures nothing uses anymore.
+#[derive(Default)]
+pub struct AssetManager {
+    handles: HashMap<(usize, String), TextureHandle>,
+    /// (group, tilesize) of the tilesheets loaded by name.
+    tilesheets: HashMap<String, Vec<(usize, u32)>>,
+}
+
+impl AssetManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The handle of name in atlases[group] if it was uploaded and is not
+    /// released yet.
+    pub fn get(&self, name: &str, group: usize) -> Option<TextureHandle> {
+        self.handles.get(&(group, name.to_owned())).cloned()
+    }
+
+    /// Loads the image at path into atlases[group] unless a texture of the
+    /// same file name is already there.
+    pub fn load(
+        &mut self,
+        path: impl AsRef<Path>,
+        group: usize,
+        atlases: &mut [AtlasGroup],
+        renderer: &GpuRenderer,
+    ) -> Result<TextureHandle, AscendingError> {
+        let name = path.as_ref().file_name().and_then(|name| name.to_str());
+
+        if let Some(handle) = name.and_then(|name| self.get(name, group)) {
+            return Ok(handle);
+        }
+
+        if group >= atlases.len() {
+            return Err(AscendingError::MissingAtlasGroup(group));
+        }
+
+        let texture = Texture::from_file(path)?;
+
+        self.upload(&texture, group, atlases, renderer)
+            .ok_or(AscendingError::AtlasFull)
+    }
+
+    /// Slices the image at path into tiles of tilesize in atlases[group]
+    /// with TileSheet::upload. They are sliced again when reloaded.
+    pub fn load_tilesheet(
+        &mut self,
+        path: impl AsRef<Path>,
+        group: usize,
+        tilesize: u32,
+        atlases: &mut [AtlasGroup],
+        renderer: &GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        let texture = Texture::from_file(path)?;
+        let atlas = atlases
+            .get_mut(group)
+            .ok_or(AscendingError::MissingAtlasGroup(group))?;
+        let sheets = self
+            .tilesheets
+            .entry(texture.name().to_owned())
+            .or_default();
+
+        if !sheets.contains(&(group, tilesize)) {
+            sheets.push((group, tilesize));
+        }
+
+        TileSheet::upload(texture, renderer, atlas, tilesize)
+            .ok_or(AscendingError::AtlasFull)
+    }
+
+    /// Uploads texture into atlases[group] and pins it. Returns the handle
+    /// already given out if the texture was uploaded before. None if the
+    /// group does not exist or is full.
+    pub fn upload(
+        &mut self,
+        texture: &Texture,
+        group: usize,
+        atlases: &mut [AtlasGroup],
+        renderer: &GpuRenderer,
+    ) -> Option<TextureHandle> {
+        if let Some(handle) = self.get(texture.name(), group) {
+            return Some(handle);
+        }
+
+        let atlas = atlases.get_mut(group)?;
+        let allocation = texture.group_upload(atlas, renderer)?;
+        let handle = TextureHandle(Rc::new(HandleState {
+            name: texture.name().to_owned(),
+            group,
+            allocation: Cell::new(Some(allocation)),
+        }));
+
+        atlas.pin(&handle.0.name);
+        self.handles
+            .insert((group, handle.0.name.clone()), handle.clone());
+        Some(handle)
+    }
+
+    /// Unpins the textures without users so the atlas may evict them and
+    /// updates every handle's allocation. Returns how many were released.
+    pub fn update(&mut self, atlases: &mut [AtlasGroup]) -> usize {
+        let count = self.handles.len();
+
+        self.handles.retain(|(group, name), handle| {
+            let Some(atlas) = atlases.get_mut(*group) else {
+                return false;
+            };
+
+            if handle.users() == 0 {
+                atlas.unpin(name);
+                return false;
+            }
+
+            handle.0.allocation.set(atlas.peek(name).copied());
+            true
+        });
+
+        count - self.handles.len()
+    }
+
+    /// Reloads the textures and tilesheets loaded from a file of the same
+    /// name as path. Textures of the same size are written over in place,
+    /// others are moved to a new allocation which their handles and the
+    /// Images using them pick up on their next update.
+    pub fn reload(
+        &mut self,
+        path: impl AsRef<Path>,
+        atlases: &mut [AtlasGroup],
+        renderer: &GpuRenderer,
+    ) -> Vec<TextureReload> {
+        let Some(name) = path.as_ref().file_name().and_then(|n| n.to_str())
+        else {
+            return Vec::new();
+        };
+        let name = name.to_owned();
+        let mut targets: Vec<(usize, Option<u32>)> = self
+            .handles
+            .keys()
+            .filter(|(_, key)| *key == name)
+            .map(|(group, _)| (*group, None))
+            .collect();
+
+        if let Some(sheets) = self.tilesheets.get(&name) {
+            targets.extend(
+                sheets
+                    .iter()
+                    .map(|(group, tilesize)| (*group, Some(*tilesize))),
+            );
+        }
+
+        if targets.is_empty() {
+            return Vec::new();
+        }
+
+        let texture = Texture::from_file(path);
+
+        targets
+            .into_iter()
+            .map(|(group, tilesize)| TextureReload {
+                name: name.clone(),
+                group,
+                result: match &texture {
+                    Ok(texture) => match tilesize {
+                        Some(tilesize) => atlases
+                            .get_mut(group)
+                            .ok_or(AscendingError::MissingAtlasGroup(group))
+                            .and_then(|atlas| {
+                                TileSheet::reload(
+                                    texture, renderer, atlas, tilesize,
+                                )
+                                .ok_or(AscendingError::AtlasFull)
+                            })
+                            .map(|_| false),
+                        None => self
+                            .reload_texture(texture, group, atlases, renderer),
+                    },
+                    Err(error) => Err(AscendingError::Other(OtherError::new(
+                        &error.to_string(),
+                    ))),
+                },
+            })
+            .collect()
+    }
+
+    /// Reloads every changed file the watcher found.
+    pub fn reload_changed(
+        &mut self,
+        watcher: &mut TextureWatcher,
+        atlases: &mut [AtlasGroup],
+        renderer: &GpuRenderer,
+    ) -> Result<Vec<TextureReload>, AscendingError> {
+        Ok(watcher
+            .changed()?
+            .into_iter()
+            .flat_map(|path| self.reload(path, atlases, renderer))
+            .collect())
+    }
+
+    fn reload_texture(
+        &mut self,
+        texture: &Texture,
+        group: usize,
+        atlases: &mut [AtlasGroup],
+        renderer: &GpuRenderer,
+    ) -> Result<bool, AscendingError> {
+        let key = (group, texture.name().to_owned());
+        let handle = self.handles.get(&key).ok_or_else(|| {
+            AscendingError::Other(OtherError::new(&format!(
+                "No handle to {} in AtlasGroup {group} was given out.",
+                key.1
+            )))
+        })?;
+        let atlas = atlases
+            .get_mut(group)
+            .ok_or(AscendingError::MissingAtlasGroup(group))?;
+
+        let same_size = atlas
+            .peek(&key.1)
+            .is_some_and(|allocation| allocation.size() == texture.size());
+
+        if same_size {
+            atlas.reupload(&key.1, &texture.bytes, renderer);
+            return Ok(false);
+        }
+
+        // The pin is kept by name so the new allocation stays pinned.
+        atlas.remove(&key.1);
+
+        let allocation = texture.group_upload(atlas, renderer);
+
+        handle.0.allocation.set(allocation);
+        allocation.map(|_| true).ok_or(AscendingError::AtlasFull)
+    }
+
+    /// Handles given out and not released yet.
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+}
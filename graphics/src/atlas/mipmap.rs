@@ -0,0 +1,219 @@
+use crate::GpuRenderer;
+
+/// Renders the mip levels of an Atlas texture from level 0, each level
+/// from the one above it.
+pub(crate) struct MipmapGenerator {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+}
+
+impl MipmapGenerator {
+    pub fn new(renderer: &GpuRenderer, format: wgpu::TextureFormat) -> Self {
+        let device = renderer.device();
+        let shader =
+            device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Mipmap shader"),
+                source: wgpu::ShaderSource::Wgsl(
+                    include_str!("../shaders/mipmapshader.wgsl").into(),
+                ),
+            });
+
+        let layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("mipmap_bind_group_layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float {
+                                filterable: true,
+                            },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(
+                            wgpu::SamplerBindingType::Filtering,
+                        ),
+                        count: None,
+                    },
+                ],
+            });
+
+        let pipeline =
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("Mipmap render pipeline"),
+                layout: Some(&device.create_pipeline_layout(
+                    &wgpu::PipelineLayoutDescriptor {
+                        label: Some("mipmap_pipeline_layout"),
+                        bind_group_layouts: &[&layout],
+                        push_constant_ranges: &[],
+                    },
+                )),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vertex",
+                    buffers: &[],
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fragment",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                multiview: None,
+            });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Mipmap sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            pipeline,
+            layout,
+            sampler,
+        }
+    }
+
+    /// Records rendering levels 1 and up of rect (x, y, width, height) in
+    /// level 0 of layer into encoder. Only the texels covering rect are
+    /// drawn.
+    pub fn generate(
+        &self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        layer: u32,
+        rect: (u32, u32, u32, u32),
+    ) {
+        let (x, y, width, height) = rect;
+
+        for level in 1..texture.mip_level_count() {
+            let level_width = (texture.width() >> level).max(1);
+            let level_height = (texture.height() >> level).max(1);
+            // Rounded outward so partly covered texels are redrawn too.
+            let left = (x >> level).min(level_width - 1);
+            let top = (y >> level).min(level_height - 1);
+            let right = (x + width).div_ceil(1 << level).min(level_width);
+            let bottom = (y + height).div_ceil(1 << level).min(level_height);
+            let size = wgpu::Extent3d {
+                width: (right - left) * 2,
+                height: (bottom - top) * 2,
+                depth_or_array_layers: 1,
+            };
+
+            // Some backends can only sample the first level and layer of a
+            // texture so the level above is copied out to sample from.
+            let source =
+                renderer.device().create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Mipmap source"),
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: texture.format(),
+                    usage: wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::COPY_DST,
+                    view_formats: &[],
+                });
+
+            encoder.copy_texture_to_texture(
+                wgpu::ImageCopyTexture {
+                    texture,
+                    mip_level: level - 1,
+                    origin: wgpu::Origin3d {
+                        x: left * 2,
+                        y: top * 2,
+                        z: layer,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyTexture {
+                    texture: &source,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                size,
+            );
+
+            let source_view =
+                source.create_view(&wgpu::TextureViewDescriptor::default());
+            let target = texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Mipmap level"),
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_mip_level: level,
+                mip_level_count: Some(1),
+                base_array_layer: layer,
+                array_layer_count: Some(1),
+                ..Default::default()
+            });
+            let bind_group = renderer.device().create_bind_group(
+                &wgpu::BindGroupDescriptor {
+                    label: Some("Mipmap Bind Group"),
+                    layout: &self.layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(
+                                &source_view,
+                            ),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(
+                                &self.sampler,
+                            ),
+                        },
+                    ],
+                },
+            );
+
+            let mut pass =
+                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Mipmap render pass"),
+                    color_attachments: &[Some(
+                        wgpu::RenderPassColorAttachment {
+                            view: &target,
+                            resolve_target: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Load,
+                                store: wgpu::StoreOp::Store,
+                            },
+                        },
+                    )],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+
+            // The source is exactly twice the size of the viewport.
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.set_viewport(
+                left as f32,
+                top as f32,
+                (right - left) as f32,
+                (bottom - top) as f32,
+                0.0,
+                1.0,
+            );
+            pass.draw(0..3, 0..1);
+        }
+    }
+}
@@ -21,6 +21,28 @@ impl OtherError {
     }
 }
 
+/// A WGSL error in a shader file. line and column start at 1 and are 0
+/// when naga could not tell where the error is.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ShaderError {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+    pub message: String,
+}
+
+impl std::error::Error for ShaderError {}
+
+impl std::fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.file, self.line, self.column, self.message
+        )
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum AscendingError {
     #[error(transparent)]
@@ -33,10 +55,34 @@ pub enum AscendingError {
     Device(#[from] wgpu::RequestDeviceError),
     #[error(transparent)]
     ImageError(#[from] image::ImageError),
+    #[error(transparent)]
+    BufferAsync(#[from] wgpu::BufferAsyncError),
+    #[error("Texture format {0:?} can not be read back.")]
+    UnsupportedFormat(wgpu::TextureFormat),
+    #[error("MSAA sample count {0} is not supported by the device.")]
+    UnsupportedSampleCount(u32),
+    #[error("Texture format {0:?} can not be rendered to by the surface.")]
+    UnsupportedSurfaceFormat(wgpu::TextureFormat),
+    #[error("Present mode {0:?} is not supported by the surface.")]
+    UnsupportedPresentMode(wgpu::PresentMode),
+    #[error("The surface is not compatible with the adapter.")]
+    IncompatibleSurface,
     #[error("Image atlas has no more space.")]
     AtlasFull,
+    #[error("No AtlasGroup {0} to upload into.")]
+    MissingAtlasGroup(usize),
+    #[error("Packed atlas can not be loaded: {0}.")]
+    PackedAtlas(String),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Packer(#[from] packer::PackerError),
+    #[error("No compatible graphics adapter was found.")]
+    AdapterNotFound,
     #[error(transparent)]
     LyonTessellation(#[from] lyon::lyon_tessellation::TessellationError),
     #[error(transparent)]
+    Shader(#[from] ShaderError),
+    #[error(transparent)]
     Other(#[from] OtherError),
 }
@@ -1,12 +1,14 @@
 use crate::{
-    BufferLayout, GpuDevice, LayoutStorage, PipeLineLayout, StaticBufferObject,
-    SystemLayout, TextVertex, TextureLayout,
+    AlphaPass, BufferLayout, GpuDevice, LayoutStorage, PipeLineLayout,
+    ShaderFile, StaticBufferObject, SystemLayout, TextVertex, TextureLayout,
 };
 use bytemuck::{Pod, Zeroable};
 
 #[repr(C)]
 #[derive(Clone, Copy, Hash, Pod, Zeroable)]
-pub struct TextRenderPipeline;
+pub struct TextRenderPipeline {
+    pub pass: AlphaPass,
+}
 
 impl PipeLineLayout for TextRenderPipeline {
     fn create_layout(
@@ -14,14 +16,14 @@ impl PipeLineLayout for TextRenderPipeline {
         gpu_device: &mut GpuDevice,
         layouts: &mut LayoutStorage,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
     ) -> wgpu::RenderPipeline {
-        let shader = gpu_device.device().create_shader_module(
-            wgpu::ShaderModuleDescriptor {
-                label: Some("Shader"),
-                source: wgpu::ShaderSource::Wgsl(
-                    include_str!("../shaders/textshader.wgsl").into(),
-                ),
-            },
+        let shader = gpu_device.create_shader_module(
+            "Shader",
+            &[ShaderFile::new(
+                "textshader.wgsl",
+                include_str!("../shaders/textshader.wgsl"),
+            )],
         );
 
         let system_layout = layouts.create_layout(gpu_device, SystemLayout);
@@ -69,14 +71,11 @@ impl PipeLineLayout for TextRenderPipeline {
                     polygon_mode: wgpu::PolygonMode::Fill,
                     conservative: false,
                 },
-                depth_stencil: Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth32Float,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::LessEqual,
-                    stencil: wgpu::StencilState::default(),
-                    bias: wgpu::DepthBiasState::default(),
-                }),
-                multisample: wgpu::MultisampleState::default(),
+                depth_stencil: Some(self.pass.depth_stencil()),
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    ..Default::default()
+                },
                 fragment: Some(wgpu::FragmentState {
                     module: &shader,
                     entry_point: "fragment",
@@ -1,9 +1,10 @@
 use crate::{
     AsBufferPass, AscendingError, AtlasGroup, GpuRenderer, InstanceBuffer,
-    OrderedIndex, SetBuffers, StaticBufferObject, Text, TextRenderPipeline,
+    OrderedIndex, SetBuffers, SetClip, SortAxis, Text, TextRenderPipeline,
     TextVertex, Vec2,
 };
 use cosmic_text::{CacheKey, SwashCache};
+use winit::dpi::PhysicalSize;
 
 pub struct TextAtlas {
     pub(crate) text: AtlasGroup<CacheKey, Vec2>,
@@ -25,6 +26,16 @@ impl TextAtlas {
         self.emoji.trim();
         self.text.trim();
     }
+
+    /// Creates the atlases on a new device after the old one was lost.
+    /// The glyphs are dropped and rasterized again as each Text is
+    /// updated, so set_change(true) on every Text.
+    pub fn recreate(&mut self, renderer: &mut GpuRenderer) {
+        self.text.recreate(renderer);
+        self.emoji.recreate(renderer);
+        self.text.clear();
+        self.emoji.clear();
+    }
 }
 
 pub struct TextRenderer {
@@ -40,6 +51,11 @@ impl TextRenderer {
         })
     }
 
+    /// Sets which position axis Text is sorted on first.
+    pub fn set_sort_axis(&mut self, axis: SortAxis) {
+        self.buffer.sort_axis = axis;
+    }
+
     pub fn add_buffer_store(
         &mut self,
         renderer: &GpuRenderer,
@@ -52,6 +68,11 @@ impl TextRenderer {
         self.buffer.finalize(renderer)
     }
 
+    /// Creates the buffers on a new device after the old one was lost.
+    pub fn recreate(&mut self, renderer: &GpuRenderer) {
+        self.buffer.recreate(renderer.gpu_device());
+    }
+
     pub fn text_update(
         &mut self,
         text: &mut Text,
@@ -69,11 +90,14 @@ pub trait RenderText<'a, 'b>
 where
     'b: 'a,
 {
+    /// target is the size of the attachment the pass draws into, which
+    /// clips are kept within.
     fn render_text(
         &mut self,
         renderer: &'b GpuRenderer,
         buffer: &'b TextRenderer,
         atlas: &'b TextAtlas,
+        target: PhysicalSize<f32>,
     );
 }
 
@@ -86,19 +110,18 @@ where
         renderer: &'b GpuRenderer,
         buffer: &'b TextRenderer,
         atlas: &'b TextAtlas,
+        target: PhysicalSize<f32>,
     ) {
         if buffer.buffer.count() > 0 {
             self.set_buffers(renderer.buffer_object.as_buffer_pass());
             self.set_bind_group(1, &atlas.text.texture.bind_group, &[]);
             self.set_bind_group(2, &atlas.emoji.texture.bind_group, &[]);
             self.set_vertex_buffer(1, buffer.buffer.instances(None));
-            self.set_pipeline(
-                renderer.get_pipelines(TextRenderPipeline).unwrap(),
-            );
-            self.draw_indexed(
-                0..StaticBufferObject::index_count(),
-                0,
-                0..buffer.buffer.count(),
+            self.draw_alpha_instances(
+                renderer,
+                &buffer.buffer,
+                target,
+                |pass| TextRenderPipeline { pass },
             );
         }
     }
@@ -1,6 +1,6 @@
 use crate::{
     AscendingError, Bounds, Color, DrawOrder, GpuRenderer, Index, OrderedIndex,
-    TextAtlas, TextVertex, Vec2, Vec3,
+    TextAtlas, TextVertex, Vec2, Vec3, WorldBounds,
 };
 use cosmic_text::{
     Attrs, Buffer, Cursor, Metrics, SwashCache, SwashContent, Wrap,
@@ -12,6 +12,7 @@ pub struct Text {
     pub size: Vec2,
     pub offsets: Vec2,
     pub default_color: Color,
+    /// Area of the screen the Text is clipped to.
     pub bounds: Option<Bounds>,
     pub store_id: Index,
     pub order: DrawOrder,
@@ -25,6 +26,8 @@ pub struct Text {
     pub wrap: Wrap,
     /// if the shader should render with the camera's view.
     pub use_camera: bool,
+    /// Area of the glyphs drawn. Used to skip Text that is not on screen.
+    pub(crate) glyph_bounds: Option<WorldBounds>,
     /// if anything got updated we need to update the buffers too.
     pub changed: bool,
 }
@@ -39,6 +42,10 @@ impl Text {
         let count: usize =
             self.buffer.lines.iter().map(|line| line.text().len()).sum();
         let mut text_buf = Vec::with_capacity(count);
+        // The lowest and highest corners of the glyphs drawn.
+        let mut glyph_area: Option<(Vec2, Vec2)> = None;
+        // Drawn with the translucent objects if any glyph color is.
+        let mut translucent = false;
 
         for run in self.buffer.layout_runs() {
             for glyph in run.glyphs.iter() {
@@ -112,10 +119,10 @@ impl Text {
 
                 let position = allocation.data;
                 let (u, v, width, height) = allocation.rect();
-                let (mut u, mut v, mut width, mut height) =
+                let (u, v, width, height) =
                     (u as f32, v as f32, width as f32, height as f32);
 
-                let (mut x, mut y) = (
+                let (x, y) = (
                     (self.pos.x
                         + self.offsets.x
                         + physical_glyph.x as f32
@@ -134,55 +141,15 @@ impl Text {
                         None => self.default_color,
                     });
 
-                let screensize = renderer.size();
+                translucent |= color.a() < 255;
 
-                if let Some(bounds) = self.bounds {
-                    //Bounds used from Glyphon
-                    let bounds_min_x = bounds.left.max(0.0);
-                    let bounds_min_y = bounds.bottom.max(0.0);
-                    let bounds_max_x = bounds.right.min(screensize.width);
-                    let bounds_max_y = bounds.top.min(screensize.height);
+                let (min, max) =
+                    (Vec2::new(x, y), Vec2::new(x + width, y + height));
 
-                    // Starts beyond right edge or ends beyond left edge
-                    let max_x = x + width;
-                    if x > bounds_max_x || max_x < bounds_min_x {
-                        continue;
-                    }
-
-                    // Starts beyond bottom edge or ends beyond top edge
-                    let max_y = y + height;
-                    if y > bounds_max_y || max_y < bounds_min_y {
-                        continue;
-                    }
-
-                    // Clip left edge
-                    if x < bounds_min_x {
-                        let right_shift = bounds_min_x - x;
-
-                        x = bounds_min_x;
-                        width = max_x - bounds_min_x;
-                        u += right_shift;
-                    }
-
-                    // Clip right edge
-                    if x + width > bounds_max_x {
-                        width = bounds_max_x - x;
-                    }
-
-                    // Clip top edge
-                    if y < bounds_min_y {
-                        height -= bounds_min_y;
-                        y = bounds_min_y;
-                    }
-
-                    // Clip top edge
-                    if y + height > bounds_max_y {
-                        let bottom_shift = (y + height) - bounds_max_y;
-
-                        v += bottom_shift;
-                        height -= bottom_shift;
-                    }
-                }
+                glyph_area = Some(match glyph_area {
+                    Some((low, high)) => (low.min(min), high.max(max)),
+                    None => (min, max),
+                });
 
                 let default = TextVertex {
                     position: [x, y, self.pos.z],
@@ -203,7 +170,10 @@ impl Text {
             store.changed = true;
         }
 
-        self.order = DrawOrder::new(false, &self.pos, 1);
+        self.glyph_bounds = glyph_area.map(|(min, max)| {
+            WorldBounds::new(min.x, min.y, max.x, max.y, max.y - min.y)
+        });
+        self.order = DrawOrder::new(translucent, &self.pos, 1);
         self.changed = false;
         self.buffer.set_redraw(false);
         Ok(())
@@ -226,6 +196,7 @@ impl Text {
             bounds: None,
             store_id: renderer.new_buffer(),
             order: DrawOrder::new(false, &pos, 1),
+            glyph_bounds: None,
             changed: true,
             default_color: Color::rgba(0, 0, 0, 255),
             use_camera: false,
@@ -339,9 +310,9 @@ impl Text {
         self
     }
 
+    /// Clipping is done when drawing so the glyphs are not rebuilt.
     pub fn set_bounds(&mut self, bounds: Option<Bounds>) -> &mut Self {
         self.bounds = bounds;
-        self.changed = true;
         self
     }
 
@@ -402,7 +373,19 @@ impl Text {
             self.create_quad(cache, atlas, renderer)?;
         }
 
-        Ok(OrderedIndex::new(self.order, self.store_id, 0))
+        let index = OrderedIndex::new(self.order, self.store_id, 0)
+            .with_clip(self.bounds);
+
+        Ok(match self.glyph_bounds {
+            Some(bounds) => index.with_bounds(bounds, self.use_camera),
+            None => index,
+        })
+    }
+
+    /// The area the glyphs were last drawn within. None until the text is
+    /// updated or when it has no glyphs.
+    pub fn world_bounds(&self) -> Option<WorldBounds> {
+        self.glyph_bounds
     }
 
     pub fn check_mouse_bounds(&self, mouse_pos: Vec2) -> bool {
@@ -1,9 +1,11 @@
 mod image;
+mod material;
 mod pipeline;
 mod render;
 mod vertex;
 
 pub use self::image::*;
+pub use material::*;
 pub use pipeline::*;
 pub use render::*;
 pub use vertex::*;
@@ -1,6 +1,6 @@
 use crate::{
     Allocation, Color, DrawOrder, GpuRenderer, ImageVertex, Index,
-    OrderedIndex, Vec2, Vec3, Vec4,
+    OrderedIndex, TextureHandle, Vec2, Vec3, Vec4, WorldBounds,
 };
 
 /// rendering data for all images.
@@ -21,9 +21,15 @@ pub struct Image {
     pub use_camera: bool,
     /// Texture area location in Atlas.
     pub texture: Option<Allocation>,
+    /// When set texture is taken from it on each update so the Image
+    /// follows the allocation if it changes.
+    pub handle: Option<TextureHandle>,
     pub store_id: Index,
     pub order: DrawOrder,
     pub render_layer: u32,
+    /// Index of the Material in the ImageRenderer to draw with.
+    /// None uses the default Image shader.
+    pub material: Option<usize>,
     /// if anything got updated we need to update the buffers too.
     pub changed: bool,
 }
@@ -44,12 +50,28 @@ impl Image {
             use_camera: true,
             color: Color::rgba(255, 255, 255, 255),
             texture,
+            handle: None,
             store_id: renderer.new_buffer(),
             order: DrawOrder::default(),
             render_layer,
+            material: None,
             changed: true,
         }
     }
+
+    /// Creates the Image with the handle's texture. Holding the handle
+    /// keeps the texture pinned in its atlas.
+    pub fn from_handle(
+        handle: TextureHandle,
+        renderer: &mut GpuRenderer,
+        render_layer: u32,
+    ) -> Self {
+        let mut image = Self::new(handle.allocation(), renderer, render_layer);
+
+        image.handle = Some(handle);
+        image
+    }
+
     pub fn create_quad(&mut self, renderer: &mut GpuRenderer) {
         let allocation = match &self.texture {
             Some(allocation) => allocation,
@@ -89,11 +111,32 @@ impl Image {
 
     /// used to check and update the vertex array.
     pub fn update(&mut self, renderer: &mut GpuRenderer) -> OrderedIndex {
+        if let Some(handle) = &self.handle {
+            let texture = handle.allocation();
+
+            if texture != self.texture {
+                self.texture = texture;
+                self.changed = true;
+            }
+        }
+
         // if pos or tex_pos or color changed.
         if self.changed {
             self.create_quad(renderer);
         }
 
         OrderedIndex::new(self.order, self.store_id, 0)
+            .with_bounds(self.world_bounds(), self.use_camera)
+    }
+
+    /// The area the Image is drawn within.
+    pub fn world_bounds(&self) -> WorldBounds {
+        WorldBounds::new(
+            self.pos.x,
+            self.pos.y,
+            self.pos.x + self.hw.x,
+            self.pos.y + self.hw.y,
+            self.hw.y,
+        )
     }
 }
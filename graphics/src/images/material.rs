@@ -0,0 +1,144 @@
+use crate::{
+    color_to_array, validate_wgsl, AlphaPass, AscendingError, Color, GpuDevice,
+    GpuRenderer, Layout, MaterialPipeline, ShaderFile, Vec4, IMAGE_SHADER,
+};
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+/// Parameters handed to a Material's shader at group 2.
+/// What params holds is up to each Material.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, Pod, Zeroable)]
+pub struct MaterialRaw {
+    pub params: [f32; 4],
+    pub color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct MaterialLayout;
+
+impl Layout for MaterialLayout {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+    ) -> wgpu::BindGroupLayout {
+        gpu_device.device().create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: Some("material_bind_group_layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            },
+        )
+    }
+}
+
+/// A custom fragment shader Images can be drawn with, like a dissolve or
+/// outline. Add it to an ImageRenderer and set Image::material to the
+/// returned index. params and color are uploaded on ImageRenderer::finalize.
+pub struct Material {
+    pub(crate) pipeline: MaterialPipeline,
+    pub(crate) bind_group: wgpu::BindGroup,
+    buffer: wgpu::Buffer,
+    pub params: Vec4,
+    pub color: Color,
+}
+
+impl Material {
+    /// Creates a Material from a WGSL fragment shader. It is joined after
+    /// the Image shader so it can use VertexOutput, image_color(), global
+    /// and material. name is used to hot reload it like any other shader.
+    pub fn new(
+        renderer: &mut GpuRenderer,
+        name: &str,
+        fragment_shader: &str,
+        params: Vec4,
+        color: Color,
+    ) -> Result<Self, AscendingError> {
+        validate_wgsl(&[IMAGE_SHADER, ShaderFile::new(name, fragment_shader)])?;
+
+        let id = renderer.device.shaders.add_file(name, fragment_shader);
+        let pipeline = MaterialPipeline {
+            id,
+            pass: AlphaPass::OPAQUE,
+        };
+
+        for pass in AlphaPass::ALL {
+            renderer.create_pipeline(MaterialPipeline { pass, ..pipeline });
+        }
+
+        let (buffer, bind_group) =
+            Self::create_bindings(renderer, params, color);
+
+        Ok(Self {
+            pipeline,
+            bind_group,
+            buffer,
+            params,
+            color,
+        })
+    }
+
+    /// Creates the uniform buffer and bind group on a new device after the
+    /// old one was lost. The pipelines are rebuilt by the GpuRenderer.
+    pub fn recreate(&mut self, renderer: &mut GpuRenderer) {
+        (self.buffer, self.bind_group) =
+            Self::create_bindings(renderer, self.params, self.color);
+    }
+
+    fn create_bindings(
+        renderer: &mut GpuRenderer,
+        params: Vec4,
+        color: Color,
+    ) -> (wgpu::Buffer, wgpu::BindGroup) {
+        let raw = MaterialRaw {
+            params: params.to_array(),
+            color: color_to_array(color),
+        };
+
+        let buffer = renderer.device().create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("material buffer"),
+                contents: bytemuck::bytes_of(&raw),
+                usage: wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST,
+            },
+        );
+
+        let layout = renderer.create_layout(MaterialLayout);
+        let bind_group =
+            renderer
+                .device()
+                .create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                    label: Some("material_bind_group"),
+                });
+
+        (buffer, bind_group)
+    }
+
+    pub(crate) fn update(&self, renderer: &GpuRenderer) {
+        let raw = MaterialRaw {
+            params: self.params.to_array(),
+            color: color_to_array(self.color),
+        };
+
+        renderer.queue().write_buffer(
+            &self.buffer,
+            0,
+            bytemuck::bytes_of(&raw),
+        );
+    }
+}
@@ -1,12 +1,22 @@
 use crate::{
-    BufferLayout, GpuDevice, ImageVertex, LayoutStorage, PipeLineLayout,
-    StaticBufferObject, SystemLayout, TextureLayout,
+    AlphaPass, BufferLayout, GpuDevice, ImageVertex, LayoutStorage,
+    MaterialLayout, PipeLineLayout, ShaderFile, StaticBufferObject,
+    SystemLayout, TextureLayout,
 };
 use bytemuck::{Pod, Zeroable};
 
+/// Bindings, the vertex shader and image_color() shared by every Image
+/// pipeline. Materials write the fragment shader that follows it.
+pub const IMAGE_SHADER: ShaderFile = ShaderFile::new(
+    "imageshader.wgsl",
+    include_str!("../shaders/imageshader.wgsl"),
+);
+
 #[repr(C)]
 #[derive(Clone, Copy, Hash, Pod, Zeroable)]
-pub struct ImageRenderPipeline;
+pub struct ImageRenderPipeline {
+    pub pass: AlphaPass,
+}
 
 impl PipeLineLayout for ImageRenderPipeline {
     fn create_layout(
@@ -14,76 +24,132 @@ impl PipeLineLayout for ImageRenderPipeline {
         gpu_device: &mut GpuDevice,
         layouts: &mut LayoutStorage,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
     ) -> wgpu::RenderPipeline {
-        let shader = gpu_device.device().create_shader_module(
-            wgpu::ShaderModuleDescriptor {
-                label: Some("Shader"),
-                source: wgpu::ShaderSource::Wgsl(
-                    include_str!("../shaders/imageshader.wgsl").into(),
-                ),
-            },
-        );
+        create_image_pipeline(
+            gpu_device,
+            layouts,
+            surface_format,
+            sample_count,
+            "Image render pipeline",
+            ShaderFile::new(
+                "imagefragmentshader.wgsl",
+                include_str!("../shaders/imagefragmentshader.wgsl"),
+            ),
+            &[],
+            self.pass,
+        )
+    }
+}
 
-        let system_layout = layouts.create_layout(gpu_device, SystemLayout);
-        let texture_layout = layouts.create_layout(gpu_device, TextureLayout);
+/// Pipeline of a Material. id is the index of its fragment shader
+/// within the renderer's runtime shader files.
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct MaterialPipeline {
+    pub(crate) id: u32,
+    pub(crate) pass: AlphaPass,
+}
 
-        // Create the render pipeline.
-        gpu_device.device().create_render_pipeline(
-            &wgpu::RenderPipelineDescriptor {
-                label: Some("Image render pipeline"),
-                layout: Some(&gpu_device.device().create_pipeline_layout(
-                    &wgpu::PipelineLayoutDescriptor {
-                        label: Some("render_pipeline_layout"),
-                        bind_group_layouts: &[&system_layout, &texture_layout],
-                        push_constant_ranges: &[],
-                    },
-                )),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: "vertex",
-                    buffers: &[
-                        wgpu::VertexBufferLayout {
-                            array_stride: StaticBufferObject::stride(),
-                            step_mode: wgpu::VertexStepMode::Vertex,
-                            attributes: &[
-                                StaticBufferObject::vertex_attribute(),
-                            ],
-                        },
-                        wgpu::VertexBufferLayout {
-                            array_stride: ImageVertex::stride() as u64,
-                            step_mode: wgpu::VertexStepMode::Instance,
-                            attributes: &ImageVertex::attributes(),
-                        },
-                    ],
-                },
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: None,
-                    unclipped_depth: false,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    conservative: false,
-                },
-                depth_stencil: Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth32Float,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::LessEqual,
-                    stencil: wgpu::StencilState::default(),
-                    bias: wgpu::DepthBiasState::default(),
-                }),
-                multisample: wgpu::MultisampleState::default(),
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: "fragment",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: surface_format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-                multiview: None,
-            },
+impl PipeLineLayout for MaterialPipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        layouts: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        let (name, source) = gpu_device.shaders.files[self.id as usize].clone();
+        let material_layout = layouts.create_layout(gpu_device, MaterialLayout);
+
+        create_image_pipeline(
+            gpu_device,
+            layouts,
+            surface_format,
+            sample_count,
+            "Material render pipeline",
+            ShaderFile::new(&name, &source),
+            &[&material_layout],
+            self.pass,
         )
     }
 }
+
+/// Creates a pipeline drawing ImageVertex instances with IMAGE_SHADER
+/// joined before fragment_shader. Groups 0 and 1 are the System and the
+/// atlas. Anything in extra_layouts is bound from group 2 on.
+#[allow(clippy::too_many_arguments)]
+pub fn create_image_pipeline(
+    gpu_device: &mut GpuDevice,
+    layouts: &mut LayoutStorage,
+    surface_format: wgpu::TextureFormat,
+    sample_count: u32,
+    label: &str,
+    fragment_shader: ShaderFile,
+    extra_layouts: &[&wgpu::BindGroupLayout],
+    pass: AlphaPass,
+) -> wgpu::RenderPipeline {
+    let shader = gpu_device
+        .create_shader_module(label, &[IMAGE_SHADER, fragment_shader]);
+
+    let system_layout = layouts.create_layout(gpu_device, SystemLayout);
+    let texture_layout = layouts.create_layout(gpu_device, TextureLayout);
+
+    let mut bind_group_layouts: Vec<&wgpu::BindGroupLayout> =
+        vec![&system_layout, &texture_layout];
+    bind_group_layouts.extend_from_slice(extra_layouts);
+
+    // Create the render pipeline.
+    gpu_device.device().create_render_pipeline(
+        &wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&gpu_device.device().create_pipeline_layout(
+                &wgpu::PipelineLayoutDescriptor {
+                    label: Some("render_pipeline_layout"),
+                    bind_group_layouts: &bind_group_layouts,
+                    push_constant_ranges: &[],
+                },
+            )),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vertex",
+                buffers: &[
+                    wgpu::VertexBufferLayout {
+                        array_stride: StaticBufferObject::stride(),
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &[StaticBufferObject::vertex_attribute()],
+                    },
+                    wgpu::VertexBufferLayout {
+                        array_stride: ImageVertex::stride() as u64,
+                        step_mode: wgpu::VertexStepMode::Instance,
+                        attributes: &ImageVertex::attributes(),
+                    },
+                ],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: Some(pass.depth_stencil()),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fragment",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        },
+    )
+}
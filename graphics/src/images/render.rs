@@ -1,29 +1,120 @@
 use crate::{
-    AscendingError, AtlasGroup, GpuRenderer, Image, ImageRenderPipeline,
-    ImageVertex, InstanceBuffer, OrderedIndex, StaticBufferObject,
+    AlphaPass, AscendingError, AtlasGroup, Bounds, BufferLayout, GpuRenderer,
+    Image, ImageRenderPipeline, ImageVertex, InstanceBuffer, Material,
+    MaterialPipeline, OrderedIndex, RenderTarget, SetClip, SortAxis,
+    StaticBufferObject, TextureGroup,
 };
+use std::ops::Range;
+use winit::dpi::PhysicalSize;
+
+/// The Material, AlphaPass and clip a range of instances is drawn with.
+type ImageBatch = (Option<usize>, AlphaPass, Option<Bounds>, Range<u32>);
 
 pub struct ImageRenderer {
     pub buffer: InstanceBuffer<ImageVertex>,
+    pub materials: Vec<Material>,
+    /// Images added this frame along with their Material.
+    images: Vec<(OrderedIndex, Option<usize>)>,
+    /// Instances drawn with the same Material, AlphaPass and clip in
+    /// DrawOrder.
+    batches: Vec<ImageBatch>,
 }
 
 impl ImageRenderer {
     pub fn new(renderer: &GpuRenderer) -> Result<Self, AscendingError> {
         Ok(Self {
             buffer: InstanceBuffer::new(renderer.gpu_device()),
+            materials: Vec::new(),
+            images: Vec::new(),
+            batches: Vec::new(),
         })
     }
 
+    /// Sets which position axis Images are sorted on first.
+    pub fn set_sort_axis(&mut self, axis: SortAxis) {
+        self.buffer.sort_axis = axis;
+    }
+
+    /// Adds a Material Images can use and returns its index.
+    pub fn add_material(&mut self, material: Material) -> usize {
+        self.materials.push(material);
+        self.materials.len() - 1
+    }
+
     pub fn add_buffer_store(
         &mut self,
         renderer: &GpuRenderer,
         index: OrderedIndex,
     ) {
-        self.buffer.add_buffer_store(renderer, index);
+        self.add_material_buffer_store(renderer, index, None);
+    }
+
+    /// Adds the buffer store to be drawn with the Material at material.
+    pub fn add_material_buffer_store(
+        &mut self,
+        renderer: &GpuRenderer,
+        mut index: OrderedIndex,
+        material: Option<usize>,
+    ) {
+        if renderer.get_buffer(&index.index).is_some()
+            && renderer.is_visible(&index)
+        {
+            renderer.clip_index(&mut index);
+            self.images.push((index, material));
+        }
     }
 
     pub fn finalize(&mut self, renderer: &mut GpuRenderer) {
-        self.buffer.finalize(renderer)
+        // Sorted the same way the InstanceBuffer sorts them so each run of
+        // Images using the same Material and clip can be drawn at once.
+        let axis = self.buffer.sort_axis;
+
+        self.images.sort_by(|(a, _), (b, _)| a.cmp_axis(b, axis));
+        self.batches.clear();
+
+        let mut start = 0;
+
+        for (index, material) in self.images.drain(..) {
+            let count = renderer
+                .get_buffer(&index.index)
+                .map(|store| (store.store.len() / ImageVertex::stride()) as u32)
+                .unwrap_or(0);
+
+            let pass = AlphaPass::new(index.order.alpha);
+
+            match self.batches.last_mut() {
+                Some((last, last_pass, clip, range))
+                    if *last == material
+                        && *last_pass == pass
+                        && *clip == index.clip =>
+                {
+                    range.end += count;
+                }
+                _ => self.batches.push((
+                    material,
+                    pass,
+                    index.clip,
+                    start..start + count,
+                )),
+            }
+
+            start += count;
+            self.buffer.add_index(renderer, index);
+        }
+
+        self.buffer.finalize(renderer);
+        self.materials
+            .iter()
+            .for_each(|material| material.update(renderer));
+    }
+
+    /// Creates the buffers and Materials on a new device after the old one
+    /// was lost.
+    pub fn recreate(&mut self, renderer: &mut GpuRenderer) {
+        self.buffer.recreate(renderer.gpu_device());
+        self.materials
+            .iter_mut()
+            .for_each(|material| material.recreate(renderer));
     }
 
     pub fn image_update(
@@ -33,7 +124,7 @@ impl ImageRenderer {
     ) {
         let index = image.update(renderer);
 
-        self.add_buffer_store(renderer, index);
+        self.add_material_buffer_store(renderer, index, image.material);
     }
 }
 
@@ -41,11 +132,23 @@ pub trait RenderImage<'a, 'b>
 where
     'b: 'a,
 {
+    /// target is the size of the attachment the pass draws into, which
+    /// clips are kept within.
     fn render_image(
         &mut self,
         renderer: &'b GpuRenderer,
         buffer: &'b ImageRenderer,
         atlas: &'b AtlasGroup,
+        target: PhysicalSize<f32>,
+    );
+
+    /// Renders the Images sampling from a RenderTarget instead of an Atlas.
+    fn render_image_target(
+        &mut self,
+        renderer: &'b GpuRenderer,
+        buffer: &'b ImageRenderer,
+        source: &'b RenderTarget,
+        target: PhysicalSize<f32>,
     );
 }
 
@@ -58,19 +161,68 @@ where
         renderer: &'b GpuRenderer,
         buffer: &'b ImageRenderer,
         atlas: &'b AtlasGroup,
+        target: PhysicalSize<f32>,
+    ) {
+        draw_images(self, renderer, buffer, &atlas.texture, target);
+    }
+
+    fn render_image_target(
+        &mut self,
+        renderer: &'b GpuRenderer,
+        buffer: &'b ImageRenderer,
+        source: &'b RenderTarget,
+        target: PhysicalSize<f32>,
     ) {
-        if buffer.buffer.count() > 0 {
-            self.set_bind_group(1, &atlas.texture.bind_group, &[]);
-            self.set_vertex_buffer(1, buffer.buffer.instances(None));
-            self.set_pipeline(
-                renderer.get_pipelines(ImageRenderPipeline).unwrap(),
-            );
-
-            self.draw_indexed(
-                0..StaticBufferObject::index_count(),
-                0,
-                0..buffer.buffer.count(),
-            );
+        draw_images(self, renderer, buffer, &source.texture_group, target);
+    }
+}
+
+fn draw_images<'a, 'b>(
+    pass: &mut wgpu::RenderPass<'a>,
+    renderer: &'b GpuRenderer,
+    buffer: &'b ImageRenderer,
+    texture: &'b TextureGroup,
+    target: PhysicalSize<f32>,
+) where
+    'b: 'a,
+{
+    if buffer.buffer.count() > 0 {
+        pass.set_bind_group(1, &texture.bind_group, &[]);
+        pass.set_vertex_buffer(1, buffer.buffer.instances(None));
+
+        for (material, alpha, clip, range) in &buffer.batches {
+            if clip.is_some() && !pass.set_clip(renderer, clip.as_ref(), target)
+            {
+                continue;
+            }
+
+            let material = material.and_then(|id| buffer.materials.get(id));
+            let pipeline = match material {
+                Some(material) => {
+                    pass.set_bind_group(2, &material.bind_group, &[]);
+                    renderer.get_pipelines(MaterialPipeline {
+                        pass: *alpha,
+                        ..material.pipeline
+                    })
+                }
+                None => {
+                    renderer.get_pipelines(ImageRenderPipeline { pass: *alpha })
+                }
+            };
+
+            if let Some(pipeline) = pipeline {
+                pass.set_pipeline(pipeline);
+                pass.draw_indexed(
+                    0..StaticBufferObject::index_count(),
+                    0,
+                    range.clone(),
+                );
+                renderer.record_stats(|stats| stats.draw_calls += 1);
+            }
+
+            if clip.is_some() {
+                pass.set_clip(renderer, None, target);
+            }
         }
     }
 }
@@ -4,8 +4,10 @@ mod error;
 mod font;
 mod images;
 mod lights;
+mod loader;
 mod maps;
 mod mesh2d;
+mod postprocess;
 mod systems;
 mod textures;
 mod tilesheet;
@@ -16,8 +18,10 @@ pub use error::*;
 pub use font::*;
 pub use images::*;
 pub use lights::*;
+pub use loader::*;
 pub use maps::*;
 pub use mesh2d::*;
+pub use postprocess::*;
 pub use systems::*;
 pub use textures::*;
 pub use tilesheet::*;
@@ -1,6 +1,6 @@
 use crate::{
     AreaLightLayout, BufferLayout, DirLightLayout, GpuDevice, LayoutStorage,
-    LightsVertex, PipeLineLayout, StaticBufferObject, SystemLayout,
+    LightsVertex, PipeLineLayout, ShaderFile, StaticBufferObject, SystemLayout,
 };
 use bytemuck::{Pod, Zeroable};
 
@@ -14,14 +14,14 @@ impl PipeLineLayout for LightRenderPipeline {
         gpu_device: &mut GpuDevice,
         layouts: &mut LayoutStorage,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
     ) -> wgpu::RenderPipeline {
-        let shader = gpu_device.device().create_shader_module(
-            wgpu::ShaderModuleDescriptor {
-                label: Some("Shader"),
-                source: wgpu::ShaderSource::Wgsl(
-                    include_str!("../shaders/lightshader.wgsl").into(),
-                ),
-            },
+        let shader = gpu_device.create_shader_module(
+            "Shader",
+            &[ShaderFile::new(
+                "lightshader.wgsl",
+                include_str!("../shaders/lightshader.wgsl"),
+            )],
         );
 
         let system_layout = layouts.create_layout(gpu_device, SystemLayout);
@@ -78,7 +78,10 @@ impl PipeLineLayout for LightRenderPipeline {
                     stencil: wgpu::StencilState::default(),
                     bias: wgpu::DepthBiasState::default(),
                 }),
-                multisample: wgpu::MultisampleState::default(),
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    ..Default::default()
+                },
                 fragment: Some(wgpu::FragmentState {
                     module: &shader,
                     entry_point: "fragment",
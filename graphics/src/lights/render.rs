@@ -1,9 +1,10 @@
 use std::{iter, mem};
+use winit::dpi::PhysicalSize;
 
 use crate::{
     AreaLightLayout, AreaLightRaw, AscendingError, DirLightLayout,
     DirectionalLightRaw, GpuRenderer, InstanceBuffer, LightRenderPipeline,
-    Lights, LightsVertex, OrderedIndex, StaticBufferObject, MAX_AREA_LIGHTS,
+    Lights, LightsVertex, OrderedIndex, SetClip, MAX_AREA_LIGHTS,
     MAX_DIR_LIGHTS,
 };
 
@@ -105,6 +106,29 @@ impl LightRenderer {
         self.buffer.finalize(renderer)
     }
 
+    /// Creates the buffers on a new device after the old one was lost.
+    /// Set areas_changed and directionals_changed on the Lights drawn with
+    /// it so they are uploaded again.
+    pub fn recreate(
+        &mut self,
+        renderer: &mut GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        let LightRenderer {
+            area_buffer,
+            dir_buffer,
+            area_bind_group,
+            dir_bind_group,
+            ..
+        } = Self::new(renderer)?;
+
+        self.area_buffer = area_buffer;
+        self.dir_buffer = dir_buffer;
+        self.area_bind_group = area_bind_group;
+        self.dir_bind_group = dir_bind_group;
+        self.buffer.recreate(renderer.gpu_device());
+        Ok(())
+    }
+
     pub fn lights_update(
         &mut self,
         lights: &mut Lights,
@@ -124,10 +148,13 @@ pub trait RenderLights<'a, 'b>
 where
     'b: 'a,
 {
+    /// target is the size of the attachment the pass draws into, which
+    /// clips are kept within.
     fn render_lights(
         &mut self,
         renderer: &'b GpuRenderer,
         buffer: &'b LightRenderer,
+        target: PhysicalSize<f32>,
     );
 }
 
@@ -139,6 +166,7 @@ where
         &mut self,
         renderer: &'b GpuRenderer,
         buffer: &'b LightRenderer,
+        target: PhysicalSize<f32>,
     ) {
         if buffer.buffer.count() > 0 {
             self.set_bind_group(1, &buffer.area_bind_group, &[]);
@@ -148,11 +176,7 @@ where
                 renderer.get_pipelines(LightRenderPipeline).unwrap(),
             );
 
-            self.draw_indexed(
-                0..StaticBufferObject::index_count(),
-                0,
-                0..buffer.buffer.count(),
-            );
+            self.draw_clipped_instances(renderer, &buffer.buffer.clips, target);
         }
     }
 }
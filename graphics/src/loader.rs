@@ -0,0 +1,341 @@
+use crate::{
+    Allocation, AscendingError, AtlasGroup, GpuRenderer, OtherError, Texture,
+    TileSheet,
+};
+use cosmic_text::fontdb;
+use std::{
+    collections::VecDeque,
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::PathBuf,
+    sync::{mpsc, Arc},
+};
+use tokio::{runtime::Handle, sync::watch};
+
+/// Where a loading asset is at.
+#[derive(Clone, Debug)]
+pub enum AssetState<T> {
+    /// Being decoded on a worker or waiting in the upload queue.
+    Loading,
+    Ready(T),
+    /// The message of the AscendingError it failed with.
+    Failed(String),
+}
+
+impl<T> AssetState<T> {
+    pub fn is_loading(&self) -> bool {
+        matches!(self, AssetState::Loading)
+    }
+}
+
+/// Returned right away by the AssetLoader and updated once the asset is
+/// uploaded or fails. Can be cloned and polled or awaited from any thread.
+#[derive(Clone, Debug)]
+pub struct AssetHandle<T> {
+    state: watch::Receiver<AssetState<T>>,
+}
+
+impl<T: Clone> AssetHandle<T> {
+    pub fn state(&self) -> AssetState<T> {
+        self.state.borrow().clone()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.state.borrow().is_loading()
+    }
+
+    /// The asset once it is Ready.
+    pub fn get(&self) -> Option<T> {
+        match &*self.state.borrow() {
+            AssetState::Ready(asset) => Some(asset.clone()),
+            _ => None,
+        }
+    }
+
+    /// Waits until the asset is Ready or Failed. AssetLoader::upload must
+    /// keep being called for this to finish. Returns Loading if the
+    /// AssetLoader is dropped first.
+    pub async fn ready(&mut self) -> AssetState<T> {
+        let _ = self.state.wait_for(|state| !state.is_loading()).await;
+        self.state()
+    }
+}
+
+/// A decoded asset sent back by a worker.
+enum Loaded {
+    Texture {
+        texture: Result<Texture, AscendingError>,
+        group: usize,
+        state: watch::Sender<AssetState<Allocation>>,
+    },
+    TileSheet {
+        texture: Result<Texture, AscendingError>,
+        group: usize,
+        tilesize: u32,
+        state: watch::Sender<AssetState<Arc<TileSheet>>>,
+    },
+    Font {
+        faces: Result<Vec<fontdb::FaceInfo>, AscendingError>,
+        state: watch::Sender<AssetState<()>>,
+    },
+}
+
+impl Loaded {
+    /// The bytes the upload writes to the GPU.
+    fn upload_size(&self) -> usize {
+        match self {
+            Loaded::Texture {
+                texture: Ok(texture),
+                ..
+            }
+            | Loaded::TileSheet {
+                texture: Ok(texture),
+                ..
+            } => texture.bytes.len(),
+            _ => 0,
+        }
+    }
+}
+
+/// Decodes images and parses font files on tokio's blocking workers so
+/// the main thread does not hitch, then uploads them in upload, which is
+/// limited to upload_budget bytes per call.
+/// Textures go into the AtlasGroup at the index given when loading.
+/// The faces of fonts are added to the renderer's FontSystem when
+/// uploaded. Fonts are only parsed here, text using them is still shaped
+/// on the main thread by Text.
+pub struct AssetLoader {
+    runtime: Handle,
+    sender: mpsc::Sender<Loaded>,
+    receiver: mpsc::Receiver<Loaded>,
+    queue: VecDeque<Loaded>,
+    loading: usize,
+    /// Bytes written to the GPU per upload call. At least one asset is
+    /// uploaded each call even if it is larger.
+    pub upload_budget: usize,
+}
+
+impl AssetLoader {
+    pub fn new(runtime: Handle, upload_budget: usize) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            runtime,
+            sender,
+            receiver,
+            queue: VecDeque::new(),
+            loading: 0,
+            upload_budget,
+        }
+    }
+
+    /// Loads the image at path into atlases[group] of upload.
+    pub fn load_texture(
+        &mut self,
+        path: impl Into<PathBuf>,
+        group: usize,
+    ) -> AssetHandle<Allocation> {
+        let path = path.into();
+
+        self.spawn(
+            move || Texture::from_file(path),
+            move |texture, state| Loaded::Texture {
+                texture,
+                group,
+                state,
+            },
+        )
+    }
+
+    /// Loads the image at path and splits it into tiles of tilesize in
+    /// atlases[group] of upload.
+    pub fn load_tilesheet(
+        &mut self,
+        path: impl Into<PathBuf>,
+        group: usize,
+        tilesize: u32,
+    ) -> AssetHandle<Arc<TileSheet>> {
+        let path = path.into();
+
+        self.spawn(
+            move || Texture::from_file(path),
+            move |texture, state| Loaded::TileSheet {
+                texture,
+                group,
+                tilesize,
+                state,
+            },
+        )
+    }
+
+    /// Reads and parses the faces of the font file at path on a worker, then
+    /// adds them to the renderer's FontSystem in upload. No text is shaped.
+    /// Fails if the file has no faces.
+    pub fn load_font(&mut self, path: impl Into<PathBuf>) -> AssetHandle<()> {
+        let path = path.into();
+
+        self.spawn(
+            move || {
+                let mut database = fontdb::Database::new();
+
+                database.load_font_data(std::fs::read(&path)?);
+
+                if database.is_empty() {
+                    return Err(AscendingError::Other(OtherError::new(
+                        &format!("No fonts found in {}.", path.display()),
+                    )));
+                }
+
+                Ok(database.faces().cloned().collect())
+            },
+            |faces, state| Loaded::Font { faces, state },
+        )
+    }
+
+    /// Assets that are not Ready or Failed yet.
+    pub fn pending(&self) -> usize {
+        self.loading
+    }
+
+    /// Uploads the decoded assets waiting in the queue, oldest first, until
+    /// upload_budget bytes are written. Returns how many finished.
+    pub fn upload(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        atlases: &mut [AtlasGroup],
+    ) -> usize {
+        self.queue.extend(self.receiver.try_iter());
+
+        let mut written = 0;
+        let mut finished = 0;
+
+        while let Some(loaded) = self.queue.front() {
+            let size = loaded.upload_size();
+
+            if finished > 0 && written + size > self.upload_budget {
+                break;
+            }
+
+            if let Some(loaded) = self.queue.pop_front() {
+                Self::finish(loaded, renderer, atlases);
+            }
+
+            written += size;
+            finished += 1;
+        }
+
+        self.loading -= finished;
+        finished
+    }
+
+    /// Runs decode on a worker and sends what it returns back as the
+    /// Loaded made by loaded. A panicking decode fails the asset.
+    fn spawn<T: Clone + Send + Sync + 'static, D>(
+        &mut self,
+        decode: impl FnOnce() -> Result<D, AscendingError> + Send + 'static,
+        loaded: impl FnOnce(
+                Result<D, AscendingError>,
+                watch::Sender<AssetState<T>>,
+            ) -> Loaded
+            + Send
+            + 'static,
+    ) -> AssetHandle<T> {
+        let (state, receiver) = watch::channel(AssetState::Loading);
+        let sender = self.sender.clone();
+
+        self.loading += 1;
+        self.runtime.spawn_blocking(move || {
+            let result = catch_unwind(AssertUnwindSafe(decode)).unwrap_or_else(
+                |panic| {
+                    let message = panic
+                        .downcast_ref::<&str>()
+                        .copied()
+                        .or_else(|| {
+                            panic.downcast_ref::<String>().map(String::as_str)
+                        })
+                        .unwrap_or("unknown panic");
+
+                    Err(AscendingError::Other(OtherError::new(&format!(
+                        "Loading panicked: {message}"
+                    ))))
+                },
+            );
+
+            // The loader was dropped so nothing is waiting on it.
+            let _ = sender.send(loaded(result, state));
+        });
+
+        AssetHandle { state: receiver }
+    }
+
+    fn finish(
+        loaded: Loaded,
+        renderer: &mut GpuRenderer,
+        atlases: &mut [AtlasGroup],
+    ) {
+        match loaded {
+            Loaded::Texture {
+                texture,
+                group,
+                state,
+            } => {
+                let result = texture.and_then(|texture| {
+                    texture
+                        .group_upload(atlas_group(atlases, group)?, renderer)
+                        .ok_or(AscendingError::AtlasFull)
+                });
+
+                send_result(&state, result);
+            }
+            Loaded::TileSheet {
+                texture,
+                group,
+                tilesize,
+                state,
+            } => {
+                let result = texture.and_then(|texture| {
+                    texture
+                        .new_tilesheet(
+                            atlas_group(atlases, group)?,
+                            renderer,
+                            tilesize,
+                        )
+                        .map(Arc::new)
+                        .ok_or(AscendingError::AtlasFull)
+                });
+
+                send_result(&state, result);
+            }
+            Loaded::Font { faces, state } => {
+                let result = faces.map(|faces| {
+                    let database = renderer.font_sys_mut().db_mut();
+
+                    faces
+                        .into_iter()
+                        .for_each(|face| database.push_face_info(face));
+                });
+
+                send_result(&state, result);
+            }
+        }
+    }
+}
+
+fn atlas_group(
+    atlases: &mut [AtlasGroup],
+    group: usize,
+) -> Result<&mut AtlasGroup, AscendingError> {
+    atlases
+        .get_mut(group)
+        .ok_or(AscendingError::MissingAtlasGroup(group))
+}
+
+fn send_result<T>(
+    state: &watch::Sender<AssetState<T>>,
+    result: Result<T, AscendingError>,
+) {
+    // The handles may all be dropped which is fine.
+    let _ = state.send(match result {
+        Ok(asset) => AssetState::Ready(asset),
+        Err(error) => AssetState::Failed(error.to_string()),
+    });
+}
@@ -1,5 +1,6 @@
 use crate::{
     DrawOrder, GpuRenderer, Index, MapVertex, OrderedIndex, Vec2, Vec3,
+    WorldBounds,
 };
 use cosmic_text::Color;
 
@@ -65,6 +66,16 @@ impl Default for TileData {
     }
 }
 
+/// The indexs Map::update returns for the lower and upper layers, split
+/// into the opaque and translucent tiles of each.
+#[derive(Copy, Clone)]
+pub struct MapIndexes {
+    pub lower: OrderedIndex,
+    pub lower_translucent: OrderedIndex,
+    pub upper: OrderedIndex,
+    pub upper_translucent: OrderedIndex,
+}
+
 pub struct Map {
     /// X, Y, GroupID for loaded map.
     /// Add this to the higher up Map struct.
@@ -77,6 +88,9 @@ pub struct Map {
     pub lowerstore_id: Index,
     /// vertex array in bytes for fringe layers.
     pub upperstore_id: Index,
+    /// vertex arrays in bytes of the translucent tiles of each.
+    pub lower_alpha_store_id: Index,
+    pub upper_alpha_store_id: Index,
     /// the draw order of the maps. created when update is called.
     pub order: DrawOrder,
     /// count if any Filled Tiles Exist. this is to optimize out empty maps in rendering.
@@ -84,6 +98,9 @@ pub struct Map {
     // The size of the Tile to render. for spacing tiles out upon
     // vertex creation. Default will be 20.
     pub tilesize: u32,
+    /// Pixels around each tile in the atlas. Set it to the Atlas padding
+    /// when the tiles are in a mipmapped atlas.
+    pub padding: u32,
     // Used to deturmine if the map can be rendered or if its just a preload.
     pub can_render: bool,
     /// if the position or a tile gets changed.
@@ -94,6 +111,8 @@ impl Map {
     pub fn create_quad(&mut self, renderer: &mut GpuRenderer) {
         let mut lowerbuffer = Vec::new();
         let mut upperbuffer = Vec::new();
+        let mut lower_alphabuffer = Vec::new();
+        let mut upper_alphabuffer = Vec::new();
 
         for i in 0..8 {
             let z = MapLayers::indexed_layerz(i);
@@ -117,25 +136,33 @@ impl Map {
                         texture_id: tile.texture_id as f32,
                         texture_layer: tile.texture_layer as f32,
                         color: tile.color.0,
+                        padding: self.padding as f32,
                     };
 
-                    if i >= 6 {
-                        upperbuffer.push(map_vertex);
-                    } else {
-                        lowerbuffer.push(map_vertex);
-                    }
+                    let buffer = match (i >= 6, tile.color.a() < 255) {
+                        (true, false) => &mut upperbuffer,
+                        (true, true) => &mut upper_alphabuffer,
+                        (false, false) => &mut lowerbuffer,
+                        (false, true) => &mut lower_alphabuffer,
+                    };
+
+                    buffer.push(map_vertex);
                 }
             }
         }
 
-        if let Some(store) = renderer.get_buffer_mut(&self.lowerstore_id) {
-            store.store = bytemuck::cast_slice(&lowerbuffer).to_vec();
-            store.changed = true;
-        }
+        let stores = [
+            (self.lowerstore_id, lowerbuffer),
+            (self.upperstore_id, upperbuffer),
+            (self.lower_alpha_store_id, lower_alphabuffer),
+            (self.upper_alpha_store_id, upper_alphabuffer),
+        ];
 
-        if let Some(store) = renderer.get_buffer_mut(&self.upperstore_id) {
-            store.store = bytemuck::cast_slice(&upperbuffer).to_vec();
-            store.changed = true;
+        for (store_id, buffer) in stores {
+            if let Some(store) = renderer.get_buffer_mut(&store_id) {
+                store.store = bytemuck::cast_slice(&buffer).to_vec();
+                store.changed = true;
+            }
         }
 
         self.order =
@@ -149,9 +176,12 @@ impl Map {
             pos: Vec2::default(),
             lowerstore_id: renderer.new_buffer(),
             upperstore_id: renderer.new_buffer(),
+            lower_alpha_store_id: renderer.new_buffer(),
+            upper_alpha_store_id: renderer.new_buffer(),
             filled_tiles: [0; MapLayers::Count as usize],
             order: DrawOrder::default(),
             tilesize,
+            padding: 0,
             can_render: false,
             changed: true,
         }
@@ -190,20 +220,47 @@ impl Map {
         self.changed = true;
     }
 
+    /// The area the 32 by 32 tiles of the Map are drawn within.
+    pub fn world_bounds(&self) -> WorldBounds {
+        let size = (32 * self.tilesize) as f32;
+
+        WorldBounds::new(
+            self.pos.x,
+            self.pos.y,
+            self.pos.x + size,
+            self.pos.y + size,
+            size,
+        )
+    }
+
     /// used to check and update the vertex array or Texture witht he image buffer.
-    pub fn update(
-        &mut self,
-        renderer: &mut GpuRenderer,
-    ) -> Option<(OrderedIndex, OrderedIndex)> {
+    pub fn update(&mut self, renderer: &mut GpuRenderer) -> Option<MapIndexes> {
         if self.can_render {
             if self.changed {
                 self.create_quad(renderer);
             }
 
-            Some((
-                OrderedIndex::new(self.order, self.lowerstore_id, 0),
-                OrderedIndex::new(self.order, self.upperstore_id, 0),
-            ))
+            let bounds = self.world_bounds();
+            let alpha_order = DrawOrder {
+                alpha: true,
+                ..self.order
+            };
+            let index = |order, store_id| {
+                OrderedIndex::new(order, store_id, 0).with_bounds(bounds, true)
+            };
+
+            Some(MapIndexes {
+                lower: index(self.order, self.lowerstore_id),
+                lower_translucent: index(
+                    alpha_order,
+                    self.lower_alpha_store_id,
+                ),
+                upper: index(self.order, self.upperstore_id),
+                upper_translucent: index(
+                    alpha_order,
+                    self.upper_alpha_store_id,
+                ),
+            })
         } else {
             None
         }
@@ -1,7 +1,8 @@
 use crate::{
     AsBufferPass, AscendingError, AtlasGroup, GpuRenderer, InstanceBuffer, Map,
-    MapRenderPipeline, MapVertex, OrderedIndex, SetBuffers, StaticBufferObject,
+    MapIndexes, MapRenderPipeline, MapVertex, SetBuffers, SetClip, SortAxis,
 };
+use winit::dpi::PhysicalSize;
 
 pub struct MapRenderer {
     pub maplower_buffer: InstanceBuffer<MapVertex>,
@@ -25,13 +26,23 @@ impl MapRenderer {
         })
     }
 
+    /// Sets which position axis Maps are sorted on first.
+    pub fn set_sort_axis(&mut self, axis: SortAxis) {
+        self.maplower_buffer.sort_axis = axis;
+        self.mapupper_buffer.sort_axis = axis;
+    }
+
     pub fn add_buffer_store(
         &mut self,
         renderer: &GpuRenderer,
-        index: (OrderedIndex, OrderedIndex),
+        index: MapIndexes,
     ) {
-        self.maplower_buffer.add_buffer_store(renderer, index.0);
-        self.mapupper_buffer.add_buffer_store(renderer, index.1);
+        self.maplower_buffer.add_buffer_store(renderer, index.lower);
+        self.maplower_buffer
+            .add_buffer_store(renderer, index.lower_translucent);
+        self.mapupper_buffer.add_buffer_store(renderer, index.upper);
+        self.mapupper_buffer
+            .add_buffer_store(renderer, index.upper_translucent);
     }
 
     pub fn finalize(&mut self, renderer: &mut GpuRenderer) {
@@ -39,6 +50,12 @@ impl MapRenderer {
         self.mapupper_buffer.finalize(renderer);
     }
 
+    /// Creates the buffers on a new device after the old one was lost.
+    pub fn recreate(&mut self, renderer: &GpuRenderer) {
+        self.maplower_buffer.recreate(renderer.gpu_device());
+        self.mapupper_buffer.recreate(renderer.gpu_device());
+    }
+
     pub fn map_update(&mut self, map: &mut Map, renderer: &mut GpuRenderer) {
         if let Some(index) = map.update(renderer) {
             self.add_buffer_store(renderer, index);
@@ -50,11 +67,14 @@ pub trait RenderMap<'a, 'b>
 where
     'b: 'a,
 {
+    /// target is the size of the attachment the pass draws into, which
+    /// clips are kept within.
     fn render_lower_maps(
         &mut self,
         renderer: &'b GpuRenderer,
         buffer: &'b MapRenderer,
         atlas_group: &'b AtlasGroup,
+        target: PhysicalSize<f32>,
     );
 
     fn render_upper_maps(
@@ -62,6 +82,7 @@ where
         renderer: &'b GpuRenderer,
         buffer: &'b MapRenderer,
         atlas_group: &'b AtlasGroup,
+        target: PhysicalSize<f32>,
     );
 }
 
@@ -74,18 +95,17 @@ where
         renderer: &'b GpuRenderer,
         buffer: &'b MapRenderer,
         atlas_group: &'b AtlasGroup,
+        target: PhysicalSize<f32>,
     ) {
         if buffer.maplower_buffer.count() > 0 {
             self.set_buffers(renderer.buffer_object.as_buffer_pass());
             self.set_bind_group(1, &atlas_group.texture.bind_group, &[]);
             self.set_vertex_buffer(1, buffer.maplower_buffer.instances(None));
-            self.set_pipeline(
-                renderer.get_pipelines(MapRenderPipeline).unwrap(),
-            );
-            self.draw_indexed(
-                0..StaticBufferObject::index_count(),
-                0,
-                0..buffer.maplower_buffer.count(),
+            self.draw_alpha_instances(
+                renderer,
+                &buffer.maplower_buffer,
+                target,
+                |pass| MapRenderPipeline { pass },
             );
         }
     }
@@ -95,18 +115,17 @@ where
         renderer: &'b GpuRenderer,
         buffer: &'b MapRenderer,
         atlas_group: &'b AtlasGroup,
+        target: PhysicalSize<f32>,
     ) {
         if buffer.mapupper_buffer.count() > 0 {
             self.set_buffers(renderer.buffer_object.as_buffer_pass());
             self.set_bind_group(1, &atlas_group.texture.bind_group, &[]);
             self.set_vertex_buffer(1, buffer.mapupper_buffer.instances(None));
-            self.set_pipeline(
-                renderer.get_pipelines(MapRenderPipeline).unwrap(),
-            );
-            self.draw_indexed(
-                0..StaticBufferObject::index_count(),
-                0,
-                0..buffer.mapupper_buffer.count(),
+            self.draw_alpha_instances(
+                renderer,
+                &buffer.mapupper_buffer,
+                target,
+                |pass| MapRenderPipeline { pass },
             );
         }
     }
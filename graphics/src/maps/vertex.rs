@@ -10,6 +10,7 @@ pub struct MapVertex {
     pub texture_id: f32,
     pub texture_layer: f32,
     pub color: u32,
+    pub padding: f32,
 }
 
 impl Default for MapVertex {
@@ -20,13 +21,14 @@ impl Default for MapVertex {
             texture_id: 0.0,
             texture_layer: 0.0,
             color: 0,
+            padding: 0.0,
         }
     }
 }
 
 impl BufferLayout for MapVertex {
     fn attributes() -> Vec<wgpu::VertexAttribute> {
-        wgpu::vertex_attr_array![1 => Float32x3, 2 => Float32, 3 => Float32, 4 => Float32, 5 => Uint32]
+        wgpu::vertex_attr_array![1 => Float32x3, 2 => Float32, 3 => Float32, 4 => Float32, 5 => Uint32, 6 => Float32]
             .to_vec()
     }
 
@@ -50,6 +52,6 @@ impl BufferLayout for MapVertex {
     }
 
     fn stride() -> usize {
-        std::mem::size_of::<[f32; 7]>()
+        std::mem::size_of::<[f32; 8]>()
     }
 }
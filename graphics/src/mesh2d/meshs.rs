@@ -104,7 +104,12 @@ impl Mesh2D {
             store.changed = true;
         }
 
-        self.order = DrawOrder::new(false, &self.position, 1);
+        let translucent = self
+            .vertices
+            .iter()
+            .any(|vertex| Color(vertex.color).a() < 255);
+
+        self.order = DrawOrder::new(translucent, &self.position, 1);
     }
 
     // used to check and update the ShapeVertex array.
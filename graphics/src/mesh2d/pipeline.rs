@@ -1,12 +1,14 @@
 use crate::{
-    BufferLayout, GpuDevice, LayoutStorage, Mesh2DVertex, PipeLineLayout,
-    SystemLayout,
+    AlphaPass, BufferLayout, GpuDevice, LayoutStorage, Mesh2DVertex,
+    PipeLineLayout, ShaderFile, SystemLayout,
 };
 use bytemuck::{Pod, Zeroable};
 
 #[repr(C)]
 #[derive(Clone, Copy, Hash, Pod, Zeroable)]
-pub struct Mesh2DRenderPipeline;
+pub struct Mesh2DRenderPipeline {
+    pub pass: AlphaPass,
+}
 
 impl PipeLineLayout for Mesh2DRenderPipeline {
     fn create_layout(
@@ -14,14 +16,14 @@ impl PipeLineLayout for Mesh2DRenderPipeline {
         gpu_device: &mut GpuDevice,
         layouts: &mut LayoutStorage,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
     ) -> wgpu::RenderPipeline {
-        let shader = gpu_device.device().create_shader_module(
-            wgpu::ShaderModuleDescriptor {
-                label: Some("Shader"),
-                source: wgpu::ShaderSource::Wgsl(
-                    include_str!("../shaders/2dmeshshader.wgsl").into(),
-                ),
-            },
+        let shader = gpu_device.create_shader_module(
+            "Shader",
+            &[ShaderFile::new(
+                "2dmeshshader.wgsl",
+                include_str!("../shaders/2dmeshshader.wgsl"),
+            )],
         );
 
         let system_layout = layouts.create_layout(gpu_device, SystemLayout);
@@ -55,14 +57,11 @@ impl PipeLineLayout for Mesh2DRenderPipeline {
                     polygon_mode: wgpu::PolygonMode::Fill,
                     conservative: false,
                 },
-                depth_stencil: Some(wgpu::DepthStencilState {
-                    format: wgpu::TextureFormat::Depth32Float,
-                    depth_write_enabled: true,
-                    depth_compare: wgpu::CompareFunction::LessEqual,
-                    stencil: wgpu::StencilState::default(),
-                    bias: wgpu::DepthBiasState::default(),
-                }),
-                multisample: wgpu::MultisampleState::default(),
+                depth_stencil: Some(self.pass.depth_stencil()),
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    ..Default::default()
+                },
                 fragment: Some(wgpu::FragmentState {
                     module: &shader,
                     entry_point: "fragment",
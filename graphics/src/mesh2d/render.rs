@@ -1,7 +1,9 @@
 use crate::{
-    AsBufferPass, AscendingError, GpuBuffer, GpuRenderer, Mesh2D,
-    Mesh2DRenderPipeline, Mesh2DVertex, OrderedIndex, SetBuffers,
+    AlphaPass, AsBufferPass, AscendingError, GpuBuffer, GpuRenderer, Mesh2D,
+    Mesh2DRenderPipeline, Mesh2DVertex, OrderedIndex, SetBuffers, SetClip,
+    SortAxis,
 };
+use winit::dpi::PhysicalSize;
 
 pub struct Mesh2DRenderer {
     pub vbos: GpuBuffer<Mesh2DVertex>,
@@ -15,6 +17,11 @@ impl Mesh2DRenderer {
         })
     }
 
+    /// Sets which position axis Meshes are sorted on first.
+    pub fn set_sort_axis(&mut self, axis: SortAxis) {
+        self.vbos.sort_axis = axis;
+    }
+
     pub fn add_buffer_store(
         &mut self,
         renderer: &GpuRenderer,
@@ -27,6 +34,11 @@ impl Mesh2DRenderer {
         self.vbos.finalize(renderer);
     }
 
+    /// Creates the buffers on a new device after the old one was lost.
+    pub fn recreate(&mut self, renderer: &GpuRenderer) {
+        self.vbos.recreate(renderer.gpu_device());
+    }
+
     pub fn mesh_update(
         &mut self,
         mesh: &mut Mesh2D,
@@ -42,10 +54,13 @@ pub trait RenderMesh2D<'a, 'b>
 where
     'b: 'a,
 {
+    /// target is the size of the attachment the pass draws into, which
+    /// clips are kept within.
     fn render_2dmeshs(
         &mut self,
         renderer: &'b GpuRenderer,
         buffer: &'b Mesh2DRenderer,
+        target: PhysicalSize<f32>,
     );
 }
 
@@ -57,27 +72,55 @@ where
         &mut self,
         renderer: &'b GpuRenderer,
         buffer: &'b Mesh2DRenderer,
+        target: PhysicalSize<f32>,
     ) {
         if !buffer.vbos.buffers.is_empty() {
             self.set_buffers(buffer.vbos.as_buffer_pass());
-            self.set_pipeline(
-                renderer.get_pipelines(Mesh2DRenderPipeline).unwrap(),
-            );
             let mut index_pos = 0;
             let mut base_vertex = 0;
 
+            // The last clip set and if it left anything to draw in.
+            let mut clip = None;
+            let mut visible = true;
+            let mut pass = None;
+
             for details in &buffer.vbos.buffers {
+                // Opaque Meshes are sorted before the translucent ones.
+                if pass != Some(details.translucent) {
+                    self.set_pipeline(
+                        renderer
+                            .get_pipelines(Mesh2DRenderPipeline {
+                                pass: AlphaPass::new(details.translucent),
+                            })
+                            .unwrap(),
+                    );
+                    pass = Some(details.translucent);
+                }
+
+                if details.clip != clip {
+                    visible =
+                        self.set_clip(renderer, details.clip.as_ref(), target);
+                    clip = details.clip;
+                }
+
                 // Indexs can always start at 0 per mesh data.
                 // Base vertex is the Addition to the Index
-                self.draw_indexed(
-                    index_pos..index_pos + details.count,
-                    base_vertex, //i as i32 * details.max,
-                    0..1,
-                );
+                if visible {
+                    self.draw_indexed(
+                        index_pos..index_pos + details.count,
+                        base_vertex, //i as i32 * details.max,
+                        0..1,
+                    );
+                    renderer.record_stats(|stats| stats.draw_calls += 1);
+                }
 
                 base_vertex += details.max as i32 + 1;
                 index_pos += details.count;
             }
+
+            if clip.is_some() {
+                self.set_clip(renderer, None, target);
+            }
         }
     }
 }
@@ -0,0 +1,9 @@
+mod effects;
+mod pipeline;
+mod render;
+mod uniforms;
+
+pub use effects::*;
+pub use pipeline::*;
+pub use render::*;
+pub use uniforms::*;
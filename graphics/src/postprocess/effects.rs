@@ -0,0 +1,300 @@
+use crate::{
+    AscendingError, BloomPipeline, Color, ColorGradePipeline, CrtPipeline,
+    FadePipeline, GpuRenderer, OtherError, PipeLineLayout, PixelatePipeline,
+    PostEffectLayout, PostEffectRaw, PostProcessLayout, Texture, Vec4,
+    VignettePipeline,
+};
+use image::{DynamicImage, RgbaImage};
+use std::any::TypeId;
+use wgpu::util::DeviceExt;
+
+/// A single full screen pass within the PostProcess chain.
+/// params and color are handed to the effect shader and can be changed
+/// at any time. They are uploaded on PostProcess::update.
+pub struct PostEffect {
+    pub(crate) pipeline: (TypeId, Vec<u8>),
+    pub(crate) bind_group: wgpu::BindGroup,
+    pub(crate) extra_bind_group: Option<wgpu::BindGroup>,
+    /// The LUT of color_grade, kept to bind it again in recreate.
+    lut: Option<Texture>,
+    buffer: wgpu::Buffer,
+    pub params: Vec4,
+    pub color: Color,
+    pub enabled: bool,
+}
+
+impl PostEffect {
+    /// Creates a effect drawn with pipeline. The pipeline is created for
+    /// the renderer's surface format if it does not exist yet.
+    pub fn new<K: PipeLineLayout>(
+        renderer: &mut GpuRenderer,
+        pipeline: K,
+        params: Vec4,
+        color: Color,
+    ) -> Self {
+        renderer.create_pipeline(pipeline);
+
+        let (buffer, bind_group) =
+            Self::create_bindings(renderer, params, color);
+
+        Self {
+            pipeline: pipeline.layout_key(),
+            bind_group,
+            extra_bind_group: None,
+            lut: None,
+            buffer,
+            params,
+            color,
+            enabled: true,
+        }
+    }
+
+    /// Creates the uniform buffer and bind groups on a new device after
+    /// the old one was lost. The pipelines are rebuilt by the GpuRenderer.
+    /// A bind group given to with_bind_group was made on the old device,
+    /// so those effects need making again. color_grade binds its LUT again
+    /// itself.
+    pub fn recreate(&mut self, renderer: &mut GpuRenderer) {
+        (self.buffer, self.bind_group) =
+            Self::create_bindings(renderer, self.params, self.color);
+
+        if let Some(lut) = &self.lut {
+            self.extra_bind_group = Some(lut_bind_group(renderer, lut));
+        }
+    }
+
+    fn create_bindings(
+        renderer: &mut GpuRenderer,
+        params: Vec4,
+        color: Color,
+    ) -> (wgpu::Buffer, wgpu::BindGroup) {
+        let raw = PostEffectRaw {
+            params: params.to_array(),
+            color: color_to_array(color),
+        };
+
+        let buffer = renderer.device().create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label: Some("post effect buffer"),
+                contents: bytemuck::bytes_of(&raw),
+                usage: wgpu::BufferUsages::UNIFORM
+                    | wgpu::BufferUsages::COPY_DST,
+            },
+        );
+
+        let layout = renderer.create_layout(PostEffectLayout);
+        let bind_group =
+            renderer
+                .device()
+                .create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                    label: Some("post_effect_bind_group"),
+                });
+
+        (buffer, bind_group)
+    }
+
+    /// Sets a bind group used at group 3 for effects that need more than
+    /// the effect uniform, like a LUT texture.
+    pub fn with_bind_group(mut self, bind_group: wgpu::BindGroup) -> Self {
+        self.extra_bind_group = Some(bind_group);
+        self
+    }
+
+    /// Darkens the edges of the screen towards color. radius and softness
+    /// are in uv space from the center. color's alpha is the strength.
+    pub fn vignette(
+        renderer: &mut GpuRenderer,
+        radius: f32,
+        softness: f32,
+        color: Color,
+    ) -> Self {
+        Self::new(
+            renderer,
+            VignettePipeline,
+            Vec4::new(radius, softness, 0.0, 0.0),
+            color,
+        )
+    }
+
+    /// Adds a glow around anything brighter than threshold.
+    /// radius is in pixels.
+    pub fn bloom(
+        renderer: &mut GpuRenderer,
+        threshold: f32,
+        intensity: f32,
+        radius: f32,
+    ) -> Self {
+        Self::new(
+            renderer,
+            BloomPipeline,
+            Vec4::new(threshold, intensity, radius, 0.0),
+            Color::rgba(255, 255, 255, 255),
+        )
+    }
+
+    /// Renders the screen in blocks of pixel_size pixels.
+    pub fn pixelate(renderer: &mut GpuRenderer, pixel_size: f32) -> Self {
+        Self::new(
+            renderer,
+            PixelatePipeline,
+            Vec4::new(pixel_size, 0.0, 0.0, 0.0),
+            Color::rgba(255, 255, 255, 255),
+        )
+    }
+
+    /// Curves the screen and adds scanlines of line_size pixels.
+    /// flicker uses the System's time so 0.0 keeps frames stable.
+    pub fn crt(
+        renderer: &mut GpuRenderer,
+        scanline_intensity: f32,
+        curvature: f32,
+        line_size: f32,
+        flicker: f32,
+    ) -> Self {
+        Self::new(
+            renderer,
+            CrtPipeline,
+            Vec4::new(scanline_intensity, curvature, line_size, flicker),
+            Color::rgba(0, 0, 0, 255),
+        )
+    }
+
+    /// Fades the screen towards color. Change params.x from 0.0 to 1.0
+    /// to fade in or out.
+    pub fn fade(renderer: &mut GpuRenderer, color: Color, amount: f32) -> Self {
+        Self::new(
+            renderer,
+            FadePipeline,
+            Vec4::new(amount, 0.0, 0.0, 0.0),
+            color,
+        )
+    }
+
+    /// Color grades the screen with a LUT strip of size * size by size
+    /// where blue picks the slice. See neutral_lut for the layout.
+    pub fn color_grade(
+        renderer: &mut GpuRenderer,
+        lut: &Texture,
+        intensity: f32,
+    ) -> Result<Self, AscendingError> {
+        let (width, height) = lut.size();
+
+        if height < 2 || width != height * height {
+            return Err(AscendingError::Other(OtherError::new(
+                "LUT must be size * size wide and size high.",
+            )));
+        }
+
+        let is_srgb = renderer.surface_format().is_srgb();
+        let bind_group = lut_bind_group(renderer, lut);
+        let mut effect = Self::new(
+            renderer,
+            ColorGradePipeline,
+            Vec4::new(intensity, f32::from(u8::from(is_srgb)), 0.0, 0.0),
+            Color::rgba(255, 255, 255, 255),
+        )
+        .with_bind_group(bind_group);
+
+        effect.lut = Some(lut.clone());
+        Ok(effect)
+    }
+
+    pub(crate) fn update(&self, renderer: &GpuRenderer) {
+        let raw = PostEffectRaw {
+            params: self.params.to_array(),
+            color: color_to_array(self.color),
+        };
+
+        renderer.queue().write_buffer(
+            &self.buffer,
+            0,
+            bytemuck::bytes_of(&raw),
+        );
+    }
+}
+
+/// Uploads lut and binds it with a linear sampler for color_grade.
+fn lut_bind_group(
+    renderer: &mut GpuRenderer,
+    lut: &Texture,
+) -> wgpu::BindGroup {
+    let (width, height) = lut.size();
+
+    let texture = renderer.device().create_texture_with_data(
+        renderer.queue(),
+        &wgpu::TextureDescriptor {
+            label: Some("LUT texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        },
+        lut.bytes(),
+    );
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    // Linear filtering blends between the LUT's cells.
+    let sampler = renderer.device().create_sampler(&wgpu::SamplerDescriptor {
+        label: Some("LUT sampler"),
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        ..Default::default()
+    });
+
+    let layout = renderer.create_layout(PostProcessLayout);
+
+    renderer
+        .device()
+        .create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("lut_bind_group"),
+        })
+}
+
+/// Creates a LUT that leaves colors as they are. Useful as a starting
+/// point to color grade in a image editor.
+pub fn neutral_lut(size: u32) -> Texture {
+    let max = (size.max(2) - 1) as f32;
+    let image = RgbaImage::from_fn(size * size, size, |x, y| {
+        let channel = |value: u32| (value as f32 / max * 255.0).round() as u8;
+
+        image::Rgba([channel(x % size), channel(y), channel(x / size), 255])
+    });
+
+    Texture::from_image(
+        String::from("neutral_lut"),
+        DynamicImage::ImageRgba8(image),
+    )
+}
+
+/// Converts a Color into the 0.0 to 1.0 floats shaders use.
+pub(crate) fn color_to_array(color: Color) -> [f32; 4] {
+    [
+        color.r() as f32 / 255.0,
+        color.g() as f32 / 255.0,
+        color.b() as f32 / 255.0,
+        color.a() as f32 / 255.0,
+    ]
+}
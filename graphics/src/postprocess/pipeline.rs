@@ -0,0 +1,260 @@
+use crate::{
+    GpuDevice, LayoutStorage, PipeLineLayout, PostEffectLayout,
+    PostProcessLayout, ShaderFile, SystemLayout,
+};
+use bytemuck::{Pod, Zeroable};
+
+/// Shared bindings and the full screen vertex shader of every post process
+/// effect. create_post_process_pipeline joins it before the fragment shader.
+pub const POST_PROCESS_SHADER: ShaderFile = ShaderFile::new(
+    "postprocessshader.wgsl",
+    include_str!("../shaders/postprocessshader.wgsl"),
+);
+
+/// Creates a full screen post process pipeline from a fragment shader.
+/// Groups 0 to 2 are the System, the source texture and the effect
+/// uniform. Anything in extra_layouts is bound from group 3 on.
+/// Effects draw into resolved single sampled textures so MSAA never applies.
+pub fn create_post_process_pipeline(
+    gpu_device: &mut GpuDevice,
+    layouts: &mut LayoutStorage,
+    surface_format: wgpu::TextureFormat,
+    label: &str,
+    fragment_shader: ShaderFile,
+    extra_layouts: &[&wgpu::BindGroupLayout],
+) -> wgpu::RenderPipeline {
+    let shader = gpu_device
+        .create_shader_module(label, &[POST_PROCESS_SHADER, fragment_shader]);
+
+    let system_layout = layouts.create_layout(gpu_device, SystemLayout);
+    let source_layout = layouts.create_layout(gpu_device, PostProcessLayout);
+    let effect_layout = layouts.create_layout(gpu_device, PostEffectLayout);
+
+    let mut bind_group_layouts: Vec<&wgpu::BindGroupLayout> =
+        vec![&system_layout, &source_layout, &effect_layout];
+    bind_group_layouts.extend_from_slice(extra_layouts);
+
+    gpu_device.device().create_render_pipeline(
+        &wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&gpu_device.device().create_pipeline_layout(
+                &wgpu::PipelineLayoutDescriptor {
+                    label: Some("post_process_pipeline_layout"),
+                    bind_group_layouts: &bind_group_layouts,
+                    push_constant_ranges: &[],
+                },
+            )),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vertex",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                unclipped_depth: false,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fragment",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        },
+    )
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct BlitPipeline;
+
+impl PipeLineLayout for BlitPipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        layouts: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+        _sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        create_post_process_pipeline(
+            gpu_device,
+            layouts,
+            surface_format,
+            "Blit render pipeline",
+            ShaderFile::new(
+                "blitshader.wgsl",
+                include_str!("../shaders/blitshader.wgsl"),
+            ),
+            &[],
+        )
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct VignettePipeline;
+
+impl PipeLineLayout for VignettePipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        layouts: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+        _sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        create_post_process_pipeline(
+            gpu_device,
+            layouts,
+            surface_format,
+            "Vignette render pipeline",
+            ShaderFile::new(
+                "vignetteshader.wgsl",
+                include_str!("../shaders/vignetteshader.wgsl"),
+            ),
+            &[],
+        )
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct BloomPipeline;
+
+impl PipeLineLayout for BloomPipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        layouts: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+        _sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        create_post_process_pipeline(
+            gpu_device,
+            layouts,
+            surface_format,
+            "Bloom render pipeline",
+            ShaderFile::new(
+                "bloomshader.wgsl",
+                include_str!("../shaders/bloomshader.wgsl"),
+            ),
+            &[],
+        )
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct PixelatePipeline;
+
+impl PipeLineLayout for PixelatePipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        layouts: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+        _sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        create_post_process_pipeline(
+            gpu_device,
+            layouts,
+            surface_format,
+            "Pixelate render pipeline",
+            ShaderFile::new(
+                "pixelateshader.wgsl",
+                include_str!("../shaders/pixelateshader.wgsl"),
+            ),
+            &[],
+        )
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct CrtPipeline;
+
+impl PipeLineLayout for CrtPipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        layouts: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+        _sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        create_post_process_pipeline(
+            gpu_device,
+            layouts,
+            surface_format,
+            "CRT render pipeline",
+            ShaderFile::new(
+                "crtshader.wgsl",
+                include_str!("../shaders/crtshader.wgsl"),
+            ),
+            &[],
+        )
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct FadePipeline;
+
+impl PipeLineLayout for FadePipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        layouts: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+        _sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        create_post_process_pipeline(
+            gpu_device,
+            layouts,
+            surface_format,
+            "Fade render pipeline",
+            ShaderFile::new(
+                "fadeshader.wgsl",
+                include_str!("../shaders/fadeshader.wgsl"),
+            ),
+            &[],
+        )
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Hash, Pod, Zeroable)]
+pub struct ColorGradePipeline;
+
+impl PipeLineLayout for ColorGradePipeline {
+    fn create_layout(
+        &self,
+        gpu_device: &mut GpuDevice,
+        layouts: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+        _sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        // The LUT is bound like a source texture.
+        let lut_layout = layouts.create_layout(gpu_device, PostProcessLayout);
+
+        create_post_process_pipeline(
+            gpu_device,
+            layouts,
+            surface_format,
+            "Color grade render pipeline",
+            ShaderFile::new(
+                "colorgradeshader.wgsl",
+                include_str!("../shaders/colorgradeshader.wgsl"),
+            ),
+            &[&lut_layout],
+        )
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{
+    BlitPipeline, Color, GpuRenderer, PostEffect, PostProcessLayout, System,
+    TextureGroup, Vec4,
+};
+use winit::dpi::PhysicalSize;
+
+struct PostTarget {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    group: TextureGroup,
+}
+
+impl PostTarget {
+    fn new(renderer: &mut GpuRenderer, size: PhysicalSize<f32>) -> Self {
+        let format = renderer.surface_format();
+        let texture =
+            renderer.device().create_texture(&wgpu::TextureDescriptor {
+                label: Some("post process texture"),
+                size: wgpu::Extent3d {
+                    width: (size.width as u32).max(1),
+                    height: (size.height as u32).max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[format],
+            });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let group = TextureGroup::from_view(renderer, &view, PostProcessLayout);
+
+        Self {
+            texture,
+            view,
+            group,
+        }
+    }
+}
+
+/// Runs full screen effects over the scene before it reaches the frame.
+/// Render the scene into scene_view() instead of the frame buffer, then
+/// call render() to draw each enabled effect in order into the frame.
+pub struct PostProcess {
+    targets: [PostTarget; 2],
+    size: PhysicalSize<f32>,
+    format: wgpu::TextureFormat,
+    /// Used when no effects are enabled to copy the scene into the frame.
+    blit: PostEffect,
+    pub effects: Vec<PostEffect>,
+}
+
+impl PostProcess {
+    pub fn new(renderer: &mut GpuRenderer) -> Self {
+        let size = renderer.size();
+
+        Self {
+            targets: [
+                PostTarget::new(renderer, size),
+                PostTarget::new(renderer, size),
+            ],
+            size,
+            format: renderer.surface_format(),
+            blit: PostEffect::new(
+                renderer,
+                BlitPipeline,
+                Vec4::default(),
+                Color::rgba(255, 255, 255, 255),
+            ),
+            effects: Vec::new(),
+        }
+    }
+
+    /// The view the scene should be rendered into. Uses the same size as
+    /// the renderer so its depth buffer can be used along with it.
+    /// Pass it to GpuRenderer::color_attachment so MSAA resolves into it.
+    pub fn scene_view(&self) -> &wgpu::TextureView {
+        &self.targets[0].view
+    }
+
+    pub fn scene_texture(&self) -> &wgpu::Texture {
+        &self.targets[0].texture
+    }
+
+    /// Adds a effect to the end of the chain and returns its index.
+    pub fn push(&mut self, effect: PostEffect) -> usize {
+        self.effects.push(effect);
+        self.effects.len() - 1
+    }
+
+    /// Recreates the textures if the renderer was resized or its surface
+    /// format changed and uploads the parameters of every effect. Call this
+    /// once per frame before render.
+    pub fn update(&mut self, renderer: &mut GpuRenderer) {
+        let size = renderer.size();
+        let format = renderer.surface_format();
+
+        if self.size != size || self.format != format {
+            self.targets = [
+                PostTarget::new(renderer, size),
+                PostTarget::new(renderer, size),
+            ];
+            self.size = size;
+            self.format = format;
+        }
+
+        self.effects
+            .iter()
+            .filter(|effect| effect.enabled)
+            .for_each(|effect| effect.update(renderer));
+    }
+
+    /// Creates the textures and effects on a new device after the old one
+    /// was lost. See PostEffect::recreate.
+    pub fn recreate(&mut self, renderer: &mut GpuRenderer) {
+        self.size = renderer.size();
+        self.format = renderer.surface_format();
+        self.targets = [
+            PostTarget::new(renderer, self.size),
+            PostTarget::new(renderer, self.size),
+        ];
+        self.blit.recreate(renderer);
+        self.effects
+            .iter_mut()
+            .for_each(|effect| effect.recreate(renderer));
+    }
+
+    /// Draws the enabled effects in order. Each pass samples the previous
+    /// one's output and the last pass draws into the frame buffer.
+    /// Effects whose pipeline is missing are skipped, and if none are
+    /// left the scene is blitted so the frame still gets drawn.
+    pub fn render<Controls: camera::controls::Controls>(
+        &self,
+        renderer: &GpuRenderer,
+        system: &System<Controls>,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let frame = renderer.frame_buffer().as_ref().expect("no frame view?");
+        let pipeline_of = |effect: &PostEffect| {
+            renderer
+                .pipeline_storage
+                .get_pipeline_by_key(&effect.pipeline)
+        };
+        let mut effects: Vec<_> = self
+            .effects
+            .iter()
+            .filter(|effect| effect.enabled)
+            .filter_map(|effect| Some((effect, pipeline_of(effect)?)))
+            .collect();
+
+        if effects.is_empty() {
+            match pipeline_of(&self.blit) {
+                Some(pipeline) => effects.push((&self.blit, pipeline)),
+                None => return,
+            }
+        }
+
+        let mut source = 0;
+
+        for (i, (effect, pipeline)) in effects.iter().enumerate() {
+            let view = if i + 1 == effects.len() {
+                frame
+            } else {
+                &self.targets[1 - source].view
+            };
+
+            let mut pass =
+                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("post process pass"),
+                    color_attachments: &[Some(
+                        wgpu::RenderPassColorAttachment {
+                            view,
+                            resolve_target: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                                store: wgpu::StoreOp::Store,
+                            },
+                        },
+                    )],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+
+            pass.set_bind_group(0, system.bind_group(), &[]);
+            pass.set_bind_group(1, &self.targets[source].group.bind_group, &[]);
+            pass.set_bind_group(2, &effect.bind_group, &[]);
+
+            if let Some(bind_group) = &effect.extra_bind_group {
+                pass.set_bind_group(3, bind_group, &[]);
+            }
+
+            pass.set_pipeline(pipeline);
+            pass.draw(0..3, 0..1);
+            renderer.record_stats(|stats| stats.draw_calls += 1);
+            source = 1 - source;
+        }
+    }
+}
@@ -0,0 +1,5 @@
+// Copies the source as is. Used when no effects are enabled.
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    return textureSampleLevel(source, source_sample, vertex.uv, 0.0);
+}
@@ -0,0 +1,20 @@
+// params.x: threshold, params.y: intensity, params.z: radius in pixels.
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let color = textureSampleLevel(source, source_sample, vertex.uv, 0.0);
+    let size = vec2<f32>(textureDimensions(source));
+    let texel = vec2<f32>(effect.params.z / 2.0) / size;
+    var glow = vec3<f32>(0.0, 0.0, 0.0);
+
+    for (var x = -2; x <= 2; x++) {
+        for (var y = -2; y <= 2; y++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * texel;
+            let sample = textureSampleLevel(source, source_sample, vertex.uv + offset, 0.0);
+
+            glow += max(sample.rgb - vec3<f32>(effect.params.x), vec3<f32>(0.0));
+        }
+    }
+
+    glow = glow / 25.0;
+    return vec4<f32>(color.rgb + glow * effect.params.y, color.a);
+}
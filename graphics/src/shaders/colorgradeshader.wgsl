@@ -0,0 +1,57 @@
+// params.x: intensity, params.y: 1.0 when the source is stored as sRGB.
+// The LUT is a strip of size * size by size with blue picking the slice.
+@group(3)
+@binding(0)
+var lut: texture_2d<f32>;
+@group(3)
+@binding(1)
+var lut_sample: sampler;
+
+fn to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, color <= vec3<f32>(0.0031308));
+}
+
+fn to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, color <= vec3<f32>(0.04045));
+}
+
+fn lut_slice(color: vec2<f32>, slice: f32, size: f32) -> vec3<f32> {
+    let uv = vec2<f32>(
+        (slice * size + color.x + 0.5) / (size * size),
+        (color.y + 0.5) / size,
+    );
+
+    return textureSampleLevel(lut, lut_sample, uv, 0.0).rgb;
+}
+
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let color = textureSampleLevel(source, source_sample, vertex.uv, 0.0);
+    let is_srgb = effect.params.y > 0.5;
+    var graded = clamp(color.rgb, vec3<f32>(0.0), vec3<f32>(1.0));
+
+    if (is_srgb) {
+        graded = to_srgb(graded);
+    }
+
+    let size = f32(textureDimensions(lut).y);
+    let scaled = graded * (size - 1.0);
+    let slice = floor(scaled.z);
+    let next = min(slice + 1.0, size - 1.0);
+
+    graded = mix(
+        lut_slice(scaled.xy, slice, size),
+        lut_slice(scaled.xy, next, size),
+        scaled.z - slice,
+    );
+
+    if (is_srgb) {
+        graded = to_linear(graded);
+    }
+
+    return vec4<f32>(mix(color.rgb, graded, effect.params.x), color.a);
+}
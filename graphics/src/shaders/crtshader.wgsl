@@ -0,0 +1,22 @@
+// params.x: scanline intensity, params.y: curvature,
+// params.z: scanline size in pixels, params.w: flicker.
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    var uv = vertex.uv * 2.0 - 1.0;
+    let offset = abs(uv.yx) * effect.params.y;
+
+    uv = uv + uv * offset * offset;
+    uv = uv * 0.5 + 0.5;
+
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+
+    let color = textureSampleLevel(source, source_sample, uv, 0.0);
+    let line_size = max(effect.params.z, 1.0);
+    let line = sin(uv.y * global.size.y * 3.14159265 / line_size);
+    let scanline = 1.0 - effect.params.x * (0.5 - 0.5 * line);
+    let flicker = 1.0 - effect.params.w * (0.5 + 0.5 * sin(global.seconds * 60.0));
+
+    return vec4<f32>(color.rgb * scanline * flicker, color.a);
+}
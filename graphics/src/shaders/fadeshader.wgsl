@@ -0,0 +1,8 @@
+// params.x: amount faded towards color. 0.0 is none and 1.0 is fully faded.
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let color = textureSampleLevel(source, source_sample, vertex.uv, 0.0);
+    let amount = clamp(effect.params.x, 0.0, 1.0);
+
+    return vec4<f32>(mix(color.rgb, effect.color.rgb, amount), color.a);
+}
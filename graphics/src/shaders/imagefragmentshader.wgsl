@@ -0,0 +1,11 @@
+// Fragment shader
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let object_color = image_color(vertex);
+
+    if (object_color.a <= 0.0) {
+        discard;
+    }
+
+    return object_color;
+}
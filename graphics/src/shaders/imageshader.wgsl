@@ -36,6 +36,7 @@ struct VertexOutput {
     @location(5) layer: i32,
     @location(6) time: u32,
     @location(7) animate: u32,
+    @location(8) lod: f32,
 };
 
 @group(1)
@@ -45,6 +46,16 @@ var tex: texture_2d_array<f32>;
 @binding(1)
 var tex_sample: sampler;
 
+// Only bound for Materials. What params holds is up to each Material.
+struct Material {
+    params: vec4<f32>,
+    color: vec4<f32>,
+};
+
+@group(2)
+@binding(0)
+var<uniform> material: Material;
+
 fn unpack_color(color: u32) -> vec4<f32> {
     return vec4<f32>(
         f32((color & 0xff0000u) >> 16u),
@@ -106,12 +117,21 @@ fn vertex(
     result.size = fsize;
     result.animate = vertex.animate;
     result.time = vertex.time;
+
+    // The mip level of a frame drawn at hw pixels.
+    var scale = 1.0;
+
+    if (vertex.use_camera == 1u) {
+        scale = global.scale;
+    }
+
+    let texels = tex_data.zw / max(vertex.hw * scale, vec2<f32>(1.0, 1.0));
+    result.lod = max(log2(max(texels.x, texels.y)), 0.0);
     return result;
 }
 
-// Fragment shader
-@fragment
-fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+// The Image's texture color tinted by its color.
+fn image_color(vertex: VertexOutput) -> vec4<f32> {
     var coords = vec2<f32>(0.0, 0.0);
     let xframes = vertex.frames[0];
     var yframes = vertex.frames[0];
@@ -141,21 +161,15 @@ fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
     let corner = floor(tex_pixel) + 1.0;
     let frac = min((corner - tex_pixel) * vec2<f32>(2.0, 2.0), vec2<f32>(1.0, 1.0));
 
-    var c1 = textureSampleLevel(tex, tex_sample, (floor(tex_pixel + vec2<f32>(0.0, 0.0)) + 0.5) / vertex.size, vertex.layer, 1.0);
-    var c2 = textureSampleLevel(tex, tex_sample, (floor(tex_pixel + vec2<f32>(step.x, 0.0)) + 0.5) / vertex.size, vertex.layer, 1.0);
-    var c3 = textureSampleLevel(tex, tex_sample, (floor(tex_pixel + vec2<f32>(0.0, step.y)) + 0.5) / vertex.size, vertex.layer, 1.0);
-    var c4 = textureSampleLevel(tex, tex_sample, (floor(tex_pixel + step.xy) + 0.5) / vertex.size, vertex.layer, 1.0);
+    var c1 = textureSampleLevel(tex, tex_sample, (floor(tex_pixel + vec2<f32>(0.0, 0.0)) + 0.5) / vertex.size, vertex.layer, vertex.lod);
+    var c2 = textureSampleLevel(tex, tex_sample, (floor(tex_pixel + vec2<f32>(step.x, 0.0)) + 0.5) / vertex.size, vertex.layer, vertex.lod);
+    var c3 = textureSampleLevel(tex, tex_sample, (floor(tex_pixel + vec2<f32>(0.0, step.y)) + 0.5) / vertex.size, vertex.layer, vertex.lod);
+    var c4 = textureSampleLevel(tex, tex_sample, (floor(tex_pixel + step.xy) + 0.5) / vertex.size, vertex.layer, vertex.lod);
 
     c1 = c1 * (frac.x * frac.y);
     c2 = c2 *((1.0 - frac.x) * frac.y);
     c3 = c3 * (frac.x * (1.0 - frac.y));
     c4 = c4 *((1.0 - frac.x) * (1.0 - frac.y));
 
-    let object_color = (c1 + c2 + c3 + c4) * vertex.col;
-
-    if (object_color.a <= 0.0) {
-        discard;
-    }
-
-    return object_color;
+    return (c1 + c2 + c3 + c4) * vertex.col;
 }
\ No newline at end of file
@@ -20,6 +20,7 @@ struct VertexInput {
     @location(3) texture_id: f32,
     @location(4) texture_layer: f32,
     @location(5) color: u32,
+    @location(6) padding: f32,
 };
 
 struct VertexOutput {
@@ -54,9 +55,11 @@ fn vertex(
     let v = vertex.vertex_idx % 4u;
     let size = textureDimensions(tex);
     let fsize = vec2<f32> (f32(size.x), f32(size.y));
-    let total_tiles = u32(size.x / u32(vertex.tilesize));
-    let tileposx = f32(u32(vertex.texture_id) % total_tiles) * vertex.tilesize;
-    let tileposy = f32(u32(vertex.texture_id) / total_tiles) * vertex.tilesize;
+    // Tiles of mipmapped atlases are padded on every side.
+    let cell = vertex.tilesize + vertex.padding * 2.0;
+    let total_tiles = u32(fsize.x / cell);
+    let tileposx = f32(u32(vertex.texture_id) % total_tiles) * cell + vertex.padding;
+    let tileposy = f32(u32(vertex.texture_id) / total_tiles) * cell + vertex.padding;
 
     switch v {
         case 1u: {
@@ -86,7 +89,9 @@ fn vertex(
 // Fragment shader
 @fragment
 fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
-    let object_color = textureSampleLevel(tex, tex_sample, vertex.uv, vertex.uv_layer, 1.0);
+    // Tiles are world sized so the camera zoom picks the mip level.
+    let lod = max(-log2(global.scale), 0.0);
+    let object_color = textureSampleLevel(tex, tex_sample, vertex.uv, vertex.uv_layer, lod);
 
     let color = object_color * vertex.color;
 
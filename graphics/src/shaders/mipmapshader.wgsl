@@ -0,0 +1,29 @@
+@group(0)
+@binding(0)
+var source: texture_2d<f32>;
+@group(0)
+@binding(1)
+var source_sample: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// Draws a single triangle that covers the viewport.
+@vertex
+fn vertex(@builtin(vertex_index) vertex_idx: u32) -> VertexOutput {
+    var result: VertexOutput;
+    let pos = vec2<f32>(f32((vertex_idx << 1u) & 2u), f32(vertex_idx & 2u));
+
+    result.clip_position = vec4<f32>(pos * 2.0 - 1.0, 0.0, 1.0);
+    result.uv = vec2<f32>(pos.x, 1.0 - pos.y);
+    return result;
+}
+
+// Each texel lands between the 2x2 texels it covers in the level above so
+// linear filtering averages them.
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    return textureSampleLevel(source, source_sample, vertex.uv, 0.0);
+}
@@ -0,0 +1,9 @@
+// params.x: size of each pixel block in screen pixels.
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(source));
+    let block = max(effect.params.x, 1.0);
+    let uv = (floor(vertex.uv * size / block) + 0.5) * block / size;
+
+    return textureSampleLevel(source, source_sample, uv, 0.0);
+}
@@ -0,0 +1,46 @@
+struct Global {
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+    inverse_proj: mat4x4<f32>,
+    eye: vec3<f32>,
+    scale: f32,
+    size: vec2<f32>,
+    seconds: f32,
+};
+
+struct Effect {
+    params: vec4<f32>,
+    color: vec4<f32>,
+};
+
+@group(0)
+@binding(0)
+var<uniform> global: Global;
+
+@group(1)
+@binding(0)
+var source: texture_2d<f32>;
+@group(1)
+@binding(1)
+var source_sample: sampler;
+
+@group(2)
+@binding(0)
+var<uniform> effect: Effect;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// Draws a single triangle that covers the whole screen.
+@vertex
+fn vertex(@builtin(vertex_index) vertex_idx: u32) -> VertexOutput {
+    var result: VertexOutput;
+    let pos = vec2<f32>(f32((vertex_idx << 1u) & 2u), f32(vertex_idx & 2u));
+
+    result.clip_position = vec4<f32>(pos * 2.0 - 1.0, 0.0, 1.0);
+    result.uv = vec2<f32>(pos.x, 1.0 - pos.y);
+    return result;
+}
+
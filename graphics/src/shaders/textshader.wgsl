@@ -108,7 +108,7 @@ fn vertex(
 fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
      switch vertex.is_color {
         case 1u: {
-            let object_color = textureSampleLevel(emoji_tex, emoji_tex_sample, vertex.uv.xy, vertex.layer, 1.0);
+            let object_color = textureSampleLevel(emoji_tex, emoji_tex_sample, vertex.uv.xy, vertex.layer, 0.0);
 
             if object_color.a <= 0.0 {
                 discard;
@@ -117,7 +117,7 @@ fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
             return vertex.color.rgba * object_color;
         }
         default: {
-            let object_color = textureSampleLevel(tex, tex_sample, vertex.uv.xy, vertex.layer, 1.0);
+            let object_color = textureSampleLevel(tex, tex_sample, vertex.uv.xy, vertex.layer, 0.0);
 
             if object_color.r <= 0.0 {
                 discard;
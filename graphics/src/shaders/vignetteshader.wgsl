@@ -0,0 +1,11 @@
+// params.x: radius, params.y: softness, color.a: strength.
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let color = textureSampleLevel(source, source_sample, vertex.uv, 0.0);
+    let dist = distance(vertex.uv, vec2<f32>(0.5, 0.5));
+    let radius = effect.params.x;
+    let vignette = 1.0 - smoothstep(radius - effect.params.y, radius, dist);
+    let amount = mix(1.0, vignette, effect.color.a);
+
+    return vec4<f32>(mix(effect.color.rgb, color.rgb, amount), color.a);
+}
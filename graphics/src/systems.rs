@@ -1,30 +1,44 @@
 mod bounds;
 mod buffer;
+mod clip;
 mod device;
 mod draw_order;
 mod instance_buffer;
 mod layout;
 mod pass;
 mod pipelines;
+mod profiler;
+mod readback;
+mod recovery;
 mod renderer;
+mod shaders;
 mod static_vbo;
+mod stats;
 mod system;
 mod vbo;
+mod watcher;
 
-pub use bounds::{Bounds, WorldBounds};
+pub use bounds::{Bounds, ViewBounds, WorldBounds};
 pub use buffer::{
     AsBufferPass, Buffer, BufferData, BufferLayout, BufferPass, BufferStore,
 };
+pub use clip::*;
 pub use device::*;
-pub use draw_order::{DrawOrder, Index, OrderedIndex};
+pub use draw_order::{DrawOrder, Index, OrderedIndex, SortAxis};
 pub use instance_buffer::*;
 pub use layout::*;
 pub use pass::*;
 pub use pipelines::*;
+pub use profiler::*;
+pub(crate) use recovery::watch_device_lost;
+pub use recovery::RendererEvent;
 pub use renderer::*;
+pub use shaders::*;
 pub use static_vbo::*;
+pub use stats::*;
 pub use system::*;
 pub use vbo::*;
+pub use watcher::DirectoryWatcher;
 
 pub(crate) type FxBuildHasher =
     std::hash::BuildHasherDefault<ritehash::FxHasher>;
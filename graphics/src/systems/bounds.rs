@@ -55,6 +55,14 @@ impl WorldBounds {
         self.bottom += offset.y;
     }
 
+    /// If any part of other is within these bounds.
+    pub fn intersects(&self, other: &WorldBounds) -> bool {
+        self.left <= other.right
+            && other.left <= self.right
+            && self.bottom <= other.top
+            && other.bottom <= self.top
+    }
+
     pub fn set_within_limits(&mut self, limits: &WorldBounds) {
         if self.left < limits.left {
             self.left = limits.left;
@@ -86,7 +94,32 @@ impl Default for WorldBounds {
     }
 }
 
-/// This is the bounds used to clip Text.
+/// What can be seen on screen. Objects fully outside of it are skipped
+/// when added to a renderer. See System::view_bounds.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ViewBounds {
+    /// The area of the world the camera sees.
+    pub world: WorldBounds,
+    /// The area objects that do not use the camera are seen within.
+    pub screen: WorldBounds,
+}
+
+impl ViewBounds {
+    pub fn new(world: WorldBounds, screen: WorldBounds) -> Self {
+        Self { world, screen }
+    }
+
+    pub fn is_visible(&self, bounds: &WorldBounds, use_camera: bool) -> bool {
+        if use_camera {
+            self.world.intersects(bounds)
+        } else {
+            self.screen.intersects(bounds)
+        }
+    }
+}
+
+/// This is the bounds used to clip Images, Text and Meshes.
+/// See GpuRenderer::push_clip.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Bounds {
     pub left: f32,
@@ -104,6 +137,20 @@ impl Bounds {
             top,
         }
     }
+
+    /// The area within both bounds. Is empty when they do not overlap.
+    pub fn intersection(&self, other: &Bounds) -> Bounds {
+        Self {
+            left: self.left.max(other.left),
+            bottom: self.bottom.max(other.bottom),
+            right: self.right.min(other.right),
+            top: self.top.min(other.top),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.right <= self.left || self.top <= self.bottom
+    }
 }
 
 impl Default for Bounds {
@@ -0,0 +1,169 @@
+use crate::{
+    AlphaPass, Bounds, BufferLayout, GpuRenderer, InstanceBuffer, OrderedIndex,
+    PipeLineLayout, StaticBufferObject,
+};
+use std::ops::Range;
+use winit::dpi::PhysicalSize;
+
+impl GpuRenderer {
+    /// Clips everything added to the renderers until pop_clip to bounds.
+    /// bounds is in pixels from the bottom left of the screen and is kept
+    /// within the clip already pushed.
+    pub fn push_clip(&mut self, bounds: Bounds) {
+        let bounds = match self.clip_stack.last() {
+            Some(clip) => clip.intersection(&bounds),
+            None => bounds,
+        };
+
+        self.clip_stack.push(bounds);
+    }
+
+    /// Removes the last pushed clip and returns it.
+    pub fn pop_clip(&mut self) -> Option<Bounds> {
+        self.clip_stack.pop()
+    }
+
+    /// The clip objects added now are drawn within.
+    pub fn clip(&self) -> Option<Bounds> {
+        self.clip_stack.last().copied()
+    }
+
+    /// Keeps the object's own clip within the current clip.
+    pub(crate) fn clip_index(&self, index: &mut OrderedIndex) {
+        index.clip = match (self.clip(), index.clip) {
+            (Some(clip), Some(bounds)) => Some(clip.intersection(&bounds)),
+            (clip, bounds) => clip.or(bounds),
+        };
+    }
+
+    /// Converts a clip into a scissor rect within an attachment of size
+    /// target. Returns None when nothing within it can be seen.
+    pub(crate) fn scissor_rect(
+        &self,
+        clip: Option<&Bounds>,
+        target: PhysicalSize<f32>,
+    ) -> Option<(u32, u32, u32, u32)> {
+        let size = target;
+        let clip = clip.copied().unwrap_or(Bounds::new(
+            0.0,
+            0.0,
+            size.width,
+            size.height,
+        ));
+        let left = clip.left.clamp(0.0, size.width).round();
+        let right = clip.right.clamp(0.0, size.width).round();
+        // Scissor rects start from the top of the attachment.
+        let top = (size.height - clip.top).clamp(0.0, size.height).round();
+        let bottom =
+            (size.height - clip.bottom).clamp(0.0, size.height).round();
+
+        if right <= left || bottom <= top {
+            return None;
+        }
+
+        Some((
+            left as u32,
+            top as u32,
+            (right - left) as u32,
+            (bottom - top) as u32,
+        ))
+    }
+}
+
+pub trait SetClip<'a> {
+    /// Sets the scissor rect to the clip or the whole attachment for None.
+    /// target is the size of the attachment the pass draws into. Returns
+    /// false if nothing drawn within the clip would be seen.
+    fn set_clip(
+        &mut self,
+        renderer: &GpuRenderer,
+        clip: Option<&Bounds>,
+        target: PhysicalSize<f32>,
+    ) -> bool;
+
+    /// Draws each range of instances of the StaticBufferObject quad within
+    /// its clip, then resets the scissor rect to the whole attachment.
+    fn draw_clipped_instances(
+        &mut self,
+        renderer: &GpuRenderer,
+        clips: &[(Option<Bounds>, Range<u32>)],
+        target: PhysicalSize<f32>,
+    );
+
+    /// Draws the opaque ranges of buffer with the pipeline for
+    /// AlphaPass::OPAQUE, then the translucent ranges with the pipeline
+    /// for AlphaPass::TRANSLUCENT.
+    fn draw_alpha_instances<K: BufferLayout, P: PipeLineLayout>(
+        &mut self,
+        renderer: &'a GpuRenderer,
+        buffer: &InstanceBuffer<K>,
+        target: PhysicalSize<f32>,
+        pipeline: impl Fn(AlphaPass) -> P,
+    );
+}
+
+impl<'a> SetClip<'a> for wgpu::RenderPass<'a> {
+    fn set_clip(
+        &mut self,
+        renderer: &GpuRenderer,
+        clip: Option<&Bounds>,
+        target: PhysicalSize<f32>,
+    ) -> bool {
+        match renderer.scissor_rect(clip, target) {
+            Some((x, y, width, height)) => {
+                self.set_scissor_rect(x, y, width, height);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn draw_clipped_instances(
+        &mut self,
+        renderer: &GpuRenderer,
+        clips: &[(Option<Bounds>, Range<u32>)],
+        target: PhysicalSize<f32>,
+    ) {
+        for (clip, range) in clips {
+            if clip.is_some() && !self.set_clip(renderer, clip.as_ref(), target)
+            {
+                continue;
+            }
+
+            self.draw_indexed(
+                0..StaticBufferObject::index_count(),
+                0,
+                range.clone(),
+            );
+            renderer.record_stats(|stats| stats.draw_calls += 1);
+
+            if clip.is_some() {
+                self.set_clip(renderer, None, target);
+            }
+        }
+    }
+
+    fn draw_alpha_instances<K: BufferLayout, P: PipeLineLayout>(
+        &mut self,
+        renderer: &'a GpuRenderer,
+        buffer: &InstanceBuffer<K>,
+        target: PhysicalSize<f32>,
+        pipeline: impl Fn(AlphaPass) -> P,
+    ) {
+        let passes = [
+            (AlphaPass::OPAQUE, buffer.opaque_clips()),
+            (AlphaPass::TRANSLUCENT, buffer.translucent_clips()),
+        ];
+
+        for (pass, clips) in passes {
+            if clips.is_empty() {
+                continue;
+            }
+
+            if let Some(pipeline) = renderer.get_pipelines(pipeline(pass)) {
+                self.set_pipeline(pipeline);
+                self.draw_clipped_instances(renderer, clips, target);
+            }
+        }
+    }
+}
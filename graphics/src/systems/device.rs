@@ -1,4 +1,4 @@
-use crate::{AscendingError, GpuRenderer};
+use crate::{AscendingError, GpuRenderer, ShaderFile, ShaderStorage};
 use async_trait::async_trait;
 use std::path::Path;
 use wgpu::TextureFormat;
@@ -12,9 +12,24 @@ use winit::{
 pub struct GpuDevice {
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
+    pub(crate) shaders: ShaderStorage,
+    /// What the device was created with so a lost device can be requested
+    /// again. The device panics if asked once lost.
+    pub(crate) features: wgpu::Features,
+    pub(crate) limits: wgpu::Limits,
 }
 
 impl GpuDevice {
+    pub fn new(device: wgpu::Device, queue: wgpu::Queue) -> Self {
+        Self {
+            features: device.features(),
+            limits: device.limits(),
+            device,
+            queue,
+            shaders: ShaderStorage::default(),
+        }
+    }
+
     pub fn device(&self) -> &wgpu::Device {
         &self.device
     }
@@ -22,6 +37,22 @@ impl GpuDevice {
     pub fn queue(&self) -> &wgpu::Queue {
         &self.queue
     }
+
+    /// Creates a shader module from the files joined in order. Files that
+    /// were reloaded at runtime are used in place of their built in source.
+    pub fn create_shader_module(
+        &mut self,
+        label: &str,
+        files: &[ShaderFile],
+    ) -> wgpu::ShaderModule {
+        let source = self.shaders.compose(files);
+
+        self.device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(label),
+                source: wgpu::ShaderSource::Wgsl(source.into()),
+            })
+    }
 }
 
 ///Handles the Window, Adapter and Surface information.
@@ -32,6 +63,8 @@ pub struct GpuWindow {
     pub(crate) surface_format: wgpu::TextureFormat,
     pub(crate) size: PhysicalSize<f32>,
     pub(crate) surface_config: wgpu::SurfaceConfiguration,
+    /// Set when the surface was lost and reconfigured.
+    pub(crate) surface_lost: bool,
 }
 
 impl GpuWindow {
@@ -39,6 +72,59 @@ impl GpuWindow {
         &self.adapter
     }
 
+    /// Configures the surface for a newly created device.
+    pub(crate) fn recreate(&mut self, gpu_device: &GpuDevice) {
+        self.surface
+            .configure(gpu_device.device(), &self.surface_config);
+    }
+
+    /// The formats and present modes the surface supports on the adapter.
+    pub fn capabilities(&self) -> wgpu::SurfaceCapabilities {
+        self.surface.get_capabilities(&self.adapter)
+    }
+
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.surface_config.present_mode
+    }
+
+    /// Reconfigures the surface with present_mode. AutoVsync and
+    /// AutoNoVsync are always supported.
+    pub fn set_present_mode(
+        &mut self,
+        gpu_device: &GpuDevice,
+        present_mode: wgpu::PresentMode,
+    ) -> Result<(), AscendingError> {
+        if !supports_present_mode(&self.capabilities(), present_mode) {
+            return Err(AscendingError::UnsupportedPresentMode(present_mode));
+        }
+
+        self.surface_config.present_mode = present_mode;
+        self.surface
+            .configure(gpu_device.device(), &self.surface_config);
+
+        Ok(())
+    }
+
+    /// Reconfigures the surface with format. The pipelines still need to
+    /// be rebuilt, so use GpuRenderer::set_surface_format instead.
+    pub(crate) fn set_surface_format(
+        &mut self,
+        gpu_device: &GpuDevice,
+        format: wgpu::TextureFormat,
+    ) -> Result<(), AscendingError> {
+        if !self.capabilities().formats.contains(&format) {
+            return Err(AscendingError::UnsupportedSurfaceFormat(format));
+        }
+
+        self.surface_config.format = format;
+        self.surface_config.view_formats = vec![format];
+        self.surface
+            .configure(gpu_device.device(), &self.surface_config);
+        self.surface_format = format;
+
+        Ok(())
+    }
+
     pub fn resize(
         &mut self,
         gpu_device: &GpuDevice,
@@ -96,6 +182,7 @@ impl GpuWindow {
                             self.size.height as u32,
                         );
                         self.resize(gpu_device, size)?;
+                        self.surface_lost = true;
                     }
                     Err(wgpu::SurfaceError::Outdated) => {
                         return Ok(None);
@@ -123,30 +210,300 @@ impl GpuWindow {
     pub fn create_depth_texture(
         &self,
         gpu_device: &GpuDevice,
+        sample_count: u32,
     ) -> wgpu::TextureView {
-        let size = wgpu::Extent3d {
-            width: self.size.width as u32,
-            height: self.size.height as u32,
-            depth_or_array_layers: 1,
-        };
+        create_depth_texture(gpu_device, self.size, sample_count)
+    }
+}
+
+///Handles the Adapter and the offscreen Texture used in place of a Surface.
+pub struct GpuHeadless {
+    pub(crate) adapter: wgpu::Adapter,
+    pub(crate) texture: wgpu::Texture,
+    pub(crate) format: wgpu::TextureFormat,
+    pub(crate) size: PhysicalSize<f32>,
+}
+
+impl GpuHeadless {
+    pub fn new(
+        adapter: wgpu::Adapter,
+        gpu_device: &GpuDevice,
+        size: PhysicalSize<u32>,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let size = PhysicalSize::new(
+            size.width.max(1) as f32,
+            size.height.max(1) as f32,
+        );
+
+        Self {
+            texture: Self::create_texture(gpu_device, size, format),
+            adapter,
+            format,
+            size,
+        }
+    }
+
+    pub fn adapter(&self) -> &wgpu::Adapter {
+        &self.adapter
+    }
+
+    /// Creates the offscreen texture on a newly created device.
+    pub(crate) fn recreate(&mut self, gpu_device: &GpuDevice) {
+        self.texture = Self::create_texture(gpu_device, self.size, self.format);
+    }
+
+    pub fn resize(
+        &mut self,
+        gpu_device: &GpuDevice,
+        size: PhysicalSize<u32>,
+    ) -> Result<(), AscendingError> {
+        if size.width == 0 || size.height == 0 {
+            return Ok(());
+        }
+
+        self.size = PhysicalSize::new(size.width as f32, size.height as f32);
+        self.texture = Self::create_texture(gpu_device, self.size, self.format);
+
+        Ok(())
+    }
+
+    pub fn size(&self) -> PhysicalSize<f32> {
+        self.size
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    /// The formats of HEADLESS_FORMATS the offscreen texture can use.
+    pub fn formats(&self, gpu_device: &GpuDevice) -> Vec<wgpu::TextureFormat> {
+        HEADLESS_FORMATS
+            .iter()
+            .copied()
+            .filter(|format| {
+                format_features(&self.adapter, gpu_device, *format)
+                    .allowed_usages
+                    .contains(HEADLESS_USAGES)
+            })
+            .collect()
+    }
+
+    /// Recreates the offscreen texture with format. The pipelines still
+    /// need to be rebuilt, so use GpuRenderer::set_surface_format instead.
+    pub(crate) fn set_format(
+        &mut self,
+        gpu_device: &GpuDevice,
+        format: wgpu::TextureFormat,
+    ) -> Result<(), AscendingError> {
+        if !self.formats(gpu_device).contains(&format) {
+            return Err(AscendingError::UnsupportedSurfaceFormat(format));
+        }
+
+        self.format = format;
+        self.texture = Self::create_texture(gpu_device, self.size, format);
+
+        Ok(())
+    }
+
+    pub fn create_view(&self) -> wgpu::TextureView {
+        self.texture
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    pub fn create_depth_texture(
+        &self,
+        gpu_device: &GpuDevice,
+        sample_count: u32,
+    ) -> wgpu::TextureView {
+        create_depth_texture(gpu_device, self.size, sample_count)
+    }
+
+    fn create_texture(
+        gpu_device: &GpuDevice,
+        size: PhysicalSize<f32>,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::Texture {
+        gpu_device
+            .device()
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("headless frame texture"),
+                size: wgpu::Extent3d {
+                    width: size.width as u32,
+                    height: size.height as u32,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: HEADLESS_USAGES,
+                view_formats: &[format],
+            })
+    }
+}
+
+/// The formats a GpuHeadless may be switched to, if the adapter allows.
+const HEADLESS_FORMATS: [wgpu::TextureFormat; 6] = [
+    TextureFormat::Rgba8UnormSrgb,
+    TextureFormat::Bgra8UnormSrgb,
+    TextureFormat::Rgba8Unorm,
+    TextureFormat::Bgra8Unorm,
+    TextureFormat::Rgba16Float,
+    TextureFormat::Rgb10a2Unorm,
+];
+
+const HEADLESS_USAGES: wgpu::TextureUsages =
+    wgpu::TextureUsages::RENDER_ATTACHMENT
+        .union(wgpu::TextureUsages::TEXTURE_BINDING)
+        .union(wgpu::TextureUsages::COPY_SRC);
+
+/// The kind of surface format to pick.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SurfaceColor {
+    /// 8 bit colors the GPU converts from linear to sRGB when written.
+    #[default]
+    Srgb,
+    /// 8 bit colors written as the shaders return them.
+    Linear,
+    /// Floating point or 10 bit colors for HDR displays.
+    Hdr,
+}
+
+/// Picks the format of the kind color from formats, preferring Rgba over
+/// Bgra. Returns None when formats has none of that kind.
+pub fn pick_surface_format(
+    formats: &[wgpu::TextureFormat],
+    color: SurfaceColor,
+) -> Option<wgpu::TextureFormat> {
+    let preferred: &[TextureFormat] = match color {
+        SurfaceColor::Srgb => {
+            &[TextureFormat::Rgba8UnormSrgb, TextureFormat::Bgra8UnormSrgb]
+        }
+        SurfaceColor::Linear => {
+            &[TextureFormat::Rgba8Unorm, TextureFormat::Bgra8Unorm]
+        }
+        SurfaceColor::Hdr => {
+            &[TextureFormat::Rgba16Float, TextureFormat::Rgb10a2Unorm]
+        }
+    };
+
+    preferred
+        .iter()
+        .find(|format| formats.contains(format))
+        .copied()
+}
+
+/// If the surface can present with present_mode.
+pub fn supports_present_mode(
+    caps: &wgpu::SurfaceCapabilities,
+    present_mode: wgpu::PresentMode,
+) -> bool {
+    // The Auto modes fall back to Fifo which every surface supports.
+    matches!(
+        present_mode,
+        wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync
+    ) || caps.present_modes.contains(&present_mode)
+}
+
+pub(crate) fn create_depth_texture(
+    gpu_device: &GpuDevice,
+    size: PhysicalSize<f32>,
+    sample_count: u32,
+) -> wgpu::TextureView {
+    let size = wgpu::Extent3d {
+        width: size.width as u32,
+        height: size.height as u32,
+        depth_or_array_layers: 1,
+    };
+
+    // Multisampled textures can not be copied into and GL needs them to
+    // be plain attachments to share a framebuffer with the msaa texture.
+    let usage = if sample_count > 1 {
+        wgpu::TextureUsages::RENDER_ATTACHMENT
+    } else {
+        wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::COPY_DST
+    };
+
+    let texture =
+        gpu_device
+            .device()
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("depth texture"),
+                size,
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Depth32Float,
+                usage,
+                view_formats: &[TextureFormat::Depth32Float],
+            });
 
-        let texture =
-            gpu_device
-                .device()
-                .create_texture(&wgpu::TextureDescriptor {
-                    label: Some("depth texture"),
-                    size,
-                    mip_level_count: 1,
-                    sample_count: 1,
-                    dimension: wgpu::TextureDimension::D2,
-                    format: wgpu::TextureFormat::Depth32Float,
-                    usage: wgpu::TextureUsages::TEXTURE_BINDING
-                        | wgpu::TextureUsages::RENDER_ATTACHMENT
-                        | wgpu::TextureUsages::COPY_DST,
-                    view_formats: &[TextureFormat::Depth32Float],
-                });
-
-        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// Creates the multisampled color texture passes render into before it is
+/// resolved into the target. Returns None when MSAA is off.
+pub(crate) fn create_msaa_texture(
+    gpu_device: &GpuDevice,
+    size: PhysicalSize<f32>,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+) -> Option<wgpu::TextureView> {
+    if sample_count <= 1 {
+        return None;
+    }
+
+    let texture =
+        gpu_device
+            .device()
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("msaa texture"),
+                size: wgpu::Extent3d {
+                    width: size.width as u32,
+                    height: size.height as u32,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[format],
+            });
+
+    Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+}
+
+/// Checks that the device can render format and the depth buffer with
+/// sample_count samples.
+pub(crate) fn validate_sample_count(
+    adapter: &wgpu::Adapter,
+    gpu_device: &GpuDevice,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+) -> Result<(), AscendingError> {
+    if ![1, 2, 4, 8].contains(&sample_count) {
+        return Err(AscendingError::UnsupportedSampleCount(sample_count));
+    }
+
+    let supported =
+        [format, TextureFormat::Depth32Float].iter().all(|format| {
+            format_features(adapter, gpu_device, *format)
+                .flags
+                .sample_count_supported(sample_count)
+        });
+
+    if supported {
+        Ok(())
+    } else {
+        Err(AscendingError::UnsupportedSampleCount(sample_count))
     }
 }
 
@@ -160,6 +517,14 @@ pub trait AdapterExt {
         trace_path: Option<&Path>,
         present_mode: wgpu::PresentMode,
     ) -> Result<GpuRenderer, AscendingError>;
+
+    async fn create_headless_renderer(
+        self,
+        device_descriptor: &wgpu::DeviceDescriptor,
+        trace_path: Option<&Path>,
+        size: PhysicalSize<u32>,
+        format: wgpu::TextureFormat,
+    ) -> Result<GpuRenderer, AscendingError>;
 }
 
 #[async_trait]
@@ -180,28 +545,38 @@ impl AdapterExt for wgpu::Adapter {
         let surface = unsafe { instance.create_surface(&window).unwrap() };
         let caps = surface.get_capabilities(&self);
 
-        println!("{:?}", caps.formats);
+        log::info!("surface formats: {:?}", caps.formats);
 
-        let rgba = caps
-            .formats
-            .iter()
-            .position(|v| *v == TextureFormat::Rgba8UnormSrgb);
-        let bgra = caps
-            .formats
-            .iter()
-            .position(|v| *v == TextureFormat::Bgra8UnormSrgb);
+        // Prefer sRGB but take whatever the surface has over failing.
+        let format = pick_surface_format(&caps.formats, SurfaceColor::Srgb)
+            .or_else(|| {
+                pick_surface_format(&caps.formats, SurfaceColor::Linear)
+            })
+            .or_else(|| caps.formats.first().copied())
+            .ok_or(AscendingError::IncompatibleSurface)?;
+
+        log::info!("surface format: {:?}", format);
+
+        let present_mode = if supports_present_mode(&caps, present_mode) {
+            present_mode
+        } else {
+            log::warn!(
+                "present mode {present_mode:?} is not supported, using \
+                 AutoVsync"
+            );
+            wgpu::PresentMode::AutoVsync
+        };
 
-        let format = if let Some(pos) = rgba {
-            caps.formats[pos]
-        } else if let Some(pos) = bgra {
-            caps.formats[pos]
+        // COPY_SRC lets the frame be read back for screenshots.
+        let usage = if caps.usages.contains(wgpu::TextureUsages::COPY_SRC) {
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC
         } else {
-            panic!("Your Rendering Device does not support Bgra8UnormSrgb or Rgba8UnormSrgb");
+            wgpu::TextureUsages::RENDER_ATTACHMENT
         };
 
-        println!("surface format: {:?}", format);
         let surface_config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            usage,
             format,
             width: size.width,
             height: size.height,
@@ -219,14 +594,33 @@ impl AdapterExt for wgpu::Adapter {
                 surface_format: format,
                 size: PhysicalSize::new(size.width as f32, size.height as f32),
                 surface_config,
+                surface_lost: false,
             },
-            GpuDevice { device, queue },
+            GpuDevice::new(device, queue),
         );
 
         // Creates the shader rendering pipelines for each renderer.
         renderer.create_pipelines(renderer.surface_format());
         Ok(renderer)
     }
+
+    async fn create_headless_renderer(
+        self,
+        device_descriptor: &wgpu::DeviceDescriptor,
+        trace_path: Option<&Path>,
+        size: PhysicalSize<u32>,
+        format: wgpu::TextureFormat,
+    ) -> Result<GpuRenderer, AscendingError> {
+        let (device, queue) =
+            self.request_device(device_descriptor, trace_path).await?;
+        let gpu_device = GpuDevice::new(device, queue);
+        let headless = GpuHeadless::new(self, &gpu_device, size, format);
+        let mut renderer = GpuRenderer::new_headless(headless, gpu_device);
+
+        // Creates the shader rendering pipelines for each renderer.
+        renderer.create_pipelines(renderer.surface_format());
+        Ok(renderer)
+    }
 }
 
 #[async_trait]
@@ -239,6 +633,15 @@ pub trait InstanceExt {
         trace_path: Option<&Path>,
         present_mode: wgpu::PresentMode,
     ) -> Result<GpuRenderer, AscendingError>;
+
+    async fn create_headless_device(
+        &self,
+        request_adapter_options: &wgpu::RequestAdapterOptions,
+        device_descriptor: &wgpu::DeviceDescriptor,
+        trace_path: Option<&Path>,
+        size: PhysicalSize<u32>,
+        format: wgpu::TextureFormat,
+    ) -> Result<GpuRenderer, AscendingError>;
 }
 
 #[async_trait]
@@ -263,4 +666,45 @@ impl InstanceExt for wgpu::Instance {
             )
             .await
     }
+
+    async fn create_headless_device(
+        &self,
+        request_adapter_options: &wgpu::RequestAdapterOptions,
+        device_descriptor: &wgpu::DeviceDescriptor,
+        trace_path: Option<&Path>,
+        size: PhysicalSize<u32>,
+        format: wgpu::TextureFormat,
+    ) -> Result<GpuRenderer, AscendingError> {
+        let adapter = self
+            .request_adapter(request_adapter_options)
+            .await
+            .ok_or(AscendingError::AdapterNotFound)?;
+
+        adapter
+            .create_headless_renderer(
+                device_descriptor,
+                trace_path,
+                size,
+                format,
+            )
+            .await
+    }
+}
+
+/// What the device allows format to be used for.
+fn format_features(
+    adapter: &wgpu::Adapter,
+    gpu_device: &GpuDevice,
+    format: wgpu::TextureFormat,
+) -> wgpu::TextureFormatFeatures {
+    let features = gpu_device.device().features();
+
+    // Without this feature the device only allows what WebGPU guarantees.
+    if features
+        .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+    {
+        adapter.get_texture_format_features(format)
+    } else {
+        format.guaranteed_format_features(features)
+    }
 }
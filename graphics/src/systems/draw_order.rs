@@ -1,18 +1,57 @@
-use crate::Vec3;
+use crate::{Bounds, Vec3, WorldBounds};
 use generational_array::GenerationalIndex;
 use std::cmp::Ordering;
 
 pub type Index = GenerationalIndex;
 
-#[derive(Copy, Clone, PartialEq, Eq, Default)]
+/// The order the position axes are compared in when sorting DrawOrders
+/// of the same alpha, layer and z. Xyz compares x first, then y, then z.
+/// Yxz is useful for top down games where lower objects overlap higher ones.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub enum SortAxis {
+    #[default]
+    Xyz,
+    Xzy,
+    Yxz,
+    Yzx,
+    Zxy,
+    Zyx,
+}
+
+impl SortAxis {
+    fn axes(self) -> [usize; 3] {
+        match self {
+            SortAxis::Xyz => [0, 1, 2],
+            SortAxis::Xzy => [0, 2, 1],
+            SortAxis::Yxz => [1, 0, 2],
+            SortAxis::Yzx => [1, 2, 0],
+            SortAxis::Zxy => [2, 0, 1],
+            SortAxis::Zyx => [2, 1, 0],
+        }
+    }
+}
+
+/// The sort key of an object. Opaque objects are sorted by layer, then
+/// front to back by z. Translucent objects are drawn after every opaque
+/// one, sorted back to front by z and then by layer. Sorts are stable so
+/// objects with equal keys are drawn in the order they were added.
+#[derive(Copy, Clone, Debug, Default)]
 pub struct DrawOrder {
     pub layer: u32, // lowest to highest. for spliting different types into layers.
-    pub alpha: bool, // alpha always is highest
-    pub x: u32,     // Lower is lower
-    pub y: u32,     // higher is lower
-    pub z: u32,     // lower is higher
+    pub alpha: bool, // translucent, drawn after every opaque object.
+    pub x: f32,     // Lower is lower
+    pub y: f32,     // higher is lower
+    pub z: f32,     // lower is higher
+}
+
+impl PartialEq for DrawOrder {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
 }
 
+impl Eq for DrawOrder {}
+
 impl PartialOrd for DrawOrder {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -21,23 +60,61 @@ impl PartialOrd for DrawOrder {
 
 impl Ord for DrawOrder {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.layer
-            .cmp(&other.layer)
-            .then(self.alpha.cmp(&other.alpha))
-            .then(self.x.cmp(&other.x))
-            .then(self.y.cmp(&other.y).reverse())
-            .then(self.z.cmp(&other.z).reverse())
+        self.cmp_axis(other, SortAxis::Xyz)
     }
 }
 
 impl DrawOrder {
     pub fn new(alpha: bool, pos: &Vec3, layer: u32) -> Self {
+        // Adding 0.0 turns -0.0 into 0.0 so they sort as equal.
         Self {
             layer,
             alpha,
-            x: (pos.x * 100.0) as u32,
-            y: (pos.y * 100.0) as u32,
-            z: (pos.z * 100.0) as u32,
+            x: pos.x + 0.0,
+            y: pos.y + 0.0,
+            z: pos.z + 0.0,
+        }
+    }
+
+    /// Compares the position axes in the order given by axis.
+    pub fn cmp_axis(&self, other: &Self, axis: SortAxis) -> Ordering {
+        let [first, second, third] = axis.axes();
+        let layer = self.layer.cmp(&other.layer);
+
+        self.alpha
+            .cmp(&other.alpha)
+            .then_with(|| {
+                // Translucent objects blend with what is behind them so z
+                // has to decide their order.
+                if self.alpha {
+                    self.cmp_depth(other).then(layer)
+                } else {
+                    layer.then_with(|| self.cmp_depth(other))
+                }
+            })
+            .then_with(|| self.cmp_position(other, first))
+            .then_with(|| self.cmp_position(other, second))
+            .then_with(|| self.cmp_position(other, third))
+    }
+
+    /// Lower z is in front. Opaque objects are drawn front to back so the
+    /// depth test skips what is hidden. Translucent objects are drawn back
+    /// to front as they do not write depth.
+    fn cmp_depth(&self, other: &Self) -> Ordering {
+        let order = self.z.total_cmp(&other.z);
+
+        if self.alpha {
+            order.reverse()
+        } else {
+            order
+        }
+    }
+
+    fn cmp_position(&self, other: &Self, axis: usize) -> Ordering {
+        match axis {
+            0 => self.x.total_cmp(&other.x),
+            1 => self.y.total_cmp(&other.y).reverse(),
+            _ => self.z.total_cmp(&other.z).reverse(),
         }
     }
 }
@@ -48,6 +125,11 @@ pub struct OrderedIndex {
     pub(crate) index: Index,
     pub(crate) index_count: u32,
     pub(crate) index_max: u32,
+    /// Where the object is drawn. None is never culled.
+    pub(crate) bounds: Option<WorldBounds>,
+    pub(crate) use_camera: bool,
+    /// Area of the screen the object is drawn within.
+    pub(crate) clip: Option<Bounds>,
 }
 
 impl PartialOrd for OrderedIndex {
@@ -71,12 +153,84 @@ impl Ord for OrderedIndex {
 }
 
 impl OrderedIndex {
+    pub(crate) fn cmp_axis(&self, other: &Self, axis: SortAxis) -> Ordering {
+        self.order.cmp_axis(&other.order, axis)
+    }
+
     pub fn new(order: DrawOrder, index: Index, index_max: u32) -> Self {
         Self {
             order,
             index,
             index_count: 0,
             index_max,
+            bounds: None,
+            use_camera: true,
+            clip: None,
         }
     }
+
+    /// Draws the object only within clip. Kept within the clip pushed to
+    /// the GpuRenderer when added.
+    pub fn with_clip(mut self, clip: Option<Bounds>) -> Self {
+        self.clip = clip;
+        self
+    }
+
+    /// Sets the area the object is drawn within so it can be skipped when
+    /// it is not on screen.
+    pub fn with_bounds(
+        mut self,
+        bounds: WorldBounds,
+        use_camera: bool,
+    ) -> Self {
+        self.bounds = Some(bounds);
+        self.use_camera = use_camera;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(x: f32, y: f32) -> DrawOrder {
+        DrawOrder::new(false, &Vec3::new(x, y, 1.0), 1)
+    }
+
+    #[test]
+    fn negative_positions() {
+        assert!(order(-2.0, 0.0) < order(-1.5, 0.0));
+        assert!(order(-1.5, 0.0) < order(0.25, 0.0));
+        assert!(order(0.25, 0.0) < order(0.26, 0.0));
+        assert_eq!(order(-0.0, 0.0), order(0.0, 0.0));
+    }
+
+    #[test]
+    fn sort_axis() {
+        // Higher y is drawn first so lower objects overlap it.
+        assert_eq!(
+            order(-1.0, -1.0).cmp_axis(&order(1.0, 1.0), SortAxis::Yxz),
+            Ordering::Greater
+        );
+        assert_eq!(
+            order(-1.0, -1.0).cmp_axis(&order(1.0, 1.0), SortAxis::Xyz),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn translucent_after_opaque() {
+        let order = |alpha: bool, z: f32, layer: u32| {
+            DrawOrder::new(alpha, &Vec3::new(0.0, 0.0, z), layer)
+        };
+
+        // Opaque by layer then front to back.
+        assert!(order(false, 3.0, 1) < order(false, 2.0, 2));
+        assert!(order(false, 2.0, 1) < order(false, 3.0, 1));
+        // Translucent after every opaque object, back to front then by
+        // layer.
+        assert!(order(false, 3.0, 2) < order(true, 5.0, 1));
+        assert!(order(true, 5.0, 2) < order(true, 4.0, 1));
+        assert!(order(true, 4.0, 1) < order(true, 4.0, 2));
+    }
 }
@@ -1,12 +1,24 @@
-use crate::{Buffer, BufferLayout, GpuDevice, GpuRenderer, OrderedIndex};
+use crate::{
+    Bounds, Buffer, BufferLayout, GpuDevice, GpuRenderer, OrderedIndex,
+    SortAxis,
+};
 use std::ops::Range;
 
 //This Holds onto all the instances Compressed into a byte array.
 pub struct InstanceBuffer<K: BufferLayout> {
     pub buffers: Vec<OrderedIndex>,
     pub buffer: Buffer<K>,
+    /// Instances drawn within the same clip in DrawOrder. The opaque
+    /// ranges come first.
+    pub clips: Vec<(Option<Bounds>, Range<u32>)>,
+    /// Index of the first translucent range within clips.
+    pub translucent: usize,
+    /// Which position axis is sorted on first.
+    pub sort_axis: SortAxis,
     // this is a calculation of the buffers size when being marked as ready to add into the buffer.
     needed_size: usize,
+    /// Writes every instance on the next finalize.
+    rewrite: bool,
 }
 
 impl<K: BufferLayout> InstanceBuffer<K> {
@@ -21,11 +33,31 @@ impl<K: BufferLayout> InstanceBuffer<K> {
                 wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
                 Some("Instance Buffer"),
             ),
+            clips: Vec::new(),
+            translucent: 0,
+            sort_axis: SortAxis::default(),
             needed_size: 0,
+            rewrite: false,
         }
     }
 
+    /// Adds the object to be drawn unless it is outside the view bounds.
+    /// It is clipped to the GpuRenderer's current clip.
     pub fn add_buffer_store(
+        &mut self,
+        renderer: &GpuRenderer,
+        mut index: OrderedIndex,
+    ) {
+        if !renderer.is_visible(&index) {
+            return;
+        }
+
+        renderer.clip_index(&mut index);
+        self.add_index(renderer, index);
+    }
+
+    /// Adds an object already culled and clipped.
+    pub(crate) fn add_index(
         &mut self,
         renderer: &GpuRenderer,
         index: OrderedIndex,
@@ -38,18 +70,26 @@ impl<K: BufferLayout> InstanceBuffer<K> {
     }
 
     pub fn finalize(&mut self, renderer: &mut GpuRenderer) {
-        let mut changed = false;
+        let mut changed = std::mem::take(&mut self.rewrite);
         let mut pos = 0;
 
         if self.needed_size > self.buffer.max {
             self.resize(renderer.gpu_device(), self.needed_size / K::stride());
+            renderer.record_stats(|stats| stats.buffer_resizes += 1);
             changed = true;
         }
 
         self.buffer.count = self.needed_size / K::stride();
         self.buffer.len = self.needed_size;
+        renderer
+            .record_stats(|stats| stats.instances += self.buffer.count as u32);
+
+        let axis = self.sort_axis;
+
+        self.buffers.sort_by(|a, b| a.cmp_axis(b, axis));
+        self.clips.clear();
 
-        self.buffers.sort();
+        let mut translucent = None;
 
         for buf in &self.buffers {
             let mut write_buffer = false;
@@ -64,20 +104,60 @@ impl<K: BufferLayout> InstanceBuffer<K> {
                     write_buffer = true
                 }
 
+                let start = (pos / K::stride()) as u32;
+                let end = start + (store.store.len() / K::stride()) as u32;
+
+                let count = self.clips.len();
+
+                if buf.order.alpha && translucent.is_none() {
+                    translucent = Some(count);
+                }
+
+                match self.clips.last_mut() {
+                    Some((clip, range))
+                        if *clip == buf.clip && translucent != Some(count) =>
+                    {
+                        range.end = end
+                    }
+                    _ => self.clips.push((buf.clip, start..end)),
+                }
+
                 pos += store.store.len();
             }
 
             if write_buffer {
                 if let Some(store) = renderer.get_buffer(&buf.index) {
                     self.buffer.write(&renderer.device, &store.store, old_pos);
+                    renderer.record_stats(|stats| {
+                        stats.bytes_uploaded += store.store.len() as u64;
+                        stats.buffer_writes += 1;
+                    });
                 }
             }
         }
 
+        self.translucent = translucent.unwrap_or(self.clips.len());
         self.needed_size = 0;
         self.buffers.clear();
     }
 
+    /// The ranges of opaque instances drawn front to back.
+    pub fn opaque_clips(&self) -> &[(Option<Bounds>, Range<u32>)] {
+        &self.clips[..self.translucent]
+    }
+
+    /// The ranges of translucent instances drawn back to front.
+    pub fn translucent_clips(&self) -> &[(Option<Bounds>, Range<u32>)] {
+        &self.clips[self.translucent..]
+    }
+
+    /// Creates the buffer on a new device after the old one was lost.
+    /// Every instance is written again on the next finalize.
+    pub fn recreate(&mut self, gpu_device: &GpuDevice) {
+        self.buffer = Self::new(gpu_device).buffer;
+        self.rewrite = true;
+    }
+
     //private but resizes the buffer on the GPU when needed.
     fn resize(&mut self, gpu_device: &GpuDevice, capacity: usize) {
         let data = K::with_capacity(capacity, 0);
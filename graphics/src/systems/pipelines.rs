@@ -1,13 +1,49 @@
-use crate::{FxHashMap, GpuDevice, LayoutStorage};
+use crate::{
+    validate_wgsl, AscendingError, FxHashMap, GpuDevice, LayoutStorage,
+    ShaderFile,
+};
 use bytemuck::{Pod, Zeroable};
 use std::any::{Any, TypeId};
 
+/// Which instances of a renderer a pipeline draws. Opaque instances are
+/// drawn first and write depth. Translucent instances are drawn after
+/// them and only test depth so overlapping ones all blend.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Pod, Zeroable)]
+pub struct AlphaPass(u32);
+
+impl AlphaPass {
+    pub const OPAQUE: AlphaPass = AlphaPass(0);
+    pub const TRANSLUCENT: AlphaPass = AlphaPass(1);
+    pub const ALL: [AlphaPass; 2] = [AlphaPass::OPAQUE, AlphaPass::TRANSLUCENT];
+
+    pub fn new(translucent: bool) -> Self {
+        AlphaPass(u32::from(translucent))
+    }
+
+    pub fn is_translucent(self) -> bool {
+        self == AlphaPass::TRANSLUCENT
+    }
+
+    /// The depth state of pipelines drawing this pass.
+    pub fn depth_stencil(self) -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format: wgpu::TextureFormat::Depth32Float,
+            depth_write_enabled: !self.is_translucent(),
+            depth_compare: wgpu::CompareFunction::LessEqual,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }
+    }
+}
+
 pub trait PipeLineLayout: Pod + Zeroable {
     fn create_layout(
         &self,
         gpu_device: &mut GpuDevice,
         layouts: &mut LayoutStorage,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
     ) -> wgpu::RenderPipeline;
 
     fn layout_key(&self) -> (TypeId, Vec<u8>) {
@@ -19,14 +55,32 @@ pub trait PipeLineLayout: Pod + Zeroable {
     }
 }
 
+type PipelineBuilder = Box<
+    dyn Fn(
+        &mut GpuDevice,
+        &mut LayoutStorage,
+        wgpu::TextureFormat,
+        u32,
+    ) -> wgpu::RenderPipeline,
+>;
+
+/// What a pipeline was built from so it can be rebuilt later.
+struct PipelineSource {
+    builder: PipelineBuilder,
+    /// (name, built in source) of each shader file in the order joined.
+    files: Vec<(String, String)>,
+}
+
 pub struct PipelineStorage {
     pub(crate) map: FxHashMap<(TypeId, Vec<u8>), wgpu::RenderPipeline>,
+    sources: FxHashMap<(TypeId, Vec<u8>), PipelineSource>,
 }
 
 impl PipelineStorage {
     pub fn new() -> Self {
         Self {
             map: FxHashMap::default(),
+            sources: FxHashMap::default(),
         }
     }
 
@@ -35,16 +89,150 @@ impl PipelineStorage {
         device: &mut GpuDevice,
         layout_storage: &mut LayoutStorage,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
         pipeline: K,
     ) {
         let key = pipeline.layout_key();
 
+        device.shaders.take_used();
         self.map.insert(
+            key.clone(),
+            pipeline.create_layout(
+                device,
+                layout_storage,
+                surface_format,
+                sample_count,
+            ),
+        );
+        self.sources.insert(
             key,
-            pipeline.create_layout(device, layout_storage, surface_format),
+            PipelineSource {
+                builder: Box::new(
+                    move |device, layout_storage, format, sample_count| {
+                        pipeline.create_layout(
+                            device,
+                            layout_storage,
+                            format,
+                            sample_count,
+                        )
+                    },
+                ),
+                files: device.shaders.take_used(),
+            },
         );
     }
 
+    /// Rebuilds every stored pipeline for the new format and sample count.
+    pub fn recreate_pipelines(
+        &mut self,
+        device: &mut GpuDevice,
+        layout_storage: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) {
+        for (key, source) in &self.sources {
+            self.map.insert(
+                key.clone(),
+                (source.builder)(
+                    device,
+                    layout_storage,
+                    surface_format,
+                    sample_count,
+                ),
+            );
+        }
+
+        device.shaders.take_used();
+    }
+
+    /// Replaces the shader file name with source and rebuilds the pipelines
+    /// using it. The shaders are validated first and if they or the new
+    /// pipelines fail the old pipelines and source are kept.
+    /// Returns how many pipelines were rebuilt.
+    pub fn reload_shader(
+        &mut self,
+        device: &mut GpuDevice,
+        layout_storage: &mut LayoutStorage,
+        surface_format: wgpu::TextureFormat,
+        sample_count: u32,
+        name: &str,
+        source: String,
+    ) -> Result<usize, AscendingError> {
+        let keys: Vec<(TypeId, Vec<u8>)> = self
+            .sources
+            .iter()
+            .filter(|(_, pipeline)| {
+                pipeline.files.iter().any(|(file, _)| file == name)
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in &keys {
+            let files: Vec<ShaderFile> = self.sources[key]
+                .files
+                .iter()
+                .map(|(file, built_in)| {
+                    let source = if file == name {
+                        source.as_str()
+                    } else {
+                        device.shaders.source(file, built_in)
+                    };
+
+                    ShaderFile::new(file, source)
+                })
+                .collect();
+
+            validate_wgsl(&files)?;
+        }
+
+        if keys.is_empty() {
+            return Ok(0);
+        }
+
+        let old = device.shaders.overrides.insert(name.to_string(), source);
+
+        // naga can not check the shader against the pipeline's layouts so
+        // we catch anything wgpu still finds wrong.
+        device
+            .device()
+            .push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let pipelines: Vec<wgpu::RenderPipeline> = keys
+            .iter()
+            .map(|key| {
+                (self.sources[key].builder)(
+                    device,
+                    layout_storage,
+                    surface_format,
+                    sample_count,
+                )
+            })
+            .collect();
+
+        device.shaders.take_used();
+
+        if let Some(error) =
+            futures::executor::block_on(device.device().pop_error_scope())
+        {
+            match old {
+                Some(old) => {
+                    device.shaders.overrides.insert(name.to_string(), old)
+                }
+                None => device.shaders.overrides.remove(name),
+            };
+
+            return Err(AscendingError::WGpu(error));
+        }
+
+        let count = pipelines.len();
+
+        for (key, pipeline) in keys.into_iter().zip(pipelines) {
+            self.map.insert(key, pipeline);
+        }
+
+        Ok(count)
+    }
+
     pub fn get_pipeline<K: PipeLineLayout>(
         &self,
         pipeline: K,
@@ -53,6 +241,13 @@ impl PipelineStorage {
 
         self.map.get(&key)
     }
+
+    pub(crate) fn get_pipeline_by_key(
+        &self,
+        key: &(TypeId, Vec<u8>),
+    ) -> Option<&wgpu::RenderPipeline> {
+        self.map.get(key)
+    }
 }
 
 impl Default for PipelineStorage {
@@ -0,0 +1,414 @@
+use crate::{AscendingError, GpuRenderer, OtherError};
+use std::{collections::VecDeque, sync::mpsc, time::Instant};
+
+/// How many scopes can be timed on the GPU each frame. Scopes past this
+/// are timed on the CPU instead.
+const MAX_GPU_SCOPES: u32 = 64;
+
+/// How many frames of timestamps can wait on the GPU at once. Frames past
+/// this are timed on the CPU instead.
+const FRAMES_IN_FLIGHT: usize = 3;
+
+const TIMESTAMP_SIZE: u64 = std::mem::size_of::<u64>() as u64;
+
+/// The features the GPU needs for timestamps around render_* calls.
+pub const PROFILER_FEATURES: wgpu::Features = wgpu::Features::TIMESTAMP_QUERY
+    .union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES);
+
+/// Where a timing was measured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProfileSource {
+    /// Time the GPU spent running the scope's commands.
+    Gpu,
+    /// Time the CPU spent in the scope. For render_* calls this is only
+    /// the time taken to record the commands.
+    Cpu,
+}
+
+/// The rolling timings of a single scope in milliseconds.
+#[derive(Clone, Debug)]
+pub struct ProfileTiming {
+    pub label: &'static str,
+    pub source: ProfileSource,
+    samples: VecDeque<f32>,
+}
+
+impl ProfileTiming {
+    /// The timing of the last frame the scope ran in.
+    pub fn last_ms(&self) -> f32 {
+        self.samples.back().copied().unwrap_or_default()
+    }
+
+    pub fn average_ms(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+
+        self.samples.iter().sum::<f32>() / self.samples.len() as f32
+    }
+
+    pub fn max_ms(&self) -> f32 {
+        self.samples.iter().copied().fold(0.0, f32::max)
+    }
+
+    /// The kept timings from oldest to newest.
+    pub fn samples(&self) -> &VecDeque<f32> {
+        &self.samples
+    }
+}
+
+enum ScopeTime {
+    /// Index of the scope's first timestamp query.
+    Query(u32),
+    Ms(f32),
+}
+
+struct FrameScope {
+    label: &'static str,
+    time: ScopeTime,
+}
+
+/// A frame whose timestamps are being mapped for reading.
+struct PendingFrame {
+    readback: usize,
+    /// The label and first timestamp query of each GPU scope.
+    scopes: Vec<(&'static str, u32)>,
+    query_count: u32,
+    mapped: mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>,
+}
+
+struct TimestampQueries {
+    set: wgpu::QuerySet,
+    resolve: wgpu::Buffer,
+    readbacks: Vec<wgpu::Buffer>,
+    /// Oldest first.
+    pending: VecDeque<PendingFrame>,
+    /// The readback buffer this frame resolves into, if one is free.
+    current: Option<usize>,
+    /// Nanoseconds per timestamp tick.
+    period: f32,
+}
+
+impl TimestampQueries {
+    fn free_readback(&self) -> Option<usize> {
+        (0..self.readbacks.len()).find(|readback| {
+            !self.pending.iter().any(|frame| frame.readback == *readback)
+        })
+    }
+}
+
+/// Times render_* calls and finalize calls each frame and keeps the last
+/// history frames of each for debug overlays.
+/// Uses timestamp queries when the device was created with
+/// PROFILER_FEATURES, otherwise every scope is timed on the CPU.
+/// GPU timings are read back without waiting, so they show up a frame or
+/// two after the frame they were measured in.
+pub struct GpuProfiler {
+    queries: Option<TimestampQueries>,
+    scopes: Vec<FrameScope>,
+    query_count: u32,
+    timings: Vec<ProfileTiming>,
+    history: usize,
+    /// When false scopes are ran without being timed.
+    pub enabled: bool,
+}
+
+impl GpuProfiler {
+    /// Creates a profiler keeping history frames of timings per scope.
+    pub fn new(renderer: &GpuRenderer, history: usize) -> Self {
+        let queries = renderer
+            .device()
+            .features()
+            .contains(PROFILER_FEATURES)
+            .then(|| {
+                let size = (MAX_GPU_SCOPES * 2) as u64 * TIMESTAMP_SIZE;
+
+                TimestampQueries {
+                    set: renderer.device().create_query_set(
+                        &wgpu::QuerySetDescriptor {
+                            label: Some("profiler query set"),
+                            ty: wgpu::QueryType::Timestamp,
+                            count: MAX_GPU_SCOPES * 2,
+                        },
+                    ),
+                    resolve: renderer.device().create_buffer(
+                        &wgpu::BufferDescriptor {
+                            label: Some("profiler resolve buffer"),
+                            size,
+                            usage: wgpu::BufferUsages::QUERY_RESOLVE
+                                | wgpu::BufferUsages::COPY_SRC,
+                            mapped_at_creation: false,
+                        },
+                    ),
+                    readbacks: (0..FRAMES_IN_FLIGHT)
+                        .map(|_| {
+                            renderer.device().create_buffer(
+                                &wgpu::BufferDescriptor {
+                                    label: Some("profiler readback buffer"),
+                                    size,
+                                    usage: wgpu::BufferUsages::COPY_DST
+                                        | wgpu::BufferUsages::MAP_READ,
+                                    mapped_at_creation: false,
+                                },
+                            )
+                        })
+                        .collect(),
+                    pending: VecDeque::with_capacity(FRAMES_IN_FLIGHT),
+                    current: None,
+                    period: renderer.queue().get_timestamp_period(),
+                }
+            });
+
+        Self {
+            queries,
+            scopes: Vec::new(),
+            query_count: 0,
+            timings: Vec::new(),
+            history: history.max(1),
+            enabled: true,
+        }
+    }
+
+    /// If render_* calls are timed on the GPU.
+    pub fn is_gpu(&self) -> bool {
+        self.queries.is_some()
+    }
+
+    /// Clears the scopes of the last frame. Call before any scopes.
+    pub fn begin_frame(&mut self) {
+        self.scopes.clear();
+        self.query_count = 0;
+
+        if let Some(queries) = &mut self.queries {
+            queries.current = queries.free_readback();
+        }
+    }
+
+    /// Times f on the CPU, for use around finalize and update calls.
+    pub fn profile_cpu<R>(
+        &mut self,
+        label: &'static str,
+        f: impl FnOnce() -> R,
+    ) -> R {
+        if !self.enabled {
+            return f();
+        }
+
+        let start = Instant::now();
+        let result = f();
+
+        self.scopes.push(FrameScope {
+            label,
+            time: ScopeTime::Ms(start.elapsed().as_secs_f32() * 1000.0),
+        });
+        result
+    }
+
+    /// Times the commands f records into pass, like a render_* call.
+    pub fn profile_pass<'a, R>(
+        &mut self,
+        pass: &mut wgpu::RenderPass<'a>,
+        label: &'static str,
+        f: impl FnOnce(&mut wgpu::RenderPass<'a>) -> R,
+    ) -> R {
+        if !self.enabled {
+            return f(pass);
+        }
+
+        match &self.queries {
+            Some(queries)
+                if queries.current.is_some()
+                    && self.query_count < MAX_GPU_SCOPES * 2 =>
+            {
+                let query = self.query_count;
+
+                pass.write_timestamp(&queries.set, query);
+                let result = f(pass);
+                pass.write_timestamp(&queries.set, query + 1);
+
+                self.query_count += 2;
+                self.scopes.push(FrameScope {
+                    label,
+                    time: ScopeTime::Query(query),
+                });
+                result
+            }
+            _ => self.profile_cpu(label, || f(pass)),
+        }
+    }
+
+    /// Copies the frame's timestamps out of the query set. Call once
+    /// after the last profiled pass has ended and before submitting.
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) {
+        let Some((queries, readback)) = self
+            .queries
+            .as_ref()
+            .and_then(|queries| Some((queries, queries.current?)))
+        else {
+            return;
+        };
+
+        if self.query_count == 0 {
+            return;
+        }
+
+        encoder.resolve_query_set(
+            &queries.set,
+            0..self.query_count,
+            &queries.resolve,
+            0,
+        );
+        encoder.copy_buffer_to_buffer(
+            &queries.resolve,
+            0,
+            &queries.readbacks[readback],
+            0,
+            self.query_count as u64 * TIMESTAMP_SIZE,
+        );
+    }
+
+    /// Adds the frame's CPU scopes to the timings and starts reading
+    /// back its timestamps. Timings of earlier frames whose timestamps
+    /// have been read are added too. Call after the frame's commands are
+    /// submitted. This does not wait on the GPU.
+    pub fn end_frame(
+        &mut self,
+        renderer: &GpuRenderer,
+    ) -> Result<(), AscendingError> {
+        let mut gpu_scopes = Vec::new();
+
+        for scope in std::mem::take(&mut self.scopes) {
+            match scope.time {
+                ScopeTime::Query(query) => {
+                    gpu_scopes.push((scope.label, query))
+                }
+                ScopeTime::Ms(ms) => {
+                    self.push_timing(scope.label, ProfileSource::Cpu, ms)
+                }
+            }
+        }
+
+        if let Some(queries) = &mut self.queries {
+            if let Some(readback) = queries.current.take() {
+                if !gpu_scopes.is_empty() {
+                    let (sender, receiver) = mpsc::channel();
+
+                    queries.readbacks[readback]
+                        .slice(..self.query_count as u64 * TIMESTAMP_SIZE)
+                        .map_async(wgpu::MapMode::Read, move |result| {
+                            let _ = sender.send(result);
+                        });
+                    queries.pending.push_back(PendingFrame {
+                        readback,
+                        scopes: gpu_scopes,
+                        query_count: self.query_count,
+                        mapped: receiver,
+                    });
+                }
+            }
+        }
+
+        self.query_count = 0;
+        renderer.device().poll(wgpu::Maintain::Poll);
+        self.read_pending()
+    }
+
+    /// Adds the GPU timings of every frame whose timestamps are mapped,
+    /// oldest first.
+    fn read_pending(&mut self) -> Result<(), AscendingError> {
+        let Some(queries) = &mut self.queries else {
+            return Ok(());
+        };
+        let period = queries.period;
+        let mut finished = Vec::new();
+
+        while let Some(frame) = queries.pending.front() {
+            match frame.mapped.try_recv() {
+                Ok(result) => {
+                    let frame = queries.pending.pop_front().unwrap();
+
+                    result?;
+
+                    let readback = &queries.readbacks[frame.readback];
+                    let timestamps: Vec<u64> = bytemuck::cast_slice(
+                        &readback
+                            .slice(..frame.query_count as u64 * TIMESTAMP_SIZE)
+                            .get_mapped_range(),
+                    )
+                    .to_vec();
+
+                    readback.unmap();
+                    finished.extend(frame.scopes.into_iter().map(
+                        |(label, query)| {
+                            let start = timestamps[query as usize];
+                            let end = timestamps[query as usize + 1];
+
+                            (
+                                label,
+                                end.saturating_sub(start) as f32 * period
+                                    / 1_000_000.0,
+                            )
+                        },
+                    ));
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    queries.pending.pop_front();
+
+                    return Err(OtherError::new(
+                        "Profiler buffer was dropped before mapping.",
+                    )
+                    .into());
+                }
+            }
+        }
+
+        for (label, ms) in finished {
+            self.push_timing(label, ProfileSource::Gpu, ms);
+        }
+
+        Ok(())
+    }
+
+    /// The rolling timings of every scope in the order first seen.
+    pub fn timings(&self) -> &[ProfileTiming] {
+        &self.timings
+    }
+
+    pub fn timing(&self, label: &str) -> Option<&ProfileTiming> {
+        self.timings.iter().find(|timing| timing.label == label)
+    }
+
+    /// Forgets all timings kept so far.
+    pub fn clear(&mut self) {
+        self.timings.clear();
+    }
+
+    fn push_timing(
+        &mut self,
+        label: &'static str,
+        source: ProfileSource,
+        ms: f32,
+    ) {
+        let timing =
+            match self.timings.iter_mut().position(|timing| {
+                timing.label == label && timing.source == source
+            }) {
+                Some(index) => &mut self.timings[index],
+                None => {
+                    self.timings.push(ProfileTiming {
+                        label,
+                        source,
+                        samples: VecDeque::with_capacity(self.history),
+                    });
+                    self.timings.last_mut().unwrap()
+                }
+            };
+
+        if timing.samples.len() == self.history {
+            timing.samples.pop_front();
+        }
+
+        timing.samples.push_back(ms);
+    }
+}
@@ -0,0 +1,234 @@
+use crate::{AscendingError, GpuRenderer, GpuTarget, OtherError};
+use image::{ImageFormat, RgbaImage};
+use std::{path::Path, sync::mpsc};
+
+impl GpuRenderer {
+    /// Returns the Texture the current frame is being rendered into.
+    /// Returns None when the windowed renderer has no active frame.
+    pub fn frame_texture(&self) -> Option<&wgpu::Texture> {
+        match &self.target {
+            GpuTarget::Window(_) => {
+                self.frame.as_ref().map(|frame| &frame.texture)
+            }
+            GpuTarget::Headless(headless) => Some(&headless.texture),
+        }
+    }
+
+    /// Copies the current frame back from the GPU.
+    /// Call this after the frame's commands are submitted and before present().
+    pub fn capture_frame(&self) -> Result<RgbaImage, AscendingError> {
+        let texture = self.frame_texture().ok_or_else(|| {
+            OtherError::new(
+                "Frame does not Exist. Did you forget to update the renderer?",
+            )
+        })?;
+
+        self.read_texture(texture)
+    }
+
+    /// Captures the current frame and writes it to path as a PNG.
+    pub fn save_screenshot(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(), AscendingError> {
+        self.capture_frame()?
+            .save_with_format(path, ImageFormat::Png)?;
+        Ok(())
+    }
+
+    /// Reads back the first layer of a Texture.
+    pub fn read_texture(
+        &self,
+        texture: &wgpu::Texture,
+    ) -> Result<RgbaImage, AscendingError> {
+        self.read_texture_layer(texture, 0)
+    }
+
+    /// Reads back a single layer of a Texture into an RgbaImage.
+    /// The Texture must have been created with TextureUsages::COPY_SRC.
+    /// Bgra formats are swizzled to Rgba and R8 formats are expanded to grey.
+    /// Rgba16Float is converted from linear to sRGB with its colors clamped
+    /// to 0 to 1, and Rgb10a2Unorm is scaled down to 8 bits.
+    pub fn read_texture_layer(
+        &self,
+        texture: &wgpu::Texture,
+        layer: u32,
+    ) -> Result<RgbaImage, AscendingError> {
+        let format = texture.format();
+        let bytes_per_pixel = match format {
+            wgpu::TextureFormat::Rgba8Unorm
+            | wgpu::TextureFormat::Rgba8UnormSrgb
+            | wgpu::TextureFormat::Bgra8Unorm
+            | wgpu::TextureFormat::Bgra8UnormSrgb
+            | wgpu::TextureFormat::Rgb10a2Unorm => 4,
+            wgpu::TextureFormat::Rgba16Float => 8,
+            wgpu::TextureFormat::R8Unorm => 1,
+            _ => return Err(AscendingError::UnsupportedFormat(format)),
+        };
+
+        if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            return Err(AscendingError::Other(OtherError::new(
+                "Texture was not created with TextureUsages::COPY_SRC.",
+            )));
+        }
+
+        let (width, height) = (texture.width(), texture.height());
+        let unpadded_row = width * bytes_per_pixel;
+        // Rows copied into a buffer must be aligned to 256 bytes.
+        let padded_row = wgpu::util::align_to(
+            unpadded_row,
+            wgpu::COPY_BYTES_PER_ROW_ALIGNMENT,
+        );
+
+        let buffer = self.device().create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback buffer"),
+            size: (padded_row * height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.device().create_command_encoder(
+            &wgpu::CommandEncoderDescriptor {
+                label: Some("readback command encoder"),
+            },
+        );
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: 0,
+                    y: 0,
+                    z: layer,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        self.queue().submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device().poll(wgpu::Maintain::Wait);
+
+        receiver.recv().map_err(|_| {
+            OtherError::new("Readback buffer was dropped before mapping.")
+        })??;
+
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+
+        {
+            let data = slice.get_mapped_range();
+
+            for row in data.chunks(padded_row as usize) {
+                let row = &row[..unpadded_row as usize];
+
+                match format {
+                    wgpu::TextureFormat::Bgra8Unorm
+                    | wgpu::TextureFormat::Bgra8UnormSrgb => {
+                        for pixel in row.chunks_exact(4) {
+                            pixels.extend_from_slice(&[
+                                pixel[2], pixel[1], pixel[0], pixel[3],
+                            ]);
+                        }
+                    }
+                    wgpu::TextureFormat::R8Unorm => {
+                        for value in row {
+                            pixels.extend_from_slice(&[
+                                *value, *value, *value, 255,
+                            ]);
+                        }
+                    }
+                    wgpu::TextureFormat::Rgba16Float => {
+                        for pixel in row.chunks_exact(8) {
+                            let channel = |i: usize| {
+                                f16_to_f32(u16::from_le_bytes([
+                                    pixel[i * 2],
+                                    pixel[i * 2 + 1],
+                                ]))
+                            };
+
+                            pixels.extend_from_slice(&[
+                                linear_to_srgb(channel(0)),
+                                linear_to_srgb(channel(1)),
+                                linear_to_srgb(channel(2)),
+                                (channel(3).clamp(0.0, 1.0) * 255.0).round()
+                                    as u8,
+                            ]);
+                        }
+                    }
+                    wgpu::TextureFormat::Rgb10a2Unorm => {
+                        for pixel in row.chunks_exact(4) {
+                            let bits = u32::from_le_bytes([
+                                pixel[0], pixel[1], pixel[2], pixel[3],
+                            ]);
+                            let channel = |shift: u32| {
+                                ((bits >> shift & 0x3ff) * 255 + 511) / 1023
+                            };
+
+                            pixels.extend_from_slice(&[
+                                channel(0) as u8,
+                                channel(10) as u8,
+                                channel(20) as u8,
+                                (bits >> 30) as u8 * 85,
+                            ]);
+                        }
+                    }
+                    _ => pixels.extend_from_slice(row),
+                }
+            }
+        }
+
+        buffer.unmap();
+
+        RgbaImage::from_raw(width, height, pixels).ok_or_else(|| {
+            AscendingError::Other(OtherError::new(
+                "Readback size did not match the Texture size.",
+            ))
+        })
+    }
+}
+
+/// Converts the bits of a half precision float.
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f32::from(bits & 0x3ff);
+
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+/// Encodes a linear color channel as 8 bit sRGB.
+fn linear_to_srgb(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+    let value = if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+
+    (value * 255.0).round() as u8
+}
@@ -0,0 +1,161 @@
+use crate::{
+    AscendingError, GpuRenderer, GpuTarget, LayoutStorage, StaticBufferObject,
+};
+use std::{
+    error::Error,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use wgpu_core::{
+    binding_model,
+    device::{queue, DeviceError},
+    pipeline, resource,
+};
+
+/// Something that happened to the renderer's device or surface which game
+/// code may need to react to. Taken with GpuRenderer::take_events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RendererEvent {
+    /// The surface was lost and has been configured again.
+    SurfaceRecovered,
+    /// The device was lost and a new one created. The renderer's pipelines
+    /// and StaticBufferObject are rebuilt. Everything else made on the old
+    /// device needs its recreate called, like Systems, atlases, the
+    /// renderers, PostProcess and RenderTargets. Atlases only write their
+    /// images again with Atlas::retain_uploads set. GpuProfilers and
+    /// PostEffects given a bind group with with_bind_group need making
+    /// again.
+    DeviceRecovered,
+}
+
+/// Returns a flag set when the device reports it was lost. Any other error
+/// panics like wgpu's default handler.
+pub(crate) fn watch_device_lost(device: &wgpu::Device) -> Arc<AtomicBool> {
+    let lost = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&lost);
+
+    device.on_uncaptured_error(Box::new(move |error| {
+        if is_device_lost(&error) {
+            flag.store(true, Ordering::Relaxed);
+        } else {
+            panic!("wgpu error: {error}");
+        }
+    }));
+
+    lost
+}
+
+fn is_device_lost(error: &wgpu::Error) -> bool {
+    let mut source: Option<&(dyn Error + 'static)> = Some(error);
+
+    // wgpu only reports the loss through the errors of later calls.
+    while let Some(error) = source {
+        if matches!(device_error(error), Some(DeviceError::Lost)) {
+            return true;
+        }
+
+        source = error.source();
+    }
+
+    false
+}
+
+/// The DeviceError of error if it is one, or one of the errors of the
+/// calls a lost device fails. Those wrap it transparently so it never
+/// shows up in the source chain by itself.
+fn device_error<'a>(
+    error: &'a (dyn Error + 'static),
+) -> Option<&'a DeviceError> {
+    macro_rules! wrapped {
+        ($($module:ident::$error:ident => $variant:ident),* $(,)?) => {
+            $(
+                if let Some($module::$error::$variant(device)) =
+                    error.downcast_ref::<$module::$error>()
+                {
+                    return Some(device);
+                }
+            )*
+        };
+    }
+
+    if let Some(device) = error.downcast_ref::<DeviceError>() {
+        return Some(device);
+    }
+
+    wrapped!(
+        resource::CreateBufferError => Device,
+        resource::CreateTextureError => Device,
+        resource::CreateSamplerError => Device,
+        resource::CreateQuerySetError => Device,
+        resource::BufferAccessError => Device,
+        queue::QueueWriteError => Queue,
+        queue::QueueSubmitError => Queue,
+        binding_model::CreateBindGroupLayoutError => Device,
+        binding_model::CreateBindGroupError => Device,
+        binding_model::CreatePipelineLayoutError => Device,
+        pipeline::CreateShaderModuleError => Device,
+        pipeline::CreateRenderPipelineError => Device,
+        pipeline::CreateComputePipelineError => Device,
+    );
+
+    None
+}
+
+impl GpuRenderer {
+    /// If the device was lost. update recovers it before the next frame.
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Relaxed)
+    }
+
+    /// The events since the last call, oldest first.
+    pub fn take_events(&mut self) -> Vec<RendererEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Requests a new device and queue with the same features and limits,
+    /// then rebuilds the frame target, depth buffer, pipelines and
+    /// StaticBufferObject on it. Sends RendererEvent::DeviceRecovered.
+    pub fn recover_device(&mut self) -> Result<(), AscendingError> {
+        let descriptor = wgpu::DeviceDescriptor {
+            label: Some("recovered device"),
+            features: self.device.features,
+            limits: self.device.limits.clone(),
+        };
+        let (device, queue) = futures::executor::block_on(
+            self.adapter().request_device(&descriptor, None),
+        )?;
+
+        self.device.device = device;
+        self.device.queue = queue;
+        self.device_lost = watch_device_lost(&self.device.device);
+        self.layout_storage = LayoutStorage::new();
+        self.frame = None;
+
+        match &mut self.target {
+            GpuTarget::Window(window) => {
+                window.recreate(&self.device);
+                self.framebuffer = None;
+            }
+            GpuTarget::Headless(headless) => {
+                headless.recreate(&self.device);
+                self.framebuffer = Some(headless.create_view());
+            }
+        }
+
+        let surface_format = self.surface_format();
+
+        self.update_depth_texture();
+        self.pipeline_storage.recreate_pipelines(
+            &mut self.device,
+            &mut self.layout_storage,
+            surface_format,
+            self.sample_count,
+        );
+        self.buffer_object = StaticBufferObject::create_buffer(&self.device);
+        self.events.push(RendererEvent::DeviceRecovered);
+
+        Ok(())
+    }
+}
@@ -1,26 +1,52 @@
 use crate::{
-    AscendingError, BufferPass, BufferStore, GpuDevice, GpuWindow, Index,
-    Layout, LayoutStorage, OtherError, PipeLineLayout, PipelineStorage,
-    StaticBufferObject,
+    create_msaa_texture, pick_surface_format, validate_sample_count,
+    watch_device_lost, AlphaPass, AscendingError, Bounds, BufferPass,
+    BufferStore, GpuDevice, GpuHeadless, GpuWindow, Index, Layout,
+    LayoutStorage, OrderedIndex, OtherError, PipeLineLayout, PipelineStorage,
+    RenderStats, RendererEvent, ShaderReload, ShaderWatcher,
+    StaticBufferObject, SurfaceColor, ViewBounds,
 };
 use cosmic_text::FontSystem;
 use generational_array::{
     GenerationalArray, GenerationalArrayResult, GenerationalArrayResultMut,
 };
-use std::rc::Rc;
+use std::{
+    cell::Cell,
+    rc::Rc,
+    sync::{atomic::AtomicBool, Arc},
+};
 
 use winit::{dpi::PhysicalSize, event::Event, window::Window};
 
+/// Where the renderer presents its frames to.
+pub enum GpuTarget {
+    /// Renders into a Window's Surface.
+    Window(GpuWindow),
+    /// Renders into an offscreen Texture. Used for tests and tools.
+    Headless(GpuHeadless),
+}
+
 ///Handles the Window, Device and buffer stores.
 pub struct GpuRenderer {
-    pub(crate) window: GpuWindow,
+    pub(crate) target: GpuTarget,
     pub(crate) device: GpuDevice,
     pub(crate) buffer_stores: GenerationalArray<BufferStore>,
     pub(crate) layout_storage: LayoutStorage,
     pub(crate) pipeline_storage: PipelineStorage,
     pub(crate) depthbuffer: wgpu::TextureView,
+    /// Multisampled color buffer resolved into the frame. None without MSAA.
+    pub(crate) msaabuffer: Option<wgpu::TextureView>,
+    pub(crate) sample_count: u32,
     pub(crate) framebuffer: Option<wgpu::TextureView>,
     pub(crate) frame: Option<wgpu::SurfaceTexture>,
+    /// A Cell so draw calls can be counted while rendering.
+    pub(crate) stats: Cell<RenderStats>,
+    /// Objects outside of these are not added to the renderers.
+    pub(crate) view_bounds: Option<ViewBounds>,
+    pub(crate) clip_stack: Vec<Bounds>,
+    /// Set by the device's error handler when it is lost.
+    pub(crate) device_lost: Arc<AtomicBool>,
+    pub(crate) events: Vec<RendererEvent>,
     pub font_sys: FontSystem,
     pub buffer_object: StaticBufferObject,
 }
@@ -47,32 +73,90 @@ where
 
 impl GpuRenderer {
     pub fn new(window: GpuWindow, device: GpuDevice) -> Self {
+        let depth_buffer = window.create_depth_texture(&device, 1);
+
+        Self::create(GpuTarget::Window(window), device, depth_buffer, None)
+    }
+
+    /// Creates a Renderer without a Window. Frames are rendered into the
+    /// offscreen texture of the GpuHeadless which is always available
+    /// through frame_buffer().
+    pub fn new_headless(headless: GpuHeadless, device: GpuDevice) -> Self {
+        let depth_buffer = headless.create_depth_texture(&device, 1);
+        let frame_buffer = headless.create_view();
+
+        Self::create(
+            GpuTarget::Headless(headless),
+            device,
+            depth_buffer,
+            Some(frame_buffer),
+        )
+    }
+
+    fn create(
+        target: GpuTarget,
+        device: GpuDevice,
+        depthbuffer: wgpu::TextureView,
+        framebuffer: Option<wgpu::TextureView>,
+    ) -> Self {
         let buffer_object = StaticBufferObject::create_buffer(&device);
-        let depth_buffer = window.create_depth_texture(&device);
+        let device_lost = watch_device_lost(&device.device);
 
         Self {
-            window,
+            target,
             device,
             buffer_stores: GenerationalArray::new(),
             layout_storage: LayoutStorage::new(),
             pipeline_storage: PipelineStorage::new(),
-            depthbuffer: depth_buffer,
-            framebuffer: None,
+            depthbuffer,
+            msaabuffer: None,
+            sample_count: 1,
+            framebuffer,
             frame: None,
+            stats: Cell::new(RenderStats::default()),
+            view_bounds: None,
+            clip_stack: Vec::new(),
+            device_lost,
+            events: Vec::new(),
             font_sys: FontSystem::new(),
             buffer_object,
         }
     }
 
     pub fn adapter(&self) -> &wgpu::Adapter {
-        self.window.adapter()
+        match &self.target {
+            GpuTarget::Window(window) => window.adapter(),
+            GpuTarget::Headless(headless) => headless.adapter(),
+        }
+    }
+
+    pub fn target(&self) -> &GpuTarget {
+        &self.target
+    }
+
+    pub fn is_headless(&self) -> bool {
+        matches!(self.target, GpuTarget::Headless(_))
     }
 
     pub fn resize(
         &mut self,
         size: PhysicalSize<u32>,
     ) -> Result<(), AscendingError> {
-        self.window.resize(&self.device, size)
+        let old_size = self.size();
+
+        match &mut self.target {
+            GpuTarget::Window(window) => window.resize(&self.device, size)?,
+            GpuTarget::Headless(headless) => {
+                headless.resize(&self.device, size)?;
+                self.framebuffer = Some(headless.create_view());
+            }
+        }
+
+        if old_size != self.size() {
+            self.update_depth_texture();
+        }
+
+        Ok(())
     }
 
     pub fn frame_buffer(&self) -> &Option<wgpu::TextureView> {
@@ -83,23 +167,222 @@ impl GpuRenderer {
         &self.depthbuffer
     }
 
+    /// The multisampled color buffer. None when MSAA is off.
+    pub fn msaa_buffer(&self) -> Option<&wgpu::TextureView> {
+        self.msaabuffer.as_ref()
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Sets the MSAA sample count of 1, 2, 4 or 8 used by all pipelines.
+    /// Rebuilds the pipelines, depth buffer and multisampled color buffer.
+    /// Errors if the device can not render with sample_count samples.
+    pub fn set_sample_count(
+        &mut self,
+        sample_count: u32,
+    ) -> Result<(), AscendingError> {
+        if self.sample_count == sample_count {
+            return Ok(());
+        }
+
+        let surface_format = self.surface_format();
+
+        validate_sample_count(
+            self.adapter(),
+            &self.device,
+            surface_format,
+            sample_count,
+        )?;
+
+        self.sample_count = sample_count;
+        self.update_depth_texture();
+        self.pipeline_storage.recreate_pipelines(
+            &mut self.device,
+            &mut self.layout_storage,
+            surface_format,
+            sample_count,
+        );
+
+        Ok(())
+    }
+
+    /// The present mode of the surface. None when the renderer is Headless.
+    pub fn present_mode(&self) -> Option<wgpu::PresentMode> {
+        match &self.target {
+            GpuTarget::Window(window) => Some(window.present_mode()),
+            GpuTarget::Headless(_) => None,
+        }
+    }
+
+    /// The present modes the surface supports. Empty when Headless.
+    pub fn present_modes(&self) -> Vec<wgpu::PresentMode> {
+        match &self.target {
+            GpuTarget::Window(window) => window.capabilities().present_modes,
+            GpuTarget::Headless(_) => Vec::new(),
+        }
+    }
+
+    /// Switches between VSync, Mailbox and Immediate presenting. Drops the
+    /// frame if one was taken. Does nothing when Headless.
+    pub fn set_present_mode(
+        &mut self,
+        present_mode: wgpu::PresentMode,
+    ) -> Result<(), AscendingError> {
+        if let GpuTarget::Window(window) = &mut self.target {
+            if window.present_mode() != present_mode {
+                self.frame = None;
+                self.framebuffer = None;
+                window.set_present_mode(&self.device, present_mode)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The formats set_surface_format can switch to.
+    pub fn surface_formats(&self) -> Vec<wgpu::TextureFormat> {
+        match &self.target {
+            GpuTarget::Window(window) => window.capabilities().formats,
+            GpuTarget::Headless(headless) => headless.formats(&self.device),
+        }
+    }
+
+    /// Switches the surface, or offscreen texture, to format and rebuilds
+    /// the pipelines, depth buffer and multisampled color buffer for it.
+    /// Drops the frame if one was taken. RenderTargets and PostEffects made
+    /// with the old format need to be made again.
+    /// Errors if the surface or the current MSAA sample count can not use
+    /// format.
+    pub fn set_surface_format(
+        &mut self,
+        format: wgpu::TextureFormat,
+    ) -> Result<(), AscendingError> {
+        if self.surface_format() == format {
+            return Ok(());
+        }
+
+        validate_sample_count(
+            self.adapter(),
+            &self.device,
+            format,
+            self.sample_count,
+        )
+        .map_err(|_| AscendingError::UnsupportedSurfaceFormat(format))?;
+
+        match &mut self.target {
+            GpuTarget::Window(window) => {
+                self.frame = None;
+                self.framebuffer = None;
+                window.set_surface_format(&self.device, format)?;
+            }
+            GpuTarget::Headless(headless) => {
+                headless.set_format(&self.device, format)?;
+                self.framebuffer = Some(headless.create_view());
+            }
+        }
+
+        self.update_depth_texture();
+        self.pipeline_storage.recreate_pipelines(
+            &mut self.device,
+            &mut self.layout_storage,
+            format,
+            self.sample_count,
+        );
+
+        Ok(())
+    }
+
+    /// Switches to the surface format of the kind color, see
+    /// set_surface_format. Keeps the current format when the surface has
+    /// none of that kind. Returns the format in use.
+    pub fn set_surface_color(
+        &mut self,
+        color: SurfaceColor,
+    ) -> Result<wgpu::TextureFormat, AscendingError> {
+        if let Some(format) =
+            pick_surface_format(&self.surface_formats(), color)
+        {
+            self.set_surface_format(format)?;
+        }
+
+        Ok(self.surface_format())
+    }
+
+    /// Color attachment for rendering into view, which must be the size of
+    /// the frame. With MSAA on it renders into the msaa buffer and resolves
+    /// into view.
+    pub fn color_attachment<'a>(
+        &'a self,
+        view: &'a wgpu::TextureView,
+        ops: wgpu::Operations<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'a> {
+        match &self.msaabuffer {
+            Some(msaa) => wgpu::RenderPassColorAttachment {
+                view: msaa,
+                resolve_target: Some(view),
+                ops,
+            },
+            None => wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops,
+            },
+        }
+    }
+
     pub fn size(&self) -> PhysicalSize<f32> {
-        self.window.size
+        match &self.target {
+            GpuTarget::Window(window) => window.size,
+            GpuTarget::Headless(headless) => headless.size,
+        }
     }
 
-    pub fn surface(&self) -> &wgpu::Surface {
-        &self.window.surface
+    /// Returns None when the renderer is Headless.
+    pub fn surface(&self) -> Option<&wgpu::Surface> {
+        match &self.target {
+            GpuTarget::Window(window) => Some(&window.surface),
+            GpuTarget::Headless(_) => None,
+        }
     }
 
     pub fn surface_format(&self) -> wgpu::TextureFormat {
-        self.window.surface_format
+        match &self.target {
+            GpuTarget::Window(window) => window.surface_format,
+            GpuTarget::Headless(headless) => headless.format,
+        }
     }
 
     pub fn update(
         &mut self,
         event: &Event<()>,
     ) -> Result<bool, AscendingError> {
-        let frame = match self.window.update(&self.device, event)? {
+        // Skips the frame so game code can recreate its resources first.
+        if self.is_device_lost() {
+            self.recover_device()?;
+            return Ok(false);
+        }
+
+        let window = match &mut self.target {
+            GpuTarget::Window(window) => window,
+            // The offscreen texture is always ready to be rendered too.
+            GpuTarget::Headless(_) => return Ok(true),
+        };
+
+        let old_size = window.size;
+        let frame = window.update(&self.device, event)?;
+
+        if std::mem::take(&mut window.surface_lost) {
+            self.events.push(RendererEvent::SurfaceRecovered);
+        }
+
+        // Keep the depth and msaa buffers the same size as the surface.
+        if old_size != self.size() {
+            self.update_depth_texture();
+        }
+
+        let frame = match frame {
             Some(frame) => frame,
             _ => return Ok(false),
         };
@@ -114,19 +397,46 @@ impl GpuRenderer {
         Ok(true)
     }
 
-    pub fn window(&self) -> &Window {
-        &self.window.window
+    /// Returns None when the renderer is Headless.
+    pub fn window(&self) -> Option<&Window> {
+        match &self.target {
+            GpuTarget::Window(window) => Some(&window.window),
+            GpuTarget::Headless(_) => None,
+        }
     }
 
-    pub fn window_mut(&mut self) -> &mut Window {
-        &mut self.window.window
+    /// Returns None when the renderer is Headless.
+    pub fn window_mut(&mut self) -> Option<&mut Window> {
+        match &mut self.target {
+            GpuTarget::Window(window) => Some(&mut window.window),
+            GpuTarget::Headless(_) => None,
+        }
     }
 
+    /// Recreates the depth and msaa buffers at the current size.
     pub fn update_depth_texture(&mut self) {
-        self.depthbuffer = self.window.create_depth_texture(&self.device);
+        self.depthbuffer = match &self.target {
+            GpuTarget::Window(window) => {
+                window.create_depth_texture(&self.device, self.sample_count)
+            }
+            GpuTarget::Headless(headless) => {
+                headless.create_depth_texture(&self.device, self.sample_count)
+            }
+        };
+        self.msaabuffer = create_msaa_texture(
+            &self.device,
+            self.size(),
+            self.surface_format(),
+            self.sample_count,
+        );
     }
 
     pub fn present(&mut self) -> Result<(), AscendingError> {
+        // Nothing to present to. The frame stays in the offscreen texture.
+        if self.is_headless() {
+            return Ok(());
+        }
+
         self.framebuffer = None;
 
         match self.frame.take() {
@@ -160,6 +470,52 @@ impl GpuRenderer {
         &mut self.font_sys
     }
 
+    /// The counters collected since the last reset_stats.
+    pub fn stats(&self) -> RenderStats {
+        self.stats.get()
+    }
+
+    /// Clears the counters and returns what they were. Call once per frame
+    /// to get each frame's stats.
+    pub fn reset_stats(&self) -> RenderStats {
+        self.stats.take()
+    }
+
+    pub(crate) fn record_stats(&self, f: impl FnOnce(&mut RenderStats)) {
+        let mut stats = self.stats.get();
+
+        f(&mut stats);
+        self.stats.set(stats);
+    }
+
+    pub fn view_bounds(&self) -> Option<ViewBounds> {
+        self.view_bounds
+    }
+
+    /// Sets what is on screen so objects outside of it are skipped by
+    /// add_buffer_store. None draws everything. Use System::view_bounds
+    /// and set it again whenever the camera moves.
+    pub fn set_view_bounds(&mut self, view_bounds: Option<ViewBounds>) {
+        self.view_bounds = view_bounds;
+    }
+
+    /// If the object is within the view bounds. Objects without bounds are
+    /// always visible. Counts the objects that are not as culled.
+    pub fn is_visible(&self, index: &OrderedIndex) -> bool {
+        let visible = match (&self.view_bounds, &index.bounds) {
+            (Some(view), Some(bounds)) => {
+                view.is_visible(bounds, index.use_camera)
+            }
+            _ => true,
+        };
+
+        if !visible {
+            self.record_stats(|stats| stats.culled += 1);
+        }
+
+        visible
+    }
+
     pub fn new_buffer(&mut self) -> Index {
         self.buffer_stores.insert(BufferStore::default())
     }
@@ -197,40 +553,115 @@ impl GpuRenderer {
     }
 
     pub fn create_pipelines(&mut self, surface_format: wgpu::TextureFormat) {
-        self.pipeline_storage.create_pipeline(
-            &mut self.device,
-            &mut self.layout_storage,
-            surface_format,
-            crate::ImageRenderPipeline,
-        );
+        let sample_count = self.sample_count;
+
+        for pass in AlphaPass::ALL {
+            self.pipeline_storage.create_pipeline(
+                &mut self.device,
+                &mut self.layout_storage,
+                surface_format,
+                sample_count,
+                crate::ImageRenderPipeline { pass },
+            );
+        }
 
-        self.pipeline_storage.create_pipeline(
-            &mut self.device,
-            &mut self.layout_storage,
-            surface_format,
-            crate::MapRenderPipeline,
-        );
+        for pass in AlphaPass::ALL {
+            self.pipeline_storage.create_pipeline(
+                &mut self.device,
+                &mut self.layout_storage,
+                surface_format,
+                sample_count,
+                crate::MapRenderPipeline { pass },
+            );
+        }
 
-        self.pipeline_storage.create_pipeline(
-            &mut self.device,
-            &mut self.layout_storage,
-            surface_format,
-            crate::TextRenderPipeline,
-        );
+        for pass in AlphaPass::ALL {
+            self.pipeline_storage.create_pipeline(
+                &mut self.device,
+                &mut self.layout_storage,
+                surface_format,
+                sample_count,
+                crate::TextRenderPipeline { pass },
+            );
+        }
+
+        for pass in AlphaPass::ALL {
+            self.pipeline_storage.create_pipeline(
+                &mut self.device,
+                &mut self.layout_storage,
+                surface_format,
+                sample_count,
+                crate::Mesh2DRenderPipeline { pass },
+            );
+        }
 
         self.pipeline_storage.create_pipeline(
             &mut self.device,
             &mut self.layout_storage,
             surface_format,
-            crate::Mesh2DRenderPipeline,
+            sample_count,
+            crate::LightRenderPipeline,
         );
+    }
 
-        self.pipeline_storage.create_pipeline(
+    /// Creates the pipeline for the surface format and sample count if it
+    /// does not exist yet.
+    pub fn create_pipeline<K: PipeLineLayout>(&mut self, pipeline: K) {
+        if self.pipeline_storage.get_pipeline(pipeline).is_none() {
+            let surface_format = self.surface_format();
+
+            self.pipeline_storage.create_pipeline(
+                &mut self.device,
+                &mut self.layout_storage,
+                surface_format,
+                self.sample_count,
+                pipeline,
+            );
+        }
+    }
+
+    /// Swaps the WGSL of the shader file name, like "lightshader.wgsl",
+    /// and rebuilds the pipelines using it. On error the old pipelines
+    /// keep being used. Returns how many pipelines were rebuilt.
+    pub fn reload_shader(
+        &mut self,
+        name: &str,
+        source: String,
+    ) -> Result<usize, AscendingError> {
+        let surface_format = self.surface_format();
+
+        self.pipeline_storage.reload_shader(
             &mut self.device,
             &mut self.layout_storage,
             surface_format,
-            crate::LightRenderPipeline,
-        );
+            self.sample_count,
+            name,
+            source,
+        )
+    }
+
+    /// Reloads every shader file the watcher saw change. Errors are kept
+    /// per file so one bad shader does not stop the others reloading.
+    pub fn reload_changed_shaders(
+        &mut self,
+        watcher: &mut ShaderWatcher,
+    ) -> Result<Vec<ShaderReload>, AscendingError> {
+        let mut reloads = Vec::new();
+
+        for path in watcher.changed()? {
+            let name = match path.file_name().and_then(|name| name.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            let result = std::fs::read_to_string(&path)
+                .map_err(AscendingError::from)
+                .and_then(|source| self.reload_shader(&name, source));
+
+            reloads.push(ShaderReload { name, result });
+        }
+
+        Ok(reloads)
     }
 
     pub fn get_pipelines<K: PipeLineLayout>(
@@ -0,0 +1,166 @@
+use crate::{AscendingError, DirectoryWatcher, FxHashMap, ShaderError};
+use std::path::{Path, PathBuf};
+
+/// A WGSL file a pipeline is built from. name is the file name used to
+/// match files reloaded from disk and source is the built in WGSL.
+#[derive(Clone, Copy, Debug)]
+pub struct ShaderFile<'a> {
+    pub name: &'a str,
+    pub source: &'a str,
+}
+
+impl<'a> ShaderFile<'a> {
+    pub const fn new(name: &'a str, source: &'a str) -> Self {
+        Self { name, source }
+    }
+}
+
+/// Holds the WGSL loaded at runtime in place of the built in shaders and
+/// tracks which files the pipeline currently being built used.
+#[derive(Default)]
+pub struct ShaderStorage {
+    pub(crate) overrides: FxHashMap<String, String>,
+    /// (name, built in source) of each file used since the last take_used.
+    pub(crate) used: Vec<(String, String)>,
+    /// (name, source) of shader files added at runtime like Materials.
+    pub(crate) files: Vec<(String, String)>,
+}
+
+impl ShaderStorage {
+    /// Adds a shader file created at runtime and returns its id.
+    pub(crate) fn add_file(&mut self, name: &str, source: &str) -> u32 {
+        self.files.push((name.to_string(), source.to_string()));
+        (self.files.len() - 1) as u32
+    }
+
+    /// Joins the files into one shader using any reloaded sources.
+    pub(crate) fn compose(&mut self, files: &[ShaderFile]) -> String {
+        let mut source = String::new();
+
+        for file in files {
+            self.used
+                .push((file.name.to_string(), file.source.to_string()));
+            source.push_str(self.source(file.name, file.source));
+        }
+
+        source
+    }
+
+    pub(crate) fn source<'a>(&'a self, name: &str, source: &'a str) -> &'a str {
+        self.overrides
+            .get(name)
+            .map(|source| source.as_str())
+            .unwrap_or(source)
+    }
+
+    pub(crate) fn take_used(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.used)
+    }
+}
+
+/// Parses and validates the files joined together as one WGSL shader.
+/// Errors point to the file, line and column the problem is in.
+pub fn validate_wgsl(files: &[ShaderFile]) -> Result<(), AscendingError> {
+    let source: String = files.iter().map(|file| file.source).collect();
+
+    let module = naga::front::wgsl::parse_str(&source).map_err(|e| {
+        shader_error(files, &source, e.location(&source), e.message())
+    })?;
+
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map_err(|e| {
+        let mut message = e.as_inner().to_string();
+
+        for (_, label) in e.spans() {
+            if !label.is_empty() {
+                message.push_str(&format!("\n  {label}"));
+            }
+        }
+
+        shader_error(files, &source, e.location(&source), &message)
+    })?;
+
+    Ok(())
+}
+
+/// Finds which of the joined files the error's location falls in.
+fn shader_error(
+    files: &[ShaderFile],
+    source: &str,
+    location: Option<naga::SourceLocation>,
+    message: &str,
+) -> AscendingError {
+    let offset = location.map(|location| location.offset as usize);
+    let mut start = 0;
+
+    for file in files {
+        let end = start + file.source.len();
+
+        if let Some(offset) = offset.filter(|offset| *offset < end) {
+            let before = &source[start..offset];
+            let line = before.matches('\n').count() as u32 + 1;
+            let column = match before.rfind('\n') {
+                Some(pos) => (offset - start - pos) as u32,
+                None => (offset - start) as u32 + 1,
+            };
+
+            return AscendingError::Shader(ShaderError {
+                file: file.name.to_string(),
+                line,
+                column,
+                message: message.to_string(),
+            });
+        }
+
+        start = end;
+    }
+
+    AscendingError::Shader(ShaderError {
+        file: files
+            .last()
+            .map(|file| file.name.to_string())
+            .unwrap_or_default(),
+        line: 0,
+        column: 0,
+        message: message.to_string(),
+    })
+}
+
+/// The result of reloading a changed shader file.
+#[derive(Debug)]
+pub struct ShaderReload {
+    pub name: String,
+    /// How many pipelines were rebuilt or why the file was not used.
+    pub result: Result<usize, AscendingError>,
+}
+
+/// Polls a directory of WGSL files for changes. Used in development to
+/// reload shaders without restarting through
+/// GpuRenderer::reload_changed_shaders.
+pub struct ShaderWatcher {
+    watcher: DirectoryWatcher,
+}
+
+impl ShaderWatcher {
+    /// Starts watching every .wgsl file in directory as it is right now.
+    pub fn new(directory: impl AsRef<Path>) -> Result<Self, AscendingError> {
+        Ok(Self {
+            watcher: DirectoryWatcher::new(directory, |path| {
+                path.extension().and_then(|ext| ext.to_str()) == Some("wgsl")
+            })?,
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        self.watcher.directory()
+    }
+
+    /// Returns the files added or modified since the last call.
+    pub fn changed(&mut self) -> Result<Vec<PathBuf>, AscendingError> {
+        self.watcher.changed()
+    }
+}
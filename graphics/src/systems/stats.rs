@@ -0,0 +1,40 @@
+use std::fmt;
+
+/// Counters of the work done to render the frame. Collected on the
+/// GpuRenderer until GpuRenderer::reset_stats is called.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Instances placed into InstanceBuffers on finalize.
+    pub instances: u32,
+    /// Vertices and indices placed into GpuBuffers on finalize.
+    pub vertices: u32,
+    pub indices: u32,
+    pub draw_calls: u32,
+    /// Objects skipped for being outside of the view bounds.
+    pub culled: u32,
+    /// Bytes written to the GPU on finalize. Objects that did not change
+    /// or move are not written again.
+    pub bytes_uploaded: u64,
+    /// How many writes into GPU buffers were made.
+    pub buffer_writes: u32,
+    /// How many times a buffer was too small and was reallocated.
+    pub buffer_resizes: u32,
+}
+
+impl fmt::Display for RenderStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "instances: {}, vertices: {}, indices: {}, draw calls: {}, \
+             culled: {}, uploaded: {} bytes in {} writes, resizes: {}",
+            self.instances,
+            self.vertices,
+            self.indices,
+            self.draw_calls,
+            self.culled,
+            self.bytes_uploaded,
+            self.buffer_writes,
+            self.buffer_resizes
+        )
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{GpuDevice, GpuRenderer, Layout, WorldBounds};
+use crate::{GpuDevice, GpuRenderer, Layout, ViewBounds, WorldBounds};
 use bytemuck::{Pod, Zeroable};
 use camera::Projection;
 use crevice::std140::AsStd140;
@@ -104,6 +104,31 @@ where
             1.0,
         );
 
+        let (global_buffer, bind_group) =
+            Self::create_bindings(renderer, &camera, screen_size);
+
+        Self {
+            camera,
+            screen_size,
+            global_buffer,
+            bind_group,
+            #[cfg(feature = "iced")]
+            iced_view,
+        }
+    }
+
+    /// Creates the uniform buffer and bind group on a new device after the
+    /// old one was lost. The camera and screen size are kept.
+    pub fn recreate(&mut self, renderer: &mut GpuRenderer) {
+        (self.global_buffer, self.bind_group) =
+            Self::create_bindings(renderer, &self.camera, self.screen_size);
+    }
+
+    fn create_bindings(
+        renderer: &mut GpuRenderer,
+        camera: &camera::Camera<Controls>,
+        screen_size: [f32; 2],
+    ) -> (wgpu::Buffer, wgpu::BindGroup) {
         // Create the camera uniform.
         let proj = camera.projection();
         let view = camera.view();
@@ -158,14 +183,7 @@ where
                     label: Some("system_bind_group"),
                 });
 
-        Self {
-            camera,
-            screen_size,
-            global_buffer,
-            bind_group,
-            #[cfg(feature = "iced")]
-            iced_view,
-        }
+        (global_buffer, bind_group)
     }
 
     pub fn projection(&self) -> mint::ColumnMatrix4<f32> {
@@ -258,6 +276,47 @@ where
         self.camera.view()
     }
 
+    /// The area of the world seen through the camera's projection and view.
+    pub fn world_bounds(&self) -> WorldBounds {
+        Self::visible_bounds(
+            Mat4::from(self.camera.projection())
+                * Mat4::from(self.camera.view()),
+        )
+    }
+
+    /// The area seen through the projection alone, used by objects that do
+    /// not use the camera.
+    pub fn screen_bounds(&self) -> WorldBounds {
+        Self::visible_bounds(Mat4::from(self.camera.projection()))
+    }
+
+    /// Pass this to GpuRenderer::set_view_bounds after update to skip
+    /// objects that are not on screen.
+    pub fn view_bounds(&self) -> ViewBounds {
+        ViewBounds::new(self.world_bounds(), self.screen_bounds())
+    }
+
+    /// Unprojects the corners of clip space to get the area they cover.
+    /// With a perspective projection this covers everything up to far.
+    fn visible_bounds(matrix: Mat4) -> WorldBounds {
+        let inverse = matrix.inverse();
+        let (mut min, mut max) = (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN));
+
+        for corner in 0..8 {
+            let clip = Vec3::new(
+                if corner & 1 == 0 { -1.0 } else { 1.0 },
+                if corner & 2 == 0 { -1.0 } else { 1.0 },
+                if corner & 4 == 0 { 0.0 } else { 1.0 },
+            );
+            let world = inverse.project_point3(clip).truncate();
+
+            min = min.min(world);
+            max = max.max(world);
+        }
+
+        WorldBounds::new(min.x, min.y, max.x, max.y, max.y - min.y)
+    }
+
     pub fn projected_world_to_screen(
         &self,
         scale: bool,
@@ -1,6 +1,6 @@
 use crate::{
-    AsBufferPass, Buffer, BufferData, BufferLayout, BufferPass, GpuDevice,
-    GpuRenderer, OrderedIndex,
+    AsBufferPass, Bounds, Buffer, BufferData, BufferLayout, BufferPass,
+    GpuDevice, GpuRenderer, OrderedIndex, SortAxis,
 };
 use std::ops::Range;
 
@@ -10,6 +10,9 @@ use std::ops::Range;
 pub struct IndexDetails {
     pub count: u32,
     pub max: u32,
+    pub clip: Option<Bounds>,
+    /// Drawn after the opaque objects without writing depth.
+    pub translucent: bool,
 }
 
 pub struct GpuBuffer<K: BufferLayout> {
@@ -19,6 +22,10 @@ pub struct GpuBuffer<K: BufferLayout> {
     vertex_needed: usize,
     pub index_buffer: Buffer<K>,
     index_needed: usize,
+    /// Which position axis is sorted on first.
+    pub sort_axis: SortAxis,
+    /// Writes every object on the next finalize.
+    rewrite: bool,
 }
 
 impl<'a, K: BufferLayout> AsBufferPass<'a> for GpuBuffer<K> {
@@ -50,14 +57,24 @@ impl<K: BufferLayout> GpuBuffer<K> {
                 Some("Index Buffer"),
             ),
             index_needed: 0,
+            sort_axis: SortAxis::default(),
+            rewrite: false,
         }
     }
 
+    /// Adds the object to be drawn unless it is outside the view bounds.
+    /// It is clipped to the GpuRenderer's current clip.
     pub fn add_buffer_store(
         &mut self,
         renderer: &GpuRenderer,
         mut index: OrderedIndex,
     ) {
+        if !renderer.is_visible(&index) {
+            return;
+        }
+
+        renderer.clip_index(&mut index);
+
         if let Some(store) = renderer.get_buffer(&index.index) {
             self.vertex_needed += store.store.len();
             self.index_needed += store.indexs.len();
@@ -69,7 +86,8 @@ impl<K: BufferLayout> GpuBuffer<K> {
     }
 
     pub fn finalize(&mut self, renderer: &mut GpuRenderer) {
-        let (mut changed, mut vertex_pos, mut index_pos) = (false, 0, 0);
+        let (mut changed, mut vertex_pos, mut index_pos) =
+            (std::mem::take(&mut self.rewrite), 0, 0);
 
         if self.vertex_needed > self.vertex_buffer.max
             || self.index_needed > self.index_buffer.max
@@ -79,13 +97,20 @@ impl<K: BufferLayout> GpuBuffer<K> {
                 self.vertex_needed / K::stride(),
                 self.index_needed,
             );
+            renderer.record_stats(|stats| stats.buffer_resizes += 1);
             changed = true;
         }
 
         self.vertex_buffer.count = self.vertex_needed / K::stride();
         self.vertex_buffer.len = self.vertex_needed;
+        renderer.record_stats(|stats| {
+            stats.vertices += self.vertex_buffer.count as u32;
+            stats.indices += (self.index_needed / 4) as u32;
+        });
 
-        self.unprocessed.sort();
+        let axis = self.sort_axis;
+
+        self.unprocessed.sort_by(|a, b| a.cmp_axis(b, axis));
         self.buffers.clear();
 
         for buf in &self.unprocessed {
@@ -123,6 +148,10 @@ impl<K: BufferLayout> GpuBuffer<K> {
                         &store.store,
                         old_vertex_pos,
                     );
+                    renderer.record_stats(|stats| {
+                        stats.bytes_uploaded += store.store.len() as u64;
+                        stats.buffer_writes += 1;
+                    });
                 }
             }
 
@@ -133,12 +162,18 @@ impl<K: BufferLayout> GpuBuffer<K> {
                         &store.indexs,
                         old_index_pos,
                     );
+                    renderer.record_stats(|stats| {
+                        stats.bytes_uploaded += store.indexs.len() as u64;
+                        stats.buffer_writes += 1;
+                    });
                 }
             }
 
             self.buffers.push(IndexDetails {
                 count: buf.index_count,
                 max: buf.index_max,
+                clip: buf.clip,
+                translucent: buf.order.alpha,
             });
         }
 
@@ -147,6 +182,20 @@ impl<K: BufferLayout> GpuBuffer<K> {
         self.index_needed = 0;
     }
 
+    /// Creates the buffers on a new device after the old one was lost.
+    /// Every object is written again on the next finalize.
+    pub fn recreate(&mut self, gpu_device: &GpuDevice) {
+        let GpuBuffer {
+            vertex_buffer,
+            index_buffer,
+            ..
+        } = Self::new(gpu_device);
+
+        self.vertex_buffer = vertex_buffer;
+        self.index_buffer = index_buffer;
+        self.rewrite = true;
+    }
+
     //private but resizes the buffer on the GPU when needed.
     fn resize(
         &mut self,
@@ -0,0 +1,56 @@
+use crate::{AscendingError, FxHashMap};
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Polls the files of a directory that pass a filter for changes by their
+/// modified time. Shared by ShaderWatcher and TextureWatcher.
+pub struct DirectoryWatcher {
+    directory: PathBuf,
+    filter: fn(&Path) -> bool,
+    modified: FxHashMap<PathBuf, SystemTime>,
+}
+
+impl DirectoryWatcher {
+    /// Starts watching every file in directory filter returns true for as
+    /// it is right now.
+    pub fn new(
+        directory: impl AsRef<Path>,
+        filter: fn(&Path) -> bool,
+    ) -> Result<Self, AscendingError> {
+        let mut watcher = Self {
+            directory: directory.as_ref().to_path_buf(),
+            filter,
+            modified: FxHashMap::default(),
+        };
+
+        watcher.changed()?;
+        Ok(watcher)
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Returns the files added or modified since the last call.
+    pub fn changed(&mut self) -> Result<Vec<PathBuf>, AscendingError> {
+        let mut changed = Vec::new();
+
+        for entry in std::fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+
+            if !(self.filter)(&path) {
+                continue;
+            }
+
+            let modified = std::fs::metadata(&path)?.modified()?;
+
+            if self.modified.insert(path.clone(), modified) != Some(modified) {
+                changed.push(path);
+            }
+        }
+
+        Ok(changed)
+    }
+}
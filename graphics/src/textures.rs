@@ -1,7 +1,11 @@
+mod rendertarget;
 mod texture;
 mod texturegroup;
 mod texturelayout;
+mod watcher;
 
+pub use rendertarget::RenderTarget;
 pub use texture::Texture;
 pub use texturegroup::TextureGroup;
 pub use texturelayout::TextureLayout;
+pub use watcher::TextureWatcher;
@@ -0,0 +1,183 @@
+use crate::{
+    create_depth_texture, create_msaa_texture, Allocation, GpuRenderer,
+    TextureGroup, TextureLayout,
+};
+use winit::dpi::PhysicalSize;
+
+/// A offscreen Texture that can be rendered into like the frame buffer
+/// and then drawn through an ImageRenderer like any other Image.
+/// Used to compose scenes like minimaps, portraits and UI panels.
+pub struct RenderTarget {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    msaa_view: Option<wgpu::TextureView>,
+    depth_view: wgpu::TextureView,
+    size: PhysicalSize<u32>,
+    sample_count: u32,
+    /// Texture Bind group for sampling the target. Uses TextureLayout.
+    pub texture_group: TextureGroup,
+}
+
+impl RenderTarget {
+    /// Creates a target in the renderer's surface format and sample count
+    /// so the existing pipelines can render into it.
+    pub fn new(renderer: &mut GpuRenderer, size: PhysicalSize<u32>) -> Self {
+        let format = renderer.surface_format();
+        let sample_count = renderer.sample_count();
+        let target_size = PhysicalSize::new(
+            size.width.max(1) as f32,
+            size.height.max(1) as f32,
+        );
+        let texture = Self::create_texture(renderer, size, format);
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("render target view"),
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            array_layer_count: Some(1),
+            ..Default::default()
+        });
+        let msaa_view = create_msaa_texture(
+            renderer.gpu_device(),
+            target_size,
+            format,
+            sample_count,
+        );
+        let depth_view = create_depth_texture(
+            renderer.gpu_device(),
+            target_size,
+            sample_count,
+        );
+        let texture_group = Self::create_group(renderer, &texture);
+
+        Self {
+            texture,
+            view,
+            msaa_view,
+            depth_view,
+            size,
+            sample_count,
+            texture_group,
+        }
+    }
+
+    /// Recreates the textures at the new size or when the renderer's
+    /// sample count changed. Images using this target need a new
+    /// allocation() if their uv depended on the old size.
+    pub fn resize(
+        &mut self,
+        renderer: &mut GpuRenderer,
+        size: PhysicalSize<u32>,
+    ) {
+        if self.size == size && self.sample_count == renderer.sample_count() {
+            return;
+        }
+
+        *self = Self::new(renderer, size);
+    }
+
+    /// Creates the textures on a new device after the old one was lost.
+    /// What was rendered into the target needs rendering again.
+    pub fn recreate(&mut self, renderer: &mut GpuRenderer) {
+        *self = Self::new(renderer, self.size);
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    /// The resolved color view. Use color_attachment() to render into it.
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    /// Color attachment for rendering into the target. With MSAA on it
+    /// renders into the target's msaa texture and resolves into view.
+    pub fn color_attachment(
+        &self,
+        ops: wgpu::Operations<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'_> {
+        match &self.msaa_view {
+            Some(msaa) => wgpu::RenderPassColorAttachment {
+                view: msaa,
+                resolve_target: Some(&self.view),
+                ops,
+            },
+            None => wgpu::RenderPassColorAttachment {
+                view: &self.view,
+                resolve_target: None,
+                ops,
+            },
+        }
+    }
+
+    /// The depth attachment view to render into.
+    pub fn depth_view(&self) -> &wgpu::TextureView {
+        &self.depth_view
+    }
+
+    pub fn size(&self) -> PhysicalSize<u32> {
+        self.size
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.texture.format()
+    }
+
+    /// Allocation covering the whole target. Give this to Image::new to
+    /// draw the target.
+    pub fn allocation(&self) -> Allocation {
+        Allocation {
+            allocation: guillotiere::Allocation {
+                id: guillotiere::AllocId::deserialize(0),
+                rectangle: guillotiere::Rectangle::new(
+                    guillotiere::point2(0, 0),
+                    guillotiere::point2(
+                        self.size.width as i32,
+                        self.size.height as i32,
+                    ),
+                ),
+            },
+            layer: 0,
+            data: 0,
+        }
+    }
+
+    fn create_texture(
+        renderer: &GpuRenderer,
+        size: PhysicalSize<u32>,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::Texture {
+        renderer.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some("render target texture"),
+            size: wgpu::Extent3d {
+                width: size.width.max(1),
+                height: size.height.max(1),
+                // Like the Atlas we need 2 layers so backends like GL
+                // create an array texture that TextureLayout can sample.
+                depth_or_array_layers: 2,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[format],
+        })
+    }
+
+    fn create_group(
+        renderer: &mut GpuRenderer,
+        texture: &wgpu::Texture,
+    ) -> TextureGroup {
+        // TextureLayout samples a D2Array so we view the target as one.
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("render target array view"),
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            array_layer_count: Some(1),
+            ..Default::default()
+        });
+
+        TextureGroup::from_view(renderer, &view, TextureLayout)
+    }
+}
@@ -13,7 +13,8 @@ impl TextureGroup {
         let diffuse_sampler =
             renderer.device().create_sampler(&wgpu::SamplerDescriptor {
                 label: Some("Texture_sampler"),
-                lod_max_clamp: 0.0,
+                // Blends between the mip levels of mipmapped atlases.
+                mipmap_filter: wgpu::FilterMode::Linear,
                 ..Default::default()
             });
 
@@ -0,0 +1,29 @@
+use crate::{AscendingError, DirectoryWatcher};
+use image::ImageFormat;
+use std::path::{Path, PathBuf};
+
+/// Polls a directory of images for changes. Used in development to reload
+/// textures without restarting through AssetManager::reload_changed.
+pub struct TextureWatcher {
+    watcher: DirectoryWatcher,
+}
+
+impl TextureWatcher {
+    /// Starts watching every image file in directory as it is right now.
+    pub fn new(directory: impl AsRef<Path>) -> Result<Self, AscendingError> {
+        Ok(Self {
+            watcher: DirectoryWatcher::new(directory, |path| {
+                ImageFormat::from_path(path).is_ok()
+            })?,
+        })
+    }
+
+    pub fn directory(&self) -> &Path {
+        self.watcher.directory()
+    }
+
+    /// Returns the images added or modified since the last call.
+    pub fn changed(&mut self) -> Result<Vec<PathBuf>, AscendingError> {
+        self.watcher.changed()
+    }
+}
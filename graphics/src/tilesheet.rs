@@ -28,7 +28,10 @@ impl TileSheet {
         let tilecount =
             (texture.size().0 / tilesize) * (texture.size().1 / tilesize);
         let sheet_width = texture.size().0 / tilesize;
-        let atlas_width = atlas.atlas.extent.width / tilesize;
+        // Tiles of mipmapped atlases are padded on every side.
+        let padding = atlas.atlas.padding;
+        let cell = tilesize + padding * 2;
+        let atlas_width = atlas.atlas.extent.width / cell;
         let sheet_image: RgbaImage = ImageBuffer::from_raw(
             texture.size().0,
             texture.size().1,
@@ -43,64 +46,56 @@ impl TileSheet {
             empty
         } else {
             let image: RgbaImage = ImageBuffer::new(tilesize, tilesize);
-            atlas.upload(
-                "Empty".to_owned(),
-                image.as_bytes(),
-                tilesize,
-                tilesize,
-                0,
-                renderer,
-            )?
+            upload_tile(atlas, renderer, "Empty".to_owned(), &image, tilesize)?
         };
 
-        for id in 0..tilecount {
-            let mut image: RgbaImage = ImageBuffer::new(tilesize, tilesize);
-            // get its location to remap it back visually.
-            let (tilex, tiley) = (
-                ((id % sheet_width) * tilesize),
-                ((id / sheet_width) * tilesize),
-            );
-
-            // lets create the tile from the texture.
-            for y in 0..tilesize {
-                for x in 0..tilesize {
-                    let posx = (id % sheet_width) * tilesize + x;
-                    let posy = (id / sheet_width) * tilesize + y;
-                    let pixel = sheet_image.get_pixel(posx, posy);
-                    image.put_pixel(x, y, *pixel);
+        atlas.batch_uploads(renderer, |atlas| {
+            for id in 0..tilecount {
+                let mut image: RgbaImage = ImageBuffer::new(tilesize, tilesize);
+                // get its location to remap it back visually.
+                let (tilex, tiley) = (
+                    ((id % sheet_width) * tilesize),
+                    ((id / sheet_width) * tilesize),
+                );
+
+                // lets create the tile from the texture.
+                for y in 0..tilesize {
+                    for x in 0..tilesize {
+                        let posx = (id % sheet_width) * tilesize + x;
+                        let posy = (id / sheet_width) * tilesize + y;
+                        let pixel = sheet_image.get_pixel(posx, posy);
+                        image.put_pixel(x, y, *pixel);
+                    }
+                }
+                // we upload the tile regardless this avoid tilesheet issues later.
+                let name: String = format!("{}-{}", texture.name(), id);
+                let allocation =
+                    upload_tile(atlas, renderer, name, &image, tilesize)?;
+
+                if image.enumerate_pixels().all(|p| p.2 .0[3] == 0) {
+                    // lets use our only Blank tile. this will always be the first loaded.
+                    // We use this when tiles are empty to avoid issues later when we do use
+                    // these spots for other tiles.
+                    tiles.push(Tile {
+                        x: tilex,
+                        y: tiley,
+                        id: 0,
+                        allocation: empty.clone(),
+                    })
+                } else {
+                    let (posx, posy) = allocation.position();
+                    tiles.push(Tile {
+                        x: tilex,
+                        y: tiley,
+                        id: ((posx - padding) / cell)
+                            + (((posy - padding) / cell) * atlas_width),
+                        allocation,
+                    })
                 }
             }
-            // we upload the tile regardless this avoid tilesheet issues later.
-            let name: String = format!("{}-{}", texture.name(), id);
-            let allocation = atlas.upload(
-                name,
-                image.as_bytes(),
-                tilesize,
-                tilesize,
-                0,
-                renderer,
-            )?;
-
-            if image.enumerate_pixels().all(|p| p.2 .0[3] == 0) {
-                // lets use our only Blank tile. this will always be the first loaded.
-                // We use this when tiles are empty to avoid issues later when we do use
-                // these spots for other tiles.
-                tiles.push(Tile {
-                    x: tilex,
-                    y: tiley,
-                    id: 0,
-                    allocation: empty.clone(),
-                })
-            } else {
-                let (posx, posy) = allocation.position();
-                tiles.push(Tile {
-                    x: tilex,
-                    y: tiley,
-                    id: (posx / tilesize) + ((posy / tilesize) * atlas_width),
-                    allocation,
-                })
-            }
-        }
+
+            Some(())
+        })?;
 
         // We return as Some(tilesheet) this allows us to check above upon
         // upload if a tile failed to get added or not due to no more room.
@@ -115,6 +110,53 @@ impl TileSheet {
         renderer: &GpuRenderer,
         atlas: &mut AtlasGroup,
         tilesize: u32,
+    ) -> Option<()> {
+        // lets check this to add in the empty tile set first if nothing else yet exists.
+        // Also lets add the black tile.
+        if atlas.atlas.cache.is_empty() {
+            let image: RgbaImage = ImageBuffer::new(tilesize, tilesize);
+            upload_tile(atlas, renderer, "Empty".to_owned(), &image, tilesize)?;
+        }
+
+        Self::upload_tiles(&texture, renderer, atlas, tilesize, false)
+    }
+
+    /// Pins the tiles sliced from texture and the Empty tile so uploads
+    /// can not evict them. Maps find tiles by their position, so an
+    /// evicted tile shows whatever is uploaded in its place. Each call
+    /// takes its own pins, kept apart from other pins of the same keys
+    /// until unpin.
+    pub fn pin(texture: &Texture, atlas: &mut AtlasGroup, tilesize: u32) {
+        for name in tile_names(texture, tilesize) {
+            atlas.pin(&name);
+        }
+    }
+
+    /// Releases the pins taken by pin.
+    pub fn unpin(texture: &Texture, atlas: &mut AtlasGroup, tilesize: u32) {
+        for name in tile_names(texture, tilesize) {
+            atlas.unpin(&name);
+        }
+    }
+
+    /// Slices the changed texture of a tilesheet made with upload again
+    /// and writes each tile over the old one. Tiles the sheet grew by are
+    /// uploaded as new.
+    pub fn reload(
+        texture: &Texture,
+        renderer: &GpuRenderer,
+        atlas: &mut AtlasGroup,
+        tilesize: u32,
+    ) -> Option<()> {
+        Self::upload_tiles(texture, renderer, atlas, tilesize, true)
+    }
+
+    fn upload_tiles(
+        texture: &Texture,
+        renderer: &GpuRenderer,
+        atlas: &mut AtlasGroup,
+        tilesize: u32,
+        rewrite: bool,
     ) -> Option<()> {
         let tilecount =
             (texture.size().0 / tilesize) * (texture.size().1 / tilesize);
@@ -126,46 +168,58 @@ impl TileSheet {
         )
         .unwrap_or(ImageBuffer::new(texture.size().0, texture.size().1));
 
-        // lets check this to add in the empty tile set first if nothing else yet exists.
-        // Also lets add the black tile.
-        if atlas.atlas.cache.is_empty() {
-            let image: RgbaImage = ImageBuffer::new(tilesize, tilesize);
-            atlas.upload(
-                "Empty".to_owned(),
-                image.as_bytes(),
-                tilesize,
-                tilesize,
-                0,
-                renderer,
-            )?;
-        }
+        atlas.batch_uploads(renderer, |atlas| {
+            for id in 0..tilecount {
+                let mut image: RgbaImage = ImageBuffer::new(tilesize, tilesize);
+
+                // lets create the tile from the texture.
+                for y in 0..tilesize {
+                    for x in 0..tilesize {
+                        let posx = (id % sheet_width) * tilesize + x;
+                        let posy = (id / sheet_width) * tilesize + y;
+                        let pixel = sheet_image.get_pixel(posx, posy);
+                        image.put_pixel(x, y, *pixel);
+                    }
+                }
 
-        for id in 0..tilecount {
-            let mut image: RgbaImage = ImageBuffer::new(tilesize, tilesize);
+                let name: String = format!("{}-{}", texture.name(), id);
 
-            // lets create the tile from the texture.
-            for y in 0..tilesize {
-                for x in 0..tilesize {
-                    let posx = (id % sheet_width) * tilesize + x;
-                    let posy = (id / sheet_width) * tilesize + y;
-                    let pixel = sheet_image.get_pixel(posx, posy);
-                    image.put_pixel(x, y, *pixel);
+                if rewrite
+                    && atlas
+                        .reupload(&name, image.as_bytes(), renderer)
+                        .is_some()
+                {
+                    continue;
                 }
+
+                upload_tile(atlas, renderer, name, &image, tilesize)?;
             }
 
-            let name: String = format!("{}-{}", texture.name(), id);
-            atlas.upload(
-                name,
-                image.as_bytes(),
-                tilesize,
-                tilesize,
-                0,
-                renderer,
-            )?;
-        }
+            Some(())
+        })?;
 
         // We return as Some(()) this allows us to check above upon
         // upload if a tile failed to get added or not due to no more room.
         Some(())
     }
 }
+
+fn upload_tile(
+    atlas: &mut AtlasGroup,
+    renderer: &GpuRenderer,
+    name: String,
+    image: &RgbaImage,
+    tilesize: u32,
+) -> Option<Allocation> {
+    atlas.upload(name, image.as_bytes(), tilesize, tilesize, 0, renderer)
+}
+
+/// The keys of the tiles sliced from texture, the Empty tile first.
+fn tile_names(texture: &Texture, tilesize: u32) -> Vec<String> {
+    let tilecount =
+        (texture.size().0 / tilesize) * (texture.size().1 / tilesize);
+
+    std::iter::once("Empty".to_owned())
+        .chain((0..tilecount).map(|id| format!("{}-{}", texture.name(), id)))
+        .collect()
+}
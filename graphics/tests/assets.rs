@@ -0,0 +1,287 @@
+//! Golden image tests for the AssetLoader, AssetManager and texture hot
+//! reloading.
+
+mod common;
+
+use ascending_graphics::*;
+use common::*;
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn asset_loader() {
+    let mut renderer = create_renderer();
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    // One byte means every upload call writes a single asset.
+    let mut loader = AssetLoader::new(runtime.handle().clone(), 1);
+    let mut atlases = [AtlasGroup::new(
+        &mut renderer,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+    )];
+
+    let mut sprite =
+        loader.load_texture(manifest_path("../images/Female_1.png"), 0);
+    let anim = loader.load_texture(manifest_path("../images/anim/0.png"), 0);
+    let missing = loader.load_texture(manifest_path("../images/none.png"), 0);
+    let wrong_group =
+        loader.load_texture(manifest_path("../images/Female_1.png"), 3);
+
+    assert_eq!(loader.pending(), 4);
+
+    let start = std::time::Instant::now();
+
+    while loader.pending() > 0 {
+        assert!(loader.upload(&mut renderer, &mut atlases) <= 1);
+        assert!(start.elapsed().as_secs() < 30, "assets never loaded");
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+
+    assert!(matches!(
+        runtime.block_on(sprite.ready()),
+        AssetState::Ready(_)
+    ));
+    assert!(matches!(missing.state(), AssetState::Failed(_)));
+    assert!(matches!(
+        wrong_group.state(),
+        AssetState::Failed(message)
+            if message == AscendingError::MissingAtlasGroup(3).to_string()
+    ));
+
+    // Fonts are parsed on the workers and write nothing to the GPU.
+    let font = loader
+        .load_font(manifest_path("../demo/src/fonts/Inconsolata-Regular.ttf"));
+    let not_font = loader.load_font(manifest_path("../images/Female_1.png"));
+    let faces = renderer.font_sys_mut().db().len();
+
+    while loader.pending() > 0 {
+        loader.upload(&mut renderer, &mut atlases);
+        assert!(start.elapsed().as_secs() < 30, "fonts never loaded");
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+
+    assert!(matches!(font.state(), AssetState::Ready(())));
+    assert!(matches!(not_font.state(), AssetState::Failed(_)));
+    assert_eq!(renderer.font_sys_mut().db().len(), faces + 1);
+
+    let mut image = Image::new(sprite.get(), &mut renderer, 1);
+    image.pos = Vec3::new(32.0, 64.0, 4.0);
+    image.hw = Vec2::new(96.0, 96.0);
+    image.uv = Vec4::new(48.0, 96.0, 48.0, 48.0);
+
+    let mut animation = Image::new(anim.get(), &mut renderer, 1);
+    animation.pos = Vec3::new(136.0, 64.0, 4.0);
+    animation.hw = Vec2::new(96.0, 96.0);
+    animation.uv = Vec4::new(0.0, 0.0, 64.0, 64.0);
+
+    let [atlas] = atlases;
+    let mut scene =
+        ImageScene::new(&mut renderer, atlas, &mut [image, animation]);
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("asset_loader", &frame, CHANNEL_TOLERANCE);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn asset_handles() {
+    let mut renderer = create_renderer();
+
+    let mut manager = AssetManager::new();
+    let mut atlases = [
+        AtlasGroup::new(&mut renderer, wgpu::TextureFormat::Rgba8UnormSrgb),
+        AtlasGroup::new(&mut renderer, wgpu::TextureFormat::Rgba8UnormSrgb),
+    ];
+
+    // Textures filling a whole layer so every upload past the first two
+    // has to evict one.
+    let size = atlases[1].atlas.extent.width;
+    let layer_texture = |name: &str| {
+        Texture::from_image(
+            name.to_owned(),
+            image::DynamicImage::new_rgba8(size, size),
+        )
+    };
+    // Keeps the atlas from adding layers.
+    atlases[1].atlas.max_layers = 3;
+
+    let pinned = manager
+        .upload(&layer_texture("pinned"), 1, &mut atlases, &renderer)
+        .unwrap();
+    let user = pinned.clone();
+
+    layer_texture("unpinned").group_upload(&mut atlases[1], &renderer);
+    atlases[1].trim();
+    layer_texture("newer").group_upload(&mut atlases[1], &renderer);
+
+    // The pinned texture is the least recently used but is skipped over.
+    assert!(atlases[1].contains(&"pinned".to_owned()));
+    assert!(!atlases[1].contains(&"unpinned".to_owned()));
+    assert_eq!(pinned.users(), 2);
+    assert_eq!(manager.update(&mut atlases), 0);
+    assert!(pinned.allocation().is_some());
+
+    drop(pinned);
+    drop(user);
+    assert_eq!(manager.update(&mut atlases), 1);
+    assert!(manager.get("pinned", 1).is_none());
+
+    atlases[1].trim();
+    layer_texture("newest").group_upload(&mut atlases[1], &renderer);
+    assert!(!atlases[1].contains(&"pinned".to_owned()));
+
+    // Every load of the same file shares one handle.
+    let sprite = manager
+        .load(
+            manifest_path("../images/Female_1.png"),
+            0,
+            &mut atlases,
+            &renderer,
+        )
+        .unwrap();
+    let again = manager
+        .load(
+            manifest_path("../images/Female_1.png"),
+            0,
+            &mut atlases,
+            &renderer,
+        )
+        .unwrap();
+
+    assert_eq!(sprite, again);
+    assert_eq!(manager.len(), 1);
+    assert!(matches!(
+        manager.load(
+            manifest_path("../images/Female_1.png"),
+            2,
+            &mut atlases,
+            &renderer,
+        ),
+        Err(AscendingError::MissingAtlasGroup(2))
+    ));
+
+    let mut image = Image::from_handle(sprite, &mut renderer, 1);
+
+    image.pos = Vec3::new(64.0, 64.0, 4.0);
+    image.hw = Vec2::new(128.0, 128.0);
+    image.uv = Vec4::new(0.0, 0.0, 48.0, 48.0);
+
+    manager.update(&mut atlases);
+
+    let [atlas, _] = atlases;
+    let mut scene = ImageScene::new(&mut renderer, atlas, &mut [image]);
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("asset_handles", &frame, CHANNEL_TOLERANCE);
+}
+
+/// Writes a solid image and moves its modified time forward so watchers
+/// see the change even on file systems with coarse timestamps.
+fn write_image(path: &Path, width: u32, height: u32, colors: &[Rgba<u8>]) {
+    let image = RgbaImage::from_fn(width, height, |x, _| {
+        colors[(x * colors.len() as u32 / width) as usize]
+    });
+
+    image.save(path).unwrap();
+
+    let modified = std::fs::metadata(path).unwrap().modified().unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(modified + std::time::Duration::from_secs(2))
+        .unwrap();
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn texture_hot_reload() {
+    let mut renderer = create_renderer();
+
+    let directory = std::env::temp_dir()
+        .join(format!("ascending_hot_reload_{}", std::process::id()));
+    let sprite_path = directory.join("sprite.png");
+    let tiles_path = directory.join("tiles.png");
+    let red = Rgba([255, 0, 0, 255]);
+    let green = Rgba([0, 255, 0, 255]);
+    let blue = Rgba([0, 0, 255, 255]);
+    let yellow = Rgba([255, 255, 0, 255]);
+
+    std::fs::create_dir_all(&directory).unwrap();
+    write_image(&sprite_path, 32, 32, &[red]);
+    write_image(&tiles_path, 40, 20, &[green, blue]);
+
+    let mut watcher = TextureWatcher::new(&directory).unwrap();
+    let mut manager = AssetManager::new();
+    let mut atlases = [AtlasGroup::new(
+        &mut renderer,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+    )];
+
+    let sprite = manager
+        .load(&sprite_path, 0, &mut atlases, &renderer)
+        .unwrap();
+    manager
+        .load_tilesheet(&tiles_path, 0, 20, &mut atlases, &renderer)
+        .unwrap();
+
+    assert!(watcher.changed().unwrap().is_empty());
+
+    // Same size so both are written over in place.
+    write_image(&sprite_path, 32, 32, &[blue]);
+    write_image(&tiles_path, 40, 20, &[yellow, red]);
+
+    let old = sprite.allocation();
+    let mut reloads = manager
+        .reload_changed(&mut watcher, &mut atlases, &renderer)
+        .unwrap();
+
+    reloads.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(reloads.len(), 2);
+    assert_eq!(reloads[0].name, "sprite.png");
+    assert!(matches!(reloads[0].result, Ok(false)));
+    assert_eq!(reloads[1].name, "tiles.png");
+    assert!(matches!(reloads[1].result, Ok(false)));
+    assert_eq!(sprite.allocation(), old);
+
+    // A new size moves it and the Image follows its handle.
+    write_image(&sprite_path, 48, 24, &[green, blue]);
+
+    let reloads = manager
+        .reload_changed(&mut watcher, &mut atlases, &renderer)
+        .unwrap();
+
+    assert_eq!(reloads.len(), 1);
+    assert!(matches!(reloads[0].result, Ok(true)));
+    assert_eq!(sprite.allocation().map(|a| a.size()), Some((48, 24)));
+
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    let mut image = Image::from_handle(sprite, &mut renderer, 1);
+
+    image.pos = Vec3::new(32.0, 128.0, 4.0);
+    image.hw = Vec2::new(96.0, 48.0);
+    image.uv = Vec4::new(0.0, 0.0, 48.0, 24.0);
+
+    let mut images = vec![image];
+
+    for id in 0..2 {
+        let mut tile = Image::new(
+            atlases[0].get(&format!("tiles.png-{id}")),
+            &mut renderer,
+            1,
+        );
+
+        tile.pos = Vec3::new(32.0 + id as f32 * 64.0, 32.0, 4.0);
+        tile.hw = Vec2::new(48.0, 48.0);
+        tile.uv = Vec4::new(0.0, 0.0, 20.0, 20.0);
+        images.push(tile);
+    }
+
+    let [atlas] = atlases;
+    let mut scene = ImageScene::new(&mut renderer, atlas, &mut images);
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("texture_hot_reload", &frame, CHANNEL_TOLERANCE);
+}
@@ -0,0 +1,383 @@
+//! Golden image tests for atlas compaction, packing, debug dumps, borders
+//! and pools.
+
+mod common;
+
+use ascending_graphics::*;
+use common::*;
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn atlas_compaction() {
+    let mut renderer = create_renderer();
+
+    let mut manager = AssetManager::new();
+    let mut atlases = [AtlasGroup::new(
+        &mut renderer,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+    )];
+
+    // Fills the first two layers so the images go in a third, then frees
+    // the first leaving the third sparse.
+    let size = atlases[0].atlas.extent.width;
+
+    for filler in ["filler", "full"] {
+        Texture::from_image(
+            filler.to_owned(),
+            image::DynamicImage::new_rgba8(size, size),
+        )
+        .group_upload(&mut atlases[0], &renderer)
+        .expect("failed to upload filler");
+        // Kept from being evicted for the images.
+        atlases[0].pin(&filler.to_owned());
+    }
+
+    let handles: Vec<TextureHandle> =
+        ["Female_1.png", "anim/0.png", "Tree.png", "fox.png"]
+            .into_iter()
+            .map(|file| {
+                manager
+                    .load(
+                        manifest_path(&format!("../images/{file}")),
+                        0,
+                        &mut atlases,
+                        &renderer,
+                    )
+                    .unwrap()
+            })
+            .collect();
+
+    assert!(handles
+        .iter()
+        .all(|handle| handle.allocation().unwrap().layer == 2));
+    atlases[0].remove(&"filler".to_owned());
+    // Uploads adding a layer leave making the bind group again to us.
+    atlases[0].texture = TextureGroup::from_view(
+        &mut renderer,
+        &atlases[0].atlas.texture_view,
+        TextureLayout,
+    );
+
+    let mut images: Vec<Image> = handles
+        .iter()
+        .enumerate()
+        .map(|(i, handle)| {
+            let mut image =
+                Image::from_handle(handle.clone(), &mut renderer, 1);
+
+            image.pos = Vec3::new(
+                16.0 + (i % 2) as f32 * 120.0,
+                16.0 + (i / 2) as f32 * 120.0,
+                4.0,
+            );
+            image.hw = Vec2::new(104.0, 104.0);
+            image.uv = Vec4::new(0.0, 0.0, 64.0, 64.0);
+            image
+        })
+        .collect();
+
+    let [atlas] = atlases;
+    let mut scene = ImageScene::new(&mut renderer, atlas, &mut images);
+    let before = capture(&renderer, &mut scene);
+    let generation = scene.atlas.atlas.generation;
+
+    // One image is moved each step.
+    assert_eq!(scene.atlas.start_compaction(), 4);
+    assert!(scene.atlas.atlas.is_compacting());
+
+    let mut steps = 1;
+
+    while !scene.atlas.compact(1, &mut renderer) {
+        steps += 1;
+    }
+
+    assert_eq!(steps, 4);
+    assert!(scene.atlas.atlas.generation > generation);
+    // The emptied third layer is dropped.
+    assert_eq!(scene.atlas.atlas.layers.len(), 2);
+    assert_eq!(scene.atlas.atlas.texture.depth_or_array_layers(), 2);
+
+    manager.update(std::slice::from_mut(&mut scene.atlas));
+    assert!(handles
+        .iter()
+        .all(|handle| handle.allocation().unwrap().layer == 0));
+
+    scene.update(&mut renderer, &mut images);
+
+    let after = capture(&renderer, &mut scene);
+
+    assert!(before == after, "the images moved while compacting");
+    assert_golden("atlas_compaction", &after, CHANNEL_TOLERANCE);
+}
+
+fn packed_scene(
+    renderer: &mut GpuRenderer,
+    atlas: AtlasGroup,
+    allocations: &[Allocation],
+) -> ImageScene {
+    let mut images: Vec<Image> = allocations
+        .iter()
+        .enumerate()
+        .map(|(i, allocation)| {
+            let (_, _, width, height) = allocation.rect();
+            let mut image = Image::new(Some(*allocation), renderer, 1);
+
+            image.pos = Vec3::new(
+                16.0 + (i % 2) as f32 * 120.0,
+                16.0 + (i / 2) as f32 * 120.0,
+                4.0,
+            );
+            image.hw = Vec2::new(104.0, 104.0);
+            image.uv = Vec4::new(0.0, 0.0, width as f32, height as f32);
+            image
+        })
+        .collect();
+
+    ImageScene::new(renderer, atlas, &mut images)
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn packed_atlas() {
+    let mut renderer = create_renderer();
+
+    let mut atlas =
+        AtlasGroup::new(&mut renderer, wgpu::TextureFormat::Rgba8UnormSrgb);
+    let path =
+        Path::new(env!("CARGO_TARGET_TMPDIR")).join("packed_atlas/atlas.json");
+    let mut packer =
+        AtlasPacker::<String, i32>::new(atlas.atlas.extent.width, 1);
+
+    assert_eq!(packer.add_directory(manifest_path("../images")).unwrap(), 4);
+
+    let (manifest, layers) = packer.pack("atlas").unwrap();
+
+    manifest.save(&path, &layers).unwrap();
+
+    let manifest = AtlasManifest::<String, i32>::from_file(&path).unwrap();
+    let layers = manifest.load_layers(&path).unwrap();
+
+    assert_eq!(manifest.layers, vec!["atlas_0.png".to_owned()]);
+
+    let packed = atlas
+        .load_packed(&manifest, &layers, &mut renderer)
+        .expect("failed to load packed atlas");
+
+    for (image, allocation) in manifest.images.iter().zip(&packed) {
+        assert_eq!(allocation.rect(), image.rect);
+        assert_eq!(atlas.peek(&image.name), Some(allocation));
+    }
+
+    // The same images uploaded one by one must look the same.
+    let mut uploaded_atlas =
+        AtlasGroup::new(&mut renderer, wgpu::TextureFormat::Rgba8UnormSrgb);
+    let uploaded: Vec<Allocation> = manifest
+        .images
+        .iter()
+        .map(|image| {
+            Texture::from_file(manifest_path(&format!(
+                "../images/{}",
+                image.name
+            )))
+            .unwrap()
+            .group_upload(&mut uploaded_atlas, &renderer)
+            .unwrap()
+        })
+        .collect();
+
+    let mut scene = packed_scene(&mut renderer, atlas, &packed);
+    let frame = capture(&renderer, &mut scene);
+    let mut scene = packed_scene(&mut renderer, uploaded_atlas, &uploaded);
+
+    assert!(frame == capture(&renderer, &mut scene));
+    assert_golden("packed_atlas", &frame, CHANNEL_TOLERANCE);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn atlas_debug_dump() {
+    let mut renderer = create_renderer();
+
+    let mut atlas =
+        AtlasGroup::new(&mut renderer, wgpu::TextureFormat::Rgba8UnormSrgb);
+    let directory = Path::new(env!("CARGO_TARGET_TMPDIR")).join("atlas_dump");
+    let allocations: Vec<Allocation> = ["fox.png", "accept.png", "Tree.png"]
+        .into_iter()
+        .map(|file| {
+            Texture::from_file(manifest_path(&format!("../images/{file}")))
+                .unwrap()
+                .group_upload(&mut atlas, &renderer)
+                .unwrap()
+        })
+        .collect();
+
+    atlas.pin(&"Tree.png".to_owned());
+
+    let paths = atlas.dump_layers(&directory, &mut renderer).unwrap();
+    let report = std::fs::read_to_string(directory.join("report.txt")).unwrap();
+
+    assert_eq!(paths.len(), atlas.atlas.layers.len());
+    assert!(report.starts_with("layer 0: 3 allocations"));
+    assert!(report.contains("layer 1: 0 allocations"));
+
+    // Tree.png was uploaded last so it is the most recently used.
+    let order: Vec<&str> = report
+        .lines()
+        .skip_while(|line| !line.starts_with("most recently used"))
+        .skip(1)
+        .collect();
+
+    assert_eq!(order.len(), 3);
+    assert!(order[0].starts_with("\"Tree.png\" layer 0"));
+    assert!(order[0].ends_with(" pinned"));
+    assert!(order[2].starts_with("\"fox.png\""));
+
+    let layer = image::open(&paths[0]).unwrap().into_rgba8();
+    let (x, y, width, height) = allocations[0].rect();
+
+    assert_eq!(layer.get_pixel(x, y), &Rgba([255, 0, 255, 255]));
+    assert_eq!(
+        layer.get_pixel(x + width - 1, y + height - 1),
+        &Rgba([255, 0, 255, 255])
+    );
+
+    let used = image::imageops::crop_imm(&layer, 0, 0, 256, 256).to_image();
+
+    assert_golden("atlas_debug_dump", &used, CHANNEL_TOLERANCE);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn atlas_edge_border() {
+    let mut renderer = create_renderer();
+
+    let colors = [
+        Rgba([255, 0, 0, 255]),
+        Rgba([0, 255, 0, 255]),
+        Rgba([0, 0, 255, 255]),
+        Rgba([255, 255, 0, 255]),
+    ];
+    let tile =
+        RgbaImage::from_fn(4, 4, |x, y| colors[(x / 2 + (y / 2) * 2) as usize]);
+    let transparent = Rgba([0, 0, 0, 0]);
+
+    // The border fills the whole padding without mipmaps and the padding
+    // nearest the image with them.
+    for (mip_levels, padding) in [(1, 2), (3, 4)] {
+        let mut atlas = AtlasGroup::with_border(
+            &mut renderer,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            mip_levels,
+            2,
+        );
+
+        assert_eq!(atlas.atlas.padding, padding);
+
+        let allocation = atlas
+            .upload("tile".to_owned(), tile.as_raw(), 4, 4, 0, &renderer)
+            .unwrap();
+        let (x, y, width, height) = allocation.rect();
+
+        assert_eq!((x, y, width, height), (padding, padding, 4, 4));
+
+        let layer = renderer
+            .read_texture_layer(&atlas.atlas.texture, allocation.layer as u32)
+            .unwrap();
+
+        assert_eq!(layer.get_pixel(x - 1, y + 1), tile.get_pixel(0, 1));
+        assert_eq!(layer.get_pixel(x - 2, y - 2), tile.get_pixel(0, 0));
+        assert_eq!(layer.get_pixel(x + 4, y + 3), tile.get_pixel(3, 3));
+        assert_eq!(layer.get_pixel(x + 2, y + 5), tile.get_pixel(2, 3));
+
+        if padding > 2 {
+            assert_eq!(layer.get_pixel(x - 3, y), &transparent);
+            assert_eq!(layer.get_pixel(x, y + 6), &transparent);
+        }
+
+        if mip_levels == 1 {
+            let used = image::imageops::crop_imm(&layer, 0, 0, 16, 16);
+
+            assert_golden(
+                "atlas_edge_border",
+                &image::imageops::resize(
+                    &used.to_image(),
+                    128,
+                    128,
+                    image::imageops::FilterType::Nearest,
+                ),
+                CHANNEL_TOLERANCE,
+            );
+        }
+    }
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn atlas_pools_and_eviction() {
+    let mut renderer = create_renderer();
+
+    let mut atlas: Atlas =
+        Atlas::new(&renderer, wgpu::TextureFormat::Rgba8UnormSrgb);
+    let size = atlas.extent.width;
+    let layer = vec![255; (size * size * 4) as usize];
+    let upload = |atlas: &mut Atlas, pool: usize, key: &str| {
+        atlas.upload_to_pool(
+            pool,
+            key.to_owned(),
+            &layer,
+            size,
+            size,
+            0,
+            &renderer,
+        )
+    };
+
+    // Room for two layers, one per pool.
+    atlas.max_layers = 3;
+    atlas.track_evictions();
+    assert_eq!(upload(&mut atlas, 1, "tiles").unwrap().layer, 0);
+    assert_eq!(upload(&mut atlas, 0, "glyph-0").unwrap().layer, 1);
+
+    // Only the glyph pool is evicted from even though the tiles are older.
+    assert_eq!(upload(&mut atlas, 0, "glyph-1").unwrap().layer, 1);
+    assert_eq!(atlas.take_evicted(), vec!["glyph-0".to_owned()]);
+    assert!(atlas.take_evicted().is_empty());
+    assert!(atlas.contains(&"tiles".to_owned()));
+
+    // Pinned keys are never evicted.
+    atlas.pin(&"glyph-1".to_owned());
+    assert!(upload(&mut atlas, 0, "glyph-2").is_none());
+    assert!(atlas.take_evicted().is_empty());
+
+    atlas.unpin(&"glyph-1".to_owned());
+    assert!(upload(&mut atlas, 0, "glyph-2").is_some());
+    assert_eq!(atlas.take_evicted(), vec!["glyph-1".to_owned()]);
+
+    // An emptied layer can be taken by another pool.
+    atlas.remove(&"tiles".to_owned());
+    assert_eq!(upload(&mut atlas, 2, "sprites").unwrap().layer, 0);
+    assert_eq!(atlas.layers[0].pool, 2);
+
+    // Tilesheets are only pinned when asked, with pins of their own.
+    let mut atlas =
+        AtlasGroup::new(&mut renderer, wgpu::TextureFormat::Rgba8UnormSrgb);
+    let texture =
+        Texture::from_file(manifest_path("../images/tiles/1.png")).unwrap();
+
+    TileSheet::upload(texture.clone(), &renderer, &mut atlas, 20).unwrap();
+    assert!(!atlas.atlas.is_pinned(&"1.png-0".to_owned()));
+
+    atlas.pin(&"1.png-0".to_owned());
+    TileSheet::pin(&texture, &mut atlas, 20);
+    assert!(atlas.atlas.is_pinned(&"Empty".to_owned()));
+    assert_eq!(atlas.atlas.pinned.get("1.png-0"), Some(&2));
+
+    TileSheet::unpin(&texture, &mut atlas, 20);
+    assert!(!atlas.atlas.is_pinned(&"Empty".to_owned()));
+    assert!(atlas.atlas.is_pinned(&"1.png-0".to_owned()));
+
+    // Evicted keys are only kept once asked for.
+    assert!(atlas.take_evicted().is_empty());
+}
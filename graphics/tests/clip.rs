@@ -0,0 +1,149 @@
+//! Golden image tests for the clip rect stack.
+
+mod common;
+
+use ascending_graphics::*;
+use common::*;
+
+struct ClipScene {
+    scene: ImageScene,
+    mesh_renderer: Mesh2DRenderer,
+}
+
+impl Pass for ClipScene {
+    fn render(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let mut pass = begin_pass(renderer, &self.scene.system, encoder);
+
+        pass.render_2dmeshs(renderer, &self.mesh_renderer, renderer.size());
+        pass.render_image(
+            renderer,
+            &self.scene.image_renderer,
+            &self.scene.atlas,
+            renderer.size(),
+        );
+    }
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn clip_stack() {
+    let mut renderer = create_renderer();
+
+    // Two Images in the panel, one in a clip nested within it and one
+    // unclipped.
+    let specs: Vec<ImageSpec> = [
+        Vec2::new(16.0, 16.0),
+        Vec2::new(96.0, 60.0),
+        Vec2::new(60.0, 140.0),
+        Vec2::new(180.0, 180.0),
+    ]
+    .into_iter()
+    .map(|pos| {
+        ImageSpec::sprite(Vec3::new(pos.x, pos.y, 4.0), Vec2::new(64.0, 64.0))
+    })
+    .collect();
+    let (mut scene, mut images) = image_scene(&mut renderer, &specs);
+    let mut mesh_renderer = Mesh2DRenderer::new(&renderer).unwrap();
+    let mut panel = Mesh2D::new(&mut renderer);
+    let mut builder = Mesh2DBuilder::default();
+
+    builder
+        .rectangle(
+            DrawMode::Fill(FillOptions::DEFAULT),
+            Vec4::new(0.0, 0.0, 256.0, 256.0),
+            5.0,
+            Color::rgba(60, 60, 60, 255),
+        )
+        .unwrap();
+    panel.from_builder(builder.finalize());
+
+    renderer.push_clip(Bounds::new(32.0, 32.0, 160.0, 224.0));
+    mesh_renderer.mesh_update(&mut panel, &mut renderer);
+    images[..2].iter_mut().for_each(|image| {
+        scene.image_renderer.image_update(image, &mut renderer)
+    });
+
+    // Nested clips are kept within the panel's clip.
+    renderer.push_clip(Bounds::new(0.0, 160.0, 100.0, 256.0));
+    assert_eq!(
+        renderer.clip(),
+        Some(Bounds::new(32.0, 160.0, 100.0, 224.0))
+    );
+    scene
+        .image_renderer
+        .image_update(&mut images[2], &mut renderer);
+
+    renderer.pop_clip();
+    renderer.pop_clip();
+    assert_eq!(renderer.clip(), None);
+
+    scene
+        .image_renderer
+        .image_update(&mut images[3], &mut renderer);
+    scene.image_renderer.finalize(&mut renderer);
+    mesh_renderer.finalize(&mut renderer);
+
+    let mut scene = ClipScene {
+        scene,
+        mesh_renderer,
+    };
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("clip_stack", &frame, CHANNEL_TOLERANCE);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn clip_offscreen_shared() {
+    let mut renderer = create_renderer();
+
+    let (scene, _) = image_scene(&mut renderer, &[]);
+    let mut mesh_renderer = Mesh2DRenderer::new(&renderer).unwrap();
+    let rectangle = |renderer: &mut GpuRenderer, x: f32, color: Color| {
+        let mut mesh = Mesh2D::new(renderer);
+        let mut builder = Mesh2DBuilder::default();
+
+        builder
+            .rectangle(
+                DrawMode::Fill(FillOptions::DEFAULT),
+                Vec4::new(x, 64.0, 64.0, 128.0),
+                1.0,
+                color,
+            )
+            .unwrap();
+        mesh.from_builder(builder.finalize());
+        mesh
+    };
+    let mut clipped = [
+        rectangle(&mut renderer, 16.0, Color::rgba(255, 0, 0, 255)),
+        rectangle(&mut renderer, 96.0, Color::rgba(0, 255, 0, 255)),
+    ];
+    let mut unclipped =
+        rectangle(&mut renderer, 176.0, Color::rgba(0, 0, 255, 255));
+
+    // Both meshes share a clip that leaves nothing on screen to draw in.
+    renderer.push_clip(Bounds::new(300.0, 300.0, 400.0, 400.0));
+    clipped
+        .iter_mut()
+        .for_each(|mesh| mesh_renderer.mesh_update(mesh, &mut renderer));
+    renderer.pop_clip();
+
+    mesh_renderer.mesh_update(&mut unclipped, &mut renderer);
+    mesh_renderer.finalize(&mut renderer);
+
+    let mut scene = ClipScene {
+        scene,
+        mesh_renderer,
+    };
+
+    renderer.reset_stats();
+
+    let frame = capture(&renderer, &mut scene);
+
+    assert_eq!(renderer.stats().draw_calls, 1);
+    assert_golden("clip_offscreen_shared", &frame, CHANNEL_TOLERANCE);
+}
@@ -0,0 +1,394 @@
+//! Helpers shared by the golden image tests.
+//!
+//! Every test renders a canned scene offscreen on a software adapter and
+//! compares the frame against a reference PNG in `tests/golden/`.
+//! Mismatching frames are written with a diff image to the cargo target tmp
+//! directory. Run with `GOLDEN_BLESS=1` to regenerate the references after an
+//! intended visual change.
+//!
+//! The tests are ignored by default as not every machine has a software
+//! adapter. Run them with `cargo test -- --include-ignored`, which fails
+//! instead of skipping when no adapter is found.
+
+// Each test file only uses some of the helpers.
+#![allow(dead_code)]
+
+use ascending_graphics::*;
+use camera::{
+    controls::{FlatControls, FlatSettings},
+    Projection,
+};
+use image::{Rgba, RgbaImage};
+use input::FrameTime;
+use std::path::{Path, PathBuf};
+use winit::dpi::PhysicalSize;
+
+pub const WIDTH: u32 = 256;
+pub const HEIGHT: u32 = 256;
+
+/// Largest difference allowed per color channel before a pixel mismatches.
+pub const CHANNEL_TOLERANCE: u8 = 2;
+/// Text is rasterized on the CPU from the system fonts so we allow a bit more.
+pub const TEXT_CHANNEL_TOLERANCE: u8 = 8;
+
+/// The fixed time every scene is rendered at so animations are stable.
+pub const SCENE_SECONDS: f32 = 1.25;
+
+pub fn manifest_path(path: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(path)
+}
+
+/// Creates a headless renderer on the fallback (software) adapter.
+/// Panics when the machine has no software adapter to render with.
+pub fn create_renderer() -> GpuRenderer {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::util::backend_bits_from_env()
+            .unwrap_or(wgpu::Backends::all()),
+        ..Default::default()
+    });
+
+    let renderer =
+        futures::executor::block_on(instance.create_headless_device(
+            &wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::LowPower,
+                compatible_surface: None,
+                force_fallback_adapter: true,
+            },
+            &wgpu::DeviceDescriptor {
+                features: wgpu::Features::default(),
+                limits: wgpu::Limits::default(),
+                label: None,
+            },
+            None,
+            PhysicalSize::new(WIDTH, HEIGHT),
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+        ));
+
+    match renderer {
+        Ok(renderer) => renderer,
+        Err(AscendingError::AdapterNotFound) => {
+            panic!("no software adapter found to run the golden tests on.")
+        }
+        Err(e) => panic!("failed to create renderer: {e}"),
+    }
+}
+
+pub fn create_system(renderer: &mut GpuRenderer) -> System<FlatControls> {
+    let size = renderer.size();
+
+    create_sized_system(renderer, size)
+}
+
+/// Creates a System whose projection and screen cover size.
+pub fn create_sized_system(
+    renderer: &mut GpuRenderer,
+    size: PhysicalSize<f32>,
+) -> System<FlatControls> {
+    let mut system = System::new(
+        renderer,
+        Projection::Orthographic {
+            left: 0.0,
+            right: size.width,
+            bottom: 0.0,
+            top: size.height,
+            near: 1.0,
+            far: -100.0,
+        },
+        FlatControls::new(FlatSettings { zoom: 1.0 }),
+        [size.width, size.height],
+    );
+
+    system.update(renderer, &FrameTime::from_seconds(SCENE_SECONDS));
+    system.update_screen(renderer, [size.width, size.height]);
+    system
+}
+
+/// The clear color every scene starts from.
+pub fn clear_ops() -> wgpu::Operations<wgpu::Color> {
+    wgpu::Operations {
+        load: wgpu::LoadOp::Clear(wgpu::Color {
+            r: 0.0,
+            g: 0.25,
+            b: 0.5,
+            a: 1.0,
+        }),
+        store: wgpu::StoreOp::Store,
+    }
+}
+
+/// Begins a render pass into the frame with the System and the shared
+/// vertex buffers set, the same way the demo sets up its pass.
+pub fn begin_pass<'a>(
+    renderer: &'a GpuRenderer,
+    system: &'a System<FlatControls>,
+    encoder: &'a mut wgpu::CommandEncoder,
+) -> wgpu::RenderPass<'a> {
+    begin_pass_into(
+        renderer,
+        system,
+        renderer.color_attachment(
+            renderer.frame_buffer().as_ref().expect("no frame view?"),
+            clear_ops(),
+        ),
+        renderer.depth_buffer(),
+        encoder,
+    )
+}
+
+pub fn begin_pass_into<'a>(
+    renderer: &'a GpuRenderer,
+    system: &'a System<FlatControls>,
+    color_attachment: wgpu::RenderPassColorAttachment<'a>,
+    depth_view: &'a wgpu::TextureView,
+    encoder: &'a mut wgpu::CommandEncoder,
+) -> wgpu::RenderPass<'a> {
+    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("golden render pass"),
+        color_attachments: &[Some(color_attachment)],
+        depth_stencil_attachment: Some(
+            wgpu::RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(0),
+                    store: wgpu::StoreOp::Store,
+                }),
+            },
+        ),
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+
+    pass.set_bind_group(0, system.bind_group(), &[]);
+    pass.set_vertex_buffer(0, renderer.buffer_object.vertices());
+    pass.set_index_buffer(
+        renderer.buffer_object.indices(),
+        wgpu::IndexFormat::Uint32,
+    );
+    pass
+}
+
+/// Submits the scene's pass and reads the frame back.
+pub fn capture(renderer: &GpuRenderer, scene: &mut impl Pass) -> RgbaImage {
+    let mut encoder = renderer.device().create_command_encoder(
+        &wgpu::CommandEncoderDescriptor {
+            label: Some("golden command encoder"),
+        },
+    );
+
+    scene.render(renderer, &mut encoder);
+    renderer.queue().submit(std::iter::once(encoder.finish()));
+    renderer.capture_frame().expect("failed to read back frame")
+}
+
+/// Compares a frame against its reference PNG. Pixels whose channels differ
+/// by more than tolerance are marked red in the diff image.
+pub fn assert_golden(name: &str, frame: &RgbaImage, tolerance: u8) {
+    let reference_path = manifest_path(&format!("tests/golden/{name}.png"));
+
+    if std::env::var_os("GOLDEN_BLESS").is_some() {
+        frame
+            .save(&reference_path)
+            .expect("failed to bless reference");
+        return;
+    }
+
+    let reference = match image::open(&reference_path) {
+        Ok(reference) => reference.to_rgba8(),
+        Err(e) => panic!(
+            "missing reference {}: {e}. Run with GOLDEN_BLESS=1 to create it.",
+            reference_path.display()
+        ),
+    };
+
+    assert_eq!(
+        reference.dimensions(),
+        frame.dimensions(),
+        "{name}: frame size does not match the reference"
+    );
+
+    let mut diff = RgbaImage::new(frame.width(), frame.height());
+    let mut mismatched = 0;
+
+    for (x, y, expected) in reference.enumerate_pixels() {
+        let actual = frame.get_pixel(x, y);
+        let differs = expected
+            .0
+            .iter()
+            .zip(actual.0.iter())
+            .any(|(e, a)| e.abs_diff(*a) > tolerance);
+
+        let pixel = if differs {
+            mismatched += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            // Dim the matching pixels so the mismatches stand out.
+            Rgba([expected[0] / 3, expected[1] / 3, expected[2] / 3, 255])
+        };
+
+        diff.put_pixel(x, y, pixel);
+    }
+
+    if mismatched > 0 {
+        let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+        std::fs::create_dir_all(&out).expect("failed to create output dir");
+
+        let actual_path = out.join(format!("{name}.actual.png"));
+        let diff_path = out.join(format!("{name}.diff.png"));
+
+        frame.save(&actual_path).expect("failed to save frame");
+        diff.save(&diff_path).expect("failed to save diff");
+
+        panic!(
+            "{name}: {mismatched} pixels differ from the reference by more \
+             than {tolerance}. Frame: {}, Diff: {}",
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+pub struct ImageScene {
+    pub system: System<FlatControls>,
+    pub image_renderer: ImageRenderer,
+    pub atlas: AtlasGroup,
+}
+
+impl ImageScene {
+    /// Draws images, whose textures are in atlas, with a new System and
+    /// ImageRenderer.
+    pub fn new(
+        renderer: &mut GpuRenderer,
+        atlas: AtlasGroup,
+        images: &mut [Image],
+    ) -> Self {
+        let mut scene = Self {
+            system: create_system(renderer),
+            image_renderer: ImageRenderer::new(renderer).unwrap(),
+            atlas,
+        };
+
+        scene.update(renderer, images);
+        scene
+    }
+
+    /// Adds the images to the ImageRenderer again and finalizes it.
+    pub fn update(&mut self, renderer: &mut GpuRenderer, images: &mut [Image]) {
+        images.iter_mut().for_each(|image| {
+            self.image_renderer.image_update(image, renderer)
+        });
+        self.image_renderer.finalize(renderer);
+    }
+}
+
+/// The sheet most scenes draw a sprite from with ImageSpec::sprite.
+pub const SPRITE_FILE: &str = "../images/Female_1.png";
+
+/// An Image for image_scene to upload the file of and draw.
+#[derive(Copy, Clone)]
+pub struct ImageSpec {
+    /// Path to the image file from the graphics crate.
+    pub file: &'static str,
+    pub pos: Vec3,
+    pub hw: Vec2,
+    pub uv: Vec4,
+    pub color: Color,
+    pub render_layer: u32,
+    /// The frames and switch time when animated.
+    pub animation: Option<(Vec2, u32)>,
+}
+
+impl ImageSpec {
+    pub fn new(file: &'static str, pos: Vec3, hw: Vec2, uv: Vec4) -> Self {
+        Self {
+            file,
+            pos,
+            hw,
+            uv,
+            color: Color::rgba(255, 255, 255, 255),
+            render_layer: 1,
+            animation: None,
+        }
+    }
+
+    /// The 48x48 sprite from SPRITE_FILE drawn at pos at size hw.
+    pub fn sprite(pos: Vec3, hw: Vec2) -> Self {
+        Self::new(SPRITE_FILE, pos, hw, Vec4::new(48.0, 96.0, 48.0, 48.0))
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn render_layer(mut self, render_layer: u32) -> Self {
+        self.render_layer = render_layer;
+        self
+    }
+
+    pub fn animated(mut self, frames: Vec2, switch_time: u32) -> Self {
+        self.animation = Some((frames, switch_time));
+        self
+    }
+
+    fn image(&self, texture: Allocation, renderer: &mut GpuRenderer) -> Image {
+        let mut image = Image::new(Some(texture), renderer, self.render_layer);
+
+        image.pos = self.pos;
+        image.hw = self.hw;
+        image.uv = self.uv;
+        image.color = self.color;
+
+        if let Some((frames, switch_time)) = self.animation {
+            image.frames = frames;
+            image.switch_time = switch_time;
+            image.animate = true;
+        }
+
+        image
+    }
+}
+
+/// Uploads the file of each spec to a new atlas and returns a scene
+/// drawing an Image for each, in the order given.
+pub fn image_scene(
+    renderer: &mut GpuRenderer,
+    specs: &[ImageSpec],
+) -> (ImageScene, Vec<Image>) {
+    let mut atlas =
+        AtlasGroup::new(renderer, wgpu::TextureFormat::Rgba8UnormSrgb);
+    let mut images: Vec<Image> = specs
+        .iter()
+        .map(|spec| {
+            let allocation = Texture::from_file(manifest_path(spec.file))
+                .unwrap()
+                .group_upload(&mut atlas, renderer)
+                .expect("failed to upload image");
+
+            spec.image(allocation, renderer)
+        })
+        .collect();
+    let scene = ImageScene::new(renderer, atlas, &mut images);
+
+    (scene, images)
+}
+
+impl Pass for ImageScene {
+    fn render(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let mut pass = begin_pass(renderer, &self.system, encoder);
+
+        pass.render_image(
+            renderer,
+            &self.image_renderer,
+            &self.atlas,
+            renderer.size(),
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Golden image tests for images: animation, culling, draw order,
+//! translucency and materials.
+
+mod common;
+
+use ascending_graphics::*;
+use camera::{
+    controls::{FlatControls, FlatSettings},
+    Projection,
+};
+use common::*;
+use input::FrameTime;
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn animated_images() {
+    let mut renderer = create_renderer();
+
+    let (mut scene, _images) = image_scene(
+        &mut renderer,
+        &[
+            ImageSpec::sprite(
+                Vec3::new(16.0, 16.0, 4.1),
+                Vec2::new(48.0, 48.0),
+            ),
+            ImageSpec::sprite(
+                Vec3::new(40.0, 16.0, 4.0),
+                Vec2::new(48.0, 48.0),
+            )
+            .color(Color::rgba(255, 255, 255, 120)),
+            ImageSpec::new(
+                "../images/anim/0.png",
+                Vec3::new(96.0, 96.0, 5.0),
+                Vec2::new(64.0, 64.0),
+                Vec4::new(0.0, 0.0, 64.0, 64.0),
+            )
+            .render_layer(2)
+            .animated(Vec2::new(8.0, 4.0), 300),
+        ],
+    );
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("animated_images", &frame, CHANNEL_TOLERANCE);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn culled_images() {
+    let mut renderer = create_renderer();
+
+    let size = renderer.size();
+    let view = create_system(&mut renderer).view_bounds();
+
+    assert!(view.world.left.abs() < 0.01 && view.world.bottom.abs() < 0.01);
+    assert!((view.world.right - size.width).abs() < 0.01);
+    assert!((view.world.top - size.height).abs() < 0.01);
+
+    renderer.set_view_bounds(Some(view));
+    renderer.reset_stats();
+
+    // On screen, over the right edge, off to the right and below.
+    let specs: Vec<ImageSpec> = [
+        Vec2::new(16.0, 16.0),
+        Vec2::new(size.width - 24.0, 96.0),
+        Vec2::new(size.width + 200.0, 96.0),
+        Vec2::new(96.0, -64.0),
+    ]
+    .into_iter()
+    .map(|pos| {
+        ImageSpec::sprite(Vec3::new(pos.x, pos.y, 4.0), Vec2::new(48.0, 48.0))
+    })
+    .collect();
+    let (mut scene, _images) = image_scene(&mut renderer, &specs);
+
+    let stats = renderer.reset_stats();
+
+    assert_eq!(stats.culled, 2);
+    assert_eq!(stats.instances, 2);
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("culled_images", &frame, CHANNEL_TOLERANCE);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn negative_draw_order() {
+    let mut renderer = create_renderer();
+
+    // Pairs of overlapping Images at the same depth. The lower red Image
+    // is added first and further left so only sorting by y puts it on top.
+    // The second pair has equal keys so the last added is on top.
+    let specs: Vec<ImageSpec> = [
+        (Vec2::new(-50.5, -40.25), Color::rgba(255, 80, 80, 255)),
+        (Vec2::new(-44.0, -30.75), Color::rgba(80, 255, 80, 255)),
+        (Vec2::new(20.0, 10.0), Color::rgba(80, 80, 255, 255)),
+        (Vec2::new(20.0, 10.0), Color::rgba(255, 255, 80, 255)),
+    ]
+    .into_iter()
+    .map(|(pos, color)| {
+        ImageSpec::sprite(Vec3::new(pos.x, pos.y, 4.0), Vec2::new(64.0, 64.0))
+            .color(color)
+    })
+    .collect();
+    let (mut scene, mut images) = image_scene(&mut renderer, &specs);
+
+    // The origin is in the middle of the screen.
+    scene.system = System::new(
+        &mut renderer,
+        Projection::Orthographic {
+            left: -128.0,
+            right: 128.0,
+            bottom: -128.0,
+            top: 128.0,
+            near: 1.0,
+            far: -100.0,
+        },
+        FlatControls::new(FlatSettings { zoom: 1.0 }),
+        [WIDTH as f32, HEIGHT as f32],
+    );
+    scene
+        .system
+        .update(&renderer, &FrameTime::from_seconds(SCENE_SECONDS));
+    scene
+        .system
+        .update_screen(&renderer, [WIDTH as f32, HEIGHT as f32]);
+    scene.image_renderer.set_sort_axis(SortAxis::Yxz);
+    scene.update(&mut renderer, &mut images);
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("negative_draw_order", &frame, CHANNEL_TOLERANCE);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn translucent_images() {
+    let mut renderer = create_renderer();
+
+    // The translucent Images are added front first. Without the back to
+    // front pass the red one would hide the blue one where they overlap.
+    let specs: Vec<ImageSpec> = [
+        (Vec3::new(48.0, 40.0, 3.0), Color::rgba(255, 60, 60, 160)),
+        (Vec3::new(72.0, 56.0, 4.0), Color::rgba(60, 60, 255, 160)),
+        (Vec3::new(60.0, 72.0, 5.0), Color::rgba(255, 255, 255, 255)),
+    ]
+    .into_iter()
+    .map(|(pos, color)| {
+        ImageSpec::sprite(pos, Vec2::new(144.0, 144.0)).color(color)
+    })
+    .collect();
+    let (mut scene, _images) = image_scene(&mut renderer, &specs);
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("translucent_images", &frame, CHANNEL_TOLERANCE);
+}
+
+/// Draws the Image in grey scale tinted by the material color.
+const TINT_MATERIAL: &str = "
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let color = image_color(vertex);
+
+    if (color.a <= 0.0) {
+        discard;
+    }
+
+    let grey = dot(color.rgb, vec3<f32>(0.299, 0.587, 0.114));
+    return vec4<f32>(vec3<f32>(grey) * material.color.rgb, color.a);
+}
+";
+
+/// Discards pixels whose noise is below params.x.
+const DISSOLVE_MATERIAL: &str = "
+fn noise(pos: vec2<f32>) -> f32 {
+    return fract(sin(dot(floor(pos / 4.0), vec2<f32>(12.9898, 78.233))) * 43758.547);
+}
+
+@fragment
+fn fragment(vertex: VertexOutput,) -> @location(0) vec4<f32> {
+    let color = image_color(vertex);
+
+    if (color.a <= 0.0 || noise(vertex.clip_position.xy) < material.params.x) {
+        discard;
+    }
+
+    return color;
+}
+";
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn image_materials() {
+    let mut renderer = create_renderer();
+
+    let specs: Vec<ImageSpec> = (0..5)
+        .map(|i| {
+            ImageSpec::sprite(
+                Vec3::new(8.0 + i as f32 * 40.0, 96.0, 5.0 - i as f32),
+                Vec2::new(64.0, 64.0),
+            )
+        })
+        .collect();
+    let (mut scene, mut images) = image_scene(&mut renderer, &specs);
+
+    assert!(matches!(
+        Material::new(
+            &mut renderer,
+            "broken.wgsl",
+            "@fragment fn fragment() -> @location(0) vec4<f32> { return 1; }",
+            Vec4::default(),
+            Color::rgba(255, 255, 255, 255),
+        ),
+        Err(AscendingError::Shader(_))
+    ));
+
+    let tint = scene.image_renderer.add_material(
+        Material::new(
+            &mut renderer,
+            "tint.wgsl",
+            TINT_MATERIAL,
+            Vec4::default(),
+            Color::rgba(255, 160, 64, 255),
+        )
+        .unwrap(),
+    );
+    let dissolve = scene.image_renderer.add_material(
+        Material::new(
+            &mut renderer,
+            "dissolve.wgsl",
+            DISSOLVE_MATERIAL,
+            Vec4::new(0.5, 0.0, 0.0, 0.0),
+            Color::rgba(255, 255, 255, 255),
+        )
+        .unwrap(),
+    );
+
+    // Alternate the materials so batches have to follow the DrawOrder.
+    let materials = [None, Some(tint), None, Some(dissolve), Some(dissolve)];
+
+    images
+        .iter_mut()
+        .zip(materials)
+        .for_each(|(image, material)| image.material = material);
+    scene.update(&mut renderer, &mut images);
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("image_materials", &frame, CHANNEL_TOLERANCE);
+}
@@ -0,0 +1,92 @@
+//! Golden image tests for area and directional lights.
+
+mod common;
+
+use ascending_graphics::*;
+use camera::controls::FlatControls;
+use common::*;
+
+struct LightScene {
+    system: System<FlatControls>,
+    light_renderer: LightRenderer,
+}
+
+impl Pass for LightScene {
+    fn render(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let mut pass = begin_pass(renderer, &self.system, encoder);
+
+        pass.render_lights(renderer, &self.light_renderer, renderer.size());
+    }
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn area_and_directional_lights() {
+    let mut renderer = create_renderer();
+
+    let system = create_system(&mut renderer);
+    let mut light_renderer = LightRenderer::new(&mut renderer).unwrap();
+    let mut lights = Lights::new(&mut renderer, 0);
+
+    lights.world_color = Vec4::new(0.0, 0.0, 0.0, 0.8);
+    lights.enable_lights = true;
+
+    lights.insert_area_light(AreaLight {
+        pos: Vec2::new(64.0, 64.0),
+        color: Color::rgba(255, 255, 0, 20),
+        max_distance: 40.0,
+        animate: false,
+        anim_speed: 5.0,
+        dither: 0.5,
+    });
+
+    lights.insert_area_light(AreaLight {
+        pos: Vec2::new(192.0, 64.0),
+        color: Color::rgba(255, 0, 0, 20),
+        max_distance: 32.0,
+        animate: true,
+        anim_speed: 5.0,
+        dither: 0.8,
+    });
+
+    lights.insert_directional_light(DirectionalLight {
+        pos: Vec2::new(64.0, 160.0),
+        color: Color::rgba(255, 255, 0, 20),
+        max_distance: 90.0,
+        max_width: 15.0,
+        anim_speed: 2.0,
+        angle: 90.0,
+        dither: 6.0,
+        fade_distance: 5.0,
+        edge_fade_distance: 0.5,
+        animate: false,
+    });
+
+    lights.insert_directional_light(DirectionalLight {
+        pos: Vec2::new(192.0, 160.0),
+        color: Color::rgba(0, 255, 255, 20),
+        max_distance: 60.0,
+        max_width: 10.0,
+        anim_speed: 2.0,
+        angle: 45.0,
+        dither: 6.0,
+        fade_distance: 4.0,
+        edge_fade_distance: 0.6,
+        animate: true,
+    });
+
+    light_renderer.lights_update(&mut lights, &mut renderer);
+    light_renderer.finalize(&mut renderer);
+
+    let mut scene = LightScene {
+        system,
+        light_renderer,
+    };
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("lights", &frame, CHANNEL_TOLERANCE);
+}
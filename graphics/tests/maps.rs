@@ -0,0 +1,204 @@
+//! Golden image tests for map layers and mipmapped map tiles.
+
+mod common;
+
+use ascending_graphics::*;
+use camera::{
+    controls::{FlatControls, FlatSettings},
+    Projection,
+};
+use common::*;
+use input::FrameTime;
+
+struct MapScene {
+    system: System<FlatControls>,
+    map_renderer: MapRenderer,
+    atlas: AtlasGroup,
+}
+
+impl Pass for MapScene {
+    fn render(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let mut pass = begin_pass(renderer, &self.system, encoder);
+
+        pass.render_lower_maps(
+            renderer,
+            &self.map_renderer,
+            &self.atlas,
+            renderer.size(),
+        );
+        pass.render_upper_maps(
+            renderer,
+            &self.map_renderer,
+            &self.atlas,
+            renderer.size(),
+        );
+    }
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn map_layers() {
+    let mut renderer = create_renderer();
+
+    let system = create_system(&mut renderer);
+    let mut atlas =
+        AtlasGroup::new(&mut renderer, wgpu::TextureFormat::Rgba8UnormSrgb);
+    let mut map_renderer = MapRenderer::new(&mut renderer, 1).unwrap();
+
+    Texture::from_file(manifest_path("../images/tiles/1.png"))
+        .unwrap()
+        .new_tilesheet(&mut atlas, &renderer, 20)
+        .expect("failed to upload tiles");
+
+    let mut map = Map::new(&mut renderer, 20);
+
+    (0..13).for_each(|x| {
+        (0..13).for_each(|y| {
+            map.set_tile(
+                (x, y, 0),
+                TileData {
+                    texture_id: 1,
+                    texture_layer: 0,
+                    color: Color::rgba(255, 255, 255, 255),
+                },
+            );
+        });
+    });
+
+    // Place one tile per upper layer in a diagonal so every layer shows.
+    (1..MapLayers::Count as u32).for_each(|layer| {
+        map.set_tile(
+            (layer, layer, layer),
+            TileData {
+                texture_id: 2,
+                texture_layer: 0,
+                color: Color::rgba(255, 255, 255, 255),
+            },
+        );
+        map.set_tile(
+            (layer + 1, layer, layer),
+            TileData {
+                texture_id: 1 + layer,
+                texture_layer: 0,
+                color: Color::rgba(255, 255, 255, 160),
+            },
+        );
+    });
+
+    map.can_render = true;
+    map_renderer.map_update(&mut map, &mut renderer);
+    map_renderer.finalize(&mut renderer);
+
+    let mut scene = MapScene {
+        system,
+        map_renderer,
+        atlas,
+    };
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("map_layers", &frame, CHANNEL_TOLERANCE);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn mipmapped_map() {
+    let mut renderer = create_renderer();
+
+    // Zoomed out so the tiles are drawn between mip levels 1 and 2.
+    let mut system = System::new(
+        &mut renderer,
+        Projection::Orthographic {
+            left: 0.0,
+            right: WIDTH as f32,
+            bottom: 0.0,
+            top: HEIGHT as f32,
+            near: 1.0,
+            far: -100.0,
+        },
+        FlatControls::new(FlatSettings { zoom: 0.375 }),
+        [WIDTH as f32, HEIGHT as f32],
+    );
+
+    system.update(&renderer, &FrameTime::from_seconds(SCENE_SECONDS));
+    system.update_screen(&renderer, [WIDTH as f32, HEIGHT as f32]);
+
+    let mut atlas = AtlasGroup::with_mip_levels(
+        &mut renderer,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        3,
+    );
+    let mut map_renderer = MapRenderer::new(&mut renderer, 1).unwrap();
+
+    assert_eq!(atlas.atlas.mip_levels, 3);
+    assert_eq!(atlas.atlas.padding, 4);
+    // The full chain would pad every tile by half the atlas.
+    assert_eq!(
+        AtlasGroup::<String, i32>::with_mip_levels(
+            &mut renderer,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            12,
+        )
+        .atlas
+        .mip_levels,
+        MAX_ATLAS_MIP_LEVELS
+    );
+
+    Texture::from_file(manifest_path("../images/tiles/1.png"))
+        .unwrap()
+        .new_tilesheet(&mut atlas, &renderer, 20)
+        .expect("failed to upload tiles");
+
+    // The map shader expects tile id i in the padded grid cell i, after
+    // the Empty tile, with the padding left out of the allocation. Only
+    // the first row of the atlas is packed in order.
+    let padding = atlas.atlas.padding;
+    let cell = 20 + padding * 2;
+    let columns = atlas.atlas.extent.width / cell;
+
+    for id in 0..columns - 1 {
+        let tile = *atlas.peek(&format!("1.png-{id}")).unwrap();
+        let index = id + 1;
+
+        assert_eq!(tile.size(), (20, 20));
+        assert_eq!(
+            tile.position(),
+            (
+                (index % columns) * cell + padding,
+                (index / columns) * cell + padding
+            )
+        );
+    }
+
+    let mut map = Map::new(&mut renderer, 20);
+    map.padding = padding;
+
+    (0..32).for_each(|x| {
+        (0..32).for_each(|y| {
+            map.set_tile(
+                (x, y, 0),
+                TileData {
+                    texture_id: (x * 7 + y * 3) % (columns - 1) + 1,
+                    texture_layer: 0,
+                    color: Color::rgba(255, 255, 255, 255),
+                },
+            );
+        });
+    });
+
+    map.can_render = true;
+    map_renderer.map_update(&mut map, &mut renderer);
+    map_renderer.finalize(&mut renderer);
+
+    let mut scene = MapScene {
+        system,
+        map_renderer,
+        atlas,
+    };
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("mipmapped_map", &frame, CHANNEL_TOLERANCE);
+}
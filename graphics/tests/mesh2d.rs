@@ -0,0 +1,327 @@
+//! Golden image tests for Mesh2D shapes, MSAA, stats, profiling and shader
+//! reloading.
+
+mod common;
+
+use ascending_graphics::*;
+use camera::controls::FlatControls;
+use common::*;
+
+struct MeshScene {
+    system: System<FlatControls>,
+    mesh_renderer: Mesh2DRenderer,
+    mesh: Mesh2D,
+}
+
+impl Pass for MeshScene {
+    fn render(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let mut pass = begin_pass(renderer, &self.system, encoder);
+
+        pass.render_2dmeshs(renderer, &self.mesh_renderer, renderer.size());
+    }
+}
+
+fn mesh_scene(renderer: &mut GpuRenderer) -> MeshScene {
+    let system = create_system(renderer);
+    let mut mesh_renderer = Mesh2DRenderer::new(renderer).unwrap();
+
+    let mut builder = Mesh2DBuilder::default();
+
+    builder
+        .circle(
+            DrawMode::Fill(FillOptions::DEFAULT),
+            Vec2::new(64.0, 64.0),
+            40.0,
+            0.5,
+            1.0,
+            Color::rgba(0, 0, 255, 255),
+        )
+        .unwrap()
+        .circle(
+            DrawMode::Stroke(StrokeOptions::DEFAULT),
+            Vec2::new(64.0, 64.0),
+            40.0,
+            0.5,
+            1.0,
+            Color::rgba(255, 255, 255, 255),
+        )
+        .unwrap()
+        .rectangle(
+            DrawMode::Fill(FillOptions::DEFAULT),
+            Vec4::new(140.0, 30.0, 80.0, 70.0),
+            1.0,
+            Color::rgba(255, 0, 0, 200),
+        )
+        .unwrap()
+        .polyline(
+            DrawMode::stroke(3.0),
+            &[
+                Vec2::new(20.0, 230.0),
+                Vec2::new(128.0, 140.0),
+                Vec2::new(236.0, 230.0),
+            ],
+            1.0,
+            Color::rgba(0, 255, 0, 255),
+        )
+        .unwrap();
+
+    let mut mesh = Mesh2D::new(renderer);
+    mesh.from_builder(builder.finalize());
+
+    mesh_renderer.mesh_update(&mut mesh, renderer);
+    mesh_renderer.finalize(renderer);
+
+    MeshScene {
+        system,
+        mesh_renderer,
+        mesh,
+    }
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn mesh2d_shapes() {
+    let mut renderer = create_renderer();
+
+    let mut scene = mesh_scene(&mut renderer);
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("mesh2d_shapes", &frame, CHANNEL_TOLERANCE);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn mesh2d_shapes_msaa() {
+    let mut renderer = create_renderer();
+
+    assert!(matches!(
+        renderer.set_sample_count(3),
+        Err(AscendingError::UnsupportedSampleCount(3))
+    ));
+
+    // 4 samples is always supported for the formats we render with.
+    renderer.set_sample_count(4).unwrap();
+    assert_eq!(renderer.sample_count(), 4);
+    assert!(renderer.msaa_buffer().is_some());
+
+    let mut scene = mesh_scene(&mut renderer);
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("mesh2d_shapes_msaa", &frame, CHANNEL_TOLERANCE);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn mesh2d_shapes_linear() {
+    use wgpu::TextureFormat;
+
+    let formats = [TextureFormat::Bgra8Unorm, TextureFormat::Rgba8UnormSrgb];
+
+    assert_eq!(
+        pick_surface_format(&formats, SurfaceColor::Linear),
+        Some(TextureFormat::Bgra8Unorm)
+    );
+    assert_eq!(pick_surface_format(&formats, SurfaceColor::Hdr), None);
+
+    let mut renderer = create_renderer();
+
+    assert_eq!(renderer.present_mode(), None);
+    assert!(matches!(
+        renderer.set_surface_format(TextureFormat::Depth32Float),
+        Err(AscendingError::UnsupportedSurfaceFormat(_))
+    ));
+
+    // The shader colors are written without the sRGB conversion so the
+    // frame comes out darker.
+    let format = renderer.set_surface_color(SurfaceColor::Linear).unwrap();
+    assert_eq!(format, TextureFormat::Rgba8Unorm);
+    assert_eq!(renderer.surface_format(), TextureFormat::Rgba8Unorm);
+
+    let mut scene = mesh_scene(&mut renderer);
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("mesh2d_shapes_linear", &frame, CHANNEL_TOLERANCE);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn mesh2d_shapes_hdr() {
+    use wgpu::TextureFormat;
+
+    let mut renderer = create_renderer();
+
+    // Float frames are read back as sRGB so they match the sRGB frame.
+    let format = renderer.set_surface_color(SurfaceColor::Hdr).unwrap();
+    assert_eq!(format, TextureFormat::Rgba16Float);
+
+    let mut scene = mesh_scene(&mut renderer);
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("mesh2d_shapes", &frame, CHANNEL_TOLERANCE);
+
+    // 10 bit frames are written as the shaders return them.
+    renderer
+        .set_surface_format(TextureFormat::Rgb10a2Unorm)
+        .unwrap();
+
+    let mut scene = mesh_scene(&mut renderer);
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("mesh2d_shapes_linear", &frame, CHANNEL_TOLERANCE);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn mesh2d_render_stats() {
+    let mut renderer = create_renderer();
+
+    let mut scene = mesh_scene(&mut renderer);
+    let uploaded = renderer.reset_stats();
+
+    assert!(uploaded.vertices > 0);
+    assert!(uploaded.indices > 0);
+    assert!(uploaded.bytes_uploaded > 0);
+    assert_eq!(uploaded.buffer_writes, 2);
+    assert_eq!(uploaded.draw_calls, 0);
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("mesh2d_shapes", &frame, CHANNEL_TOLERANCE);
+
+    let rendered = renderer.reset_stats();
+
+    assert_eq!(rendered.draw_calls, 1);
+    assert_eq!(rendered.bytes_uploaded, 0);
+
+    // A mesh that did not change or move is not uploaded again.
+    scene
+        .mesh_renderer
+        .mesh_update(&mut scene.mesh, &mut renderer);
+    scene.mesh_renderer.finalize(&mut renderer);
+
+    let unchanged = renderer.reset_stats();
+
+    assert_eq!(unchanged.vertices, uploaded.vertices);
+    assert_eq!(unchanged.indices, uploaded.indices);
+    assert_eq!(unchanged.bytes_uploaded, 0);
+    assert_eq!(unchanged.buffer_writes, 0);
+    assert_eq!(unchanged.buffer_resizes, 0);
+    assert_eq!(renderer.stats(), RenderStats::default());
+}
+
+struct ProfiledMeshScene {
+    scene: MeshScene,
+    profiler: GpuProfiler,
+}
+
+impl Pass for ProfiledMeshScene {
+    fn render(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let mut pass = begin_pass(renderer, &self.scene.system, encoder);
+
+        self.profiler
+            .profile_pass(&mut pass, "render_2dmeshs", |pass| {
+                pass.render_2dmeshs(
+                    renderer,
+                    &self.scene.mesh_renderer,
+                    renderer.size(),
+                )
+            });
+
+        drop(pass);
+        self.profiler.resolve(encoder);
+    }
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn mesh2d_shapes_profiled() {
+    let mut renderer = create_renderer();
+
+    let mut scene = ProfiledMeshScene {
+        scene: mesh_scene(&mut renderer),
+        profiler: GpuProfiler::new(&renderer, 2),
+    };
+
+    for _ in 0..3 {
+        scene.profiler.begin_frame();
+        scene.profiler.profile_cpu("finalize", || {
+            let mesh = &mut scene.scene;
+
+            mesh.mesh_renderer
+                .mesh_update(&mut mesh.mesh, &mut renderer);
+            mesh.mesh_renderer.finalize(&mut renderer)
+        });
+
+        let frame = capture(&renderer, &mut scene);
+        scene.profiler.end_frame(&renderer).unwrap();
+
+        // Timing the pass must not change what it draws.
+        assert_golden("mesh2d_shapes", &frame, CHANNEL_TOLERANCE);
+    }
+
+    let profiler = &scene.profiler;
+    let source = if profiler.is_gpu() {
+        ProfileSource::Gpu
+    } else {
+        ProfileSource::Cpu
+    };
+    let render = profiler.timing("render_2dmeshs").unwrap();
+
+    assert_eq!(render.source, source);
+    assert_eq!(render.samples().len(), 2);
+    assert!(render.average_ms() >= 0.0);
+    assert!(render.max_ms() >= render.last_ms());
+
+    let finalize = profiler.timing("finalize").unwrap();
+
+    assert_eq!(finalize.source, ProfileSource::Cpu);
+    assert_eq!(profiler.timings().len(), 2);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn mesh2d_shader_reload() {
+    let mut renderer = create_renderer();
+
+    let mut scene = mesh_scene(&mut renderer);
+    let shader = include_str!("../src/shaders/2dmeshshader.wgsl");
+
+    // A broken shader reports where it broke and keeps the old pipeline.
+    let broken =
+        shader.replace("return vertex.color;", "return vertex.colour;");
+    let line = shader
+        .lines()
+        .position(|line| line.contains("return vertex.color;"))
+        .unwrap() as u32
+        + 1;
+
+    match renderer.reload_shader("2dmeshshader.wgsl", broken) {
+        Err(AscendingError::Shader(error)) => {
+            assert_eq!(error.file, "2dmeshshader.wgsl");
+            assert_eq!(error.line, line);
+        }
+        result => panic!("expected a shader error, got {result:?}"),
+    }
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("mesh2d_shapes", &frame, CHANNEL_TOLERANCE);
+
+    // Swap in a shader that inverts the mesh colors. Both the opaque and
+    // translucent pipelines use it.
+    let inverted = shader.replace(
+        "return vertex.color;",
+        "return vec4<f32>(vec3<f32>(1.0) - vertex.color.rgb, vertex.color.a);",
+    );
+
+    assert_eq!(
+        renderer
+            .reload_shader("2dmeshshader.wgsl", inverted)
+            .unwrap(),
+        2
+    );
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("mesh2d_shader_reload", &frame, CHANNEL_TOLERANCE);
+}
@@ -0,0 +1,120 @@
+//! Golden image tests for post processing effects.
+
+mod common;
+
+use ascending_graphics::*;
+use camera::controls::FlatControls;
+use common::*;
+
+struct PostProcessScene {
+    system: System<FlatControls>,
+    mesh_renderer: Mesh2DRenderer,
+    post_process: PostProcess,
+}
+
+impl Pass for PostProcessScene {
+    fn render(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        {
+            let mut pass = begin_pass_into(
+                renderer,
+                &self.system,
+                renderer.color_attachment(
+                    self.post_process.scene_view(),
+                    clear_ops(),
+                ),
+                renderer.depth_buffer(),
+                encoder,
+            );
+
+            pass.render_2dmeshs(renderer, &self.mesh_renderer, renderer.size());
+        }
+
+        self.post_process.render(renderer, &self.system, encoder);
+    }
+}
+
+fn post_process_scene(
+    renderer: &mut GpuRenderer,
+    effects: Vec<PostEffect>,
+) -> PostProcessScene {
+    let system = create_system(renderer);
+    let mut mesh_renderer = Mesh2DRenderer::new(renderer).unwrap();
+    let mut builder = Mesh2DBuilder::default();
+
+    builder
+        .circle(
+            DrawMode::Fill(FillOptions::DEFAULT),
+            Vec2::new(96.0, 128.0),
+            48.0,
+            0.5,
+            1.0,
+            Color::rgba(255, 255, 255, 255),
+        )
+        .unwrap()
+        .rectangle(
+            DrawMode::Fill(FillOptions::DEFAULT),
+            Vec4::new(150.0, 60.0, 70.0, 140.0),
+            1.0,
+            Color::rgba(255, 120, 0, 255),
+        )
+        .unwrap();
+
+    let mut mesh = Mesh2D::new(renderer);
+    mesh.from_builder(builder.finalize());
+    mesh_renderer.mesh_update(&mut mesh, renderer);
+    mesh_renderer.finalize(renderer);
+
+    let mut post_process = PostProcess::new(renderer);
+
+    effects.into_iter().for_each(|effect| {
+        post_process.push(effect);
+    });
+    post_process.update(renderer);
+
+    PostProcessScene {
+        system,
+        mesh_renderer,
+        post_process,
+    }
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn post_process_grading() {
+    let mut renderer = create_renderer();
+
+    let effects = vec![
+        PostEffect::color_grade(&mut renderer, &neutral_lut(16), 1.0).unwrap(),
+        PostEffect::bloom(&mut renderer, 0.6, 1.5, 12.0),
+        PostEffect::vignette(
+            &mut renderer,
+            0.6,
+            0.3,
+            Color::rgba(0, 0, 0, 200),
+        ),
+        PostEffect::fade(&mut renderer, Color::rgba(255, 0, 0, 255), 0.25),
+    ];
+    let mut scene = post_process_scene(&mut renderer, effects);
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("post_process_grading", &frame, CHANNEL_TOLERANCE);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn post_process_retro() {
+    let mut renderer = create_renderer();
+
+    let effects = vec![
+        PostEffect::pixelate(&mut renderer, 8.0),
+        PostEffect::crt(&mut renderer, 0.5, 0.25, 2.0, 0.0),
+    ];
+    let mut scene = post_process_scene(&mut renderer, effects);
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("post_process_retro", &frame, CHANNEL_TOLERANCE);
+}
@@ -0,0 +1,55 @@
+//! Golden image tests for recovering from a lost device.
+
+mod common;
+
+use ascending_graphics::*;
+use common::*;
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn device_recovery() {
+    let mut renderer = create_renderer();
+
+    let (mut scene, mut images) = image_scene(
+        &mut renderer,
+        &[ImageSpec::sprite(
+            Vec3::new(64.0, 64.0, 4.0),
+            Vec2::new(128.0, 128.0),
+        )],
+    );
+    let before = capture(&renderer, &mut scene);
+
+    // wgpu only reports the loss through the errors of later calls.
+    renderer.device().destroy();
+    renderer.device().create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 16,
+        usage: wgpu::BufferUsages::VERTEX,
+        mapped_at_creation: false,
+    });
+    assert!(renderer.is_device_lost());
+
+    renderer.recover_device().expect("failed to recover device");
+    assert!(!renderer.is_device_lost());
+    assert_eq!(renderer.take_events(), vec![RendererEvent::DeviceRecovered]);
+
+    scene.system.recreate(&mut renderer);
+    // Without retain_uploads the owner writes its image again.
+    let texture = Texture::from_file(manifest_path(SPRITE_FILE)).unwrap();
+
+    scene.atlas.recreate(&mut renderer);
+    assert_eq!(
+        scene.atlas.reupload(
+            &texture.name().to_owned(),
+            texture.bytes(),
+            &renderer
+        ),
+        images[0].texture
+    );
+    scene.image_renderer.recreate(&mut renderer);
+    scene.update(&mut renderer, &mut images);
+
+    let after = capture(&renderer, &mut scene);
+    assert_golden("device_recovery", &before, CHANNEL_TOLERANCE);
+    assert_golden("device_recovery", &after, CHANNEL_TOLERANCE);
+}
@@ -0,0 +1,167 @@
+//! Golden image tests for rendering into a RenderTarget used as an Image.
+
+mod common;
+
+use ascending_graphics::*;
+use camera::controls::FlatControls;
+use common::*;
+use winit::dpi::PhysicalSize;
+
+struct RenderTargetScene {
+    system: System<FlatControls>,
+    target_system: System<FlatControls>,
+    target: RenderTarget,
+    mesh_renderer: Mesh2DRenderer,
+    image_renderer: ImageRenderer,
+}
+
+impl Pass for RenderTargetScene {
+    fn render(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        {
+            let mut pass = begin_pass_into(
+                renderer,
+                &self.target_system,
+                self.target.color_attachment(clear_ops()),
+                self.target.depth_view(),
+                encoder,
+            );
+
+            pass.render_2dmeshs(
+                renderer,
+                &self.mesh_renderer,
+                self.target.size().cast(),
+            );
+        }
+
+        let mut pass = begin_pass(renderer, &self.system, encoder);
+
+        pass.render_image_target(
+            renderer,
+            &self.image_renderer,
+            &self.target,
+            renderer.size(),
+        );
+    }
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn render_target_image() {
+    let mut renderer = create_renderer();
+
+    let system = create_system(&mut renderer);
+    let target_system =
+        create_sized_system(&mut renderer, PhysicalSize::new(64.0, 64.0));
+    let target = RenderTarget::new(&mut renderer, PhysicalSize::new(64, 64));
+    let mut mesh_renderer = Mesh2DRenderer::new(&renderer).unwrap();
+    let mut image_renderer = ImageRenderer::new(&renderer).unwrap();
+
+    let mut builder = Mesh2DBuilder::default();
+
+    builder
+        .circle(
+            DrawMode::Fill(FillOptions::DEFAULT),
+            Vec2::new(32.0, 32.0),
+            24.0,
+            0.5,
+            1.0,
+            Color::rgba(255, 255, 0, 255),
+        )
+        .unwrap()
+        .rectangle(
+            DrawMode::Fill(FillOptions::DEFAULT),
+            Vec4::new(0.0, 0.0, 32.0, 16.0),
+            1.0,
+            Color::rgba(255, 0, 255, 255),
+        )
+        .unwrap();
+
+    let mut mesh = Mesh2D::new(&mut renderer);
+    mesh.from_builder(builder.finalize());
+
+    mesh_renderer.mesh_update(&mut mesh, &mut renderer);
+    mesh_renderer.finalize(&mut renderer);
+
+    // Draw the target twice, once scaled up, through the Image path.
+    let mut portrait = Image::new(Some(target.allocation()), &mut renderer, 1);
+    portrait.pos = Vec3::new(16.0, 16.0, 5.0);
+    portrait.hw = Vec2::new(64.0, 64.0);
+    portrait.uv = Vec4::new(0.0, 0.0, 64.0, 64.0);
+
+    let mut minimap = Image::new(Some(target.allocation()), &mut renderer, 1);
+    minimap.pos = Vec3::new(112.0, 112.0, 5.0);
+    minimap.hw = Vec2::new(128.0, 128.0);
+    minimap.uv = Vec4::new(0.0, 0.0, 64.0, 64.0);
+
+    image_renderer.image_update(&mut portrait, &mut renderer);
+    image_renderer.image_update(&mut minimap, &mut renderer);
+    image_renderer.finalize(&mut renderer);
+
+    let mut scene = RenderTargetScene {
+        system,
+        target_system,
+        target,
+        mesh_renderer,
+        image_renderer,
+    };
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("render_target_image", &frame, CHANNEL_TOLERANCE);
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn render_target_clipped() {
+    let mut renderer = create_renderer();
+
+    let system = create_system(&mut renderer);
+    let target_system =
+        create_sized_system(&mut renderer, PhysicalSize::new(64.0, 64.0));
+    let target = RenderTarget::new(&mut renderer, PhysicalSize::new(64, 64));
+    let mut mesh_renderer = Mesh2DRenderer::new(&renderer).unwrap();
+    let mut image_renderer = ImageRenderer::new(&renderer).unwrap();
+
+    let mut builder = Mesh2DBuilder::default();
+
+    builder
+        .rectangle(
+            DrawMode::Fill(FillOptions::DEFAULT),
+            Vec4::new(0.0, 0.0, 64.0, 64.0),
+            1.0,
+            Color::rgba(0, 255, 255, 255),
+        )
+        .unwrap();
+
+    let mut mesh = Mesh2D::new(&mut renderer);
+    mesh.from_builder(builder.finalize());
+
+    // The clip reaches past the target so only its top right quarter is
+    // drawn, flipped against the target's height rather than the frame's.
+    renderer.push_clip(Bounds::new(32.0, 32.0, 96.0, 96.0));
+    mesh_renderer.mesh_update(&mut mesh, &mut renderer);
+    renderer.pop_clip();
+    mesh_renderer.finalize(&mut renderer);
+
+    let mut minimap = Image::new(Some(target.allocation()), &mut renderer, 1);
+    minimap.pos = Vec3::new(64.0, 64.0, 5.0);
+    minimap.hw = Vec2::new(128.0, 128.0);
+    minimap.uv = Vec4::new(0.0, 0.0, 64.0, 64.0);
+
+    image_renderer.image_update(&mut minimap, &mut renderer);
+    image_renderer.finalize(&mut renderer);
+
+    let mut scene = RenderTargetScene {
+        system,
+        target_system,
+        target,
+        mesh_renderer,
+        image_renderer,
+    };
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("render_target_clipped", &frame, CHANNEL_TOLERANCE);
+}
@@ -0,0 +1,72 @@
+//! Golden image tests for text bounds.
+
+mod common;
+
+use ascending_graphics::*;
+use camera::controls::FlatControls;
+use common::*;
+use cosmic_text::{Attrs, Metrics};
+
+struct TextScene {
+    system: System<FlatControls>,
+    text_renderer: TextRenderer,
+    atlas: TextAtlas,
+}
+
+impl Pass for TextScene {
+    fn render(
+        &mut self,
+        renderer: &GpuRenderer,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let mut pass = begin_pass(renderer, &self.system, encoder);
+
+        pass.render_text(
+            renderer,
+            &self.text_renderer,
+            &self.atlas,
+            renderer.size(),
+        );
+    }
+}
+
+#[test]
+#[ignore = "needs a software adapter, run with --include-ignored"]
+fn text_bounds() {
+    let mut renderer = create_renderer();
+
+    let system = create_system(&mut renderer);
+    let mut atlas = TextAtlas::new(&mut renderer).unwrap();
+    let mut text_renderer = TextRenderer::new(&renderer).unwrap();
+
+    let mut text = Text::new(
+        &mut renderer,
+        Some(Metrics::new(16.0, 16.0)),
+        Vec3::new(8.0, 200.0, 1.0),
+        Vec2::new(240.0, 48.0),
+    );
+
+    // The bounds cut through the second line and the end of the first.
+    text.set_buffer_size(&mut renderer, WIDTH as i32, HEIGHT as i32)
+        .set_bounds(Some(Bounds::new(8.0, 226.0, 180.0, 248.0)))
+        .set_default_color(Color::rgba(255, 255, 255, 255));
+    text.set_text(
+        &mut renderer,
+        "Golden text rendering test\nclipped by bounds",
+        Attrs::new(),
+    );
+
+    text_renderer
+        .text_update(&mut text, &mut atlas, &mut renderer)
+        .unwrap();
+    text_renderer.finalize(&mut renderer);
+
+    let mut scene = TextScene {
+        system,
+        text_renderer,
+        atlas,
+    };
+
+    let frame = capture(&renderer, &mut scene);
+    assert_golden("text_bounds", &frame, TEXT_CHANNEL_TOLERANCE);
+}
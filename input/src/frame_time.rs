@@ -25,6 +25,19 @@ impl FrameTime {
         }
     }
 
+    /// Creates a FrameTime frozen at the given seconds. Used to render
+    /// deterministic frames, like in the golden image tests.
+    pub fn from_seconds(seconds: f32) -> Self {
+        let instant = Instant::now();
+
+        Self {
+            delta_seconds: 0.0,
+            seconds,
+            frame_time: instant,
+            start_time: instant,
+        }
+    }
+
     pub fn seconds(&self) -> f32 {
         self.seconds
     }
@@ -0,0 +1,42 @@
+//! Packs a directory of images into atlas layer images and a manifest for
+//! Atlas::load_packed.
+//!
+//! atlas_packer <image directory> <manifest path> [size] [mip levels]
+//! [border]
+//!
+//! size is the max_texture_dimension_3d of the device the atlas is loaded
+//! on, 2048 by default. mip levels and border must match the Atlas, 1 and
+//! 0 by default.
+
+use ascending_packer::{AtlasPacker, PackerError};
+use std::path::Path;
+
+fn main() -> Result<(), PackerError> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.len() < 2 {
+        eprintln!(
+            "usage: atlas_packer <image directory> <manifest path> [size] \
+             [mip levels] [border]"
+        );
+        std::process::exit(2);
+    }
+
+    let size = args.get(2).and_then(|s| s.parse().ok()).unwrap_or(2048);
+    let mip_levels = args.get(3).and_then(|s| s.parse().ok()).unwrap_or(1);
+    let border = args.get(4).and_then(|s| s.parse().ok()).unwrap_or(0);
+    let manifest_path = Path::new(&args[1]);
+    let name = manifest_path
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .unwrap_or("atlas");
+
+    let mut packer =
+        AtlasPacker::<String, i32>::with_border(size, mip_levels, border);
+    let count = packer.add_directory(&args[0])?;
+    let (manifest, layers) = packer.pack(name)?;
+
+    manifest.save(manifest_path, &layers)?;
+    println!("Packed {count} images into {} layers.", layers.len());
+    Ok(())
+}
@@ -0,0 +1,11 @@
+//! Packs images into atlas layers ahead of time. Kept apart from
+//! ascending_graphics so the atlas_packer tool builds without a renderer.
+mod error;
+mod packer;
+mod padding;
+
+pub use error::PackerError;
+pub use packer::{AtlasManifest, AtlasPacker, PackedImage};
+pub use padding::{
+    clamp_mip_levels, mip_padding, pad_pixels, MAX_ATLAS_MIP_LEVELS,
+};
@@ -0,0 +1,246 @@
+use crate::{clamp_mip_levels, mip_padding, pad_pixels, PackerError};
+use guillotiere::{size2, AtlasAllocator};
+use image::{ImageFormat, RgbaImage};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{fs::File, io::BufWriter, path::Path};
+
+/// Where AtlasPacker put an image.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PackedImage<U = String, Data = i32> {
+    pub name: U,
+    pub layer: usize,
+    /// x, y, width and height in the layer, padding left out.
+    pub rect: (u32, u32, u32, u32),
+    pub data: Data,
+}
+
+/// The layout of an atlas packed ahead of time, saved as JSON next to an
+/// image per layer. Images are listed in the order they were allocated so
+/// Atlas::load_packed can allocate them the same way again.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AtlasManifest<U = String, Data = i32> {
+    /// Width and height of the layers. Must match the Atlas extent.
+    pub size: u32,
+    /// Must match the Atlas padding.
+    pub padding: u32,
+    /// Must match the Atlas border.
+    #[serde(default)]
+    pub border: u32,
+    /// The image file of each layer, next to the manifest.
+    pub layers: Vec<String>,
+    pub images: Vec<PackedImage<U, Data>>,
+}
+
+impl<U: Serialize + DeserializeOwned, Data: Serialize + DeserializeOwned>
+    AtlasManifest<U, Data>
+{
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, PackerError> {
+        Ok(serde_json::from_reader(std::io::BufReader::new(
+            File::open(path)?,
+        ))?)
+    }
+
+    /// Writes the manifest to path and each layer image next to it,
+    /// creating the directory if needed.
+    pub fn save(
+        &self,
+        path: impl AsRef<Path>,
+        layers: &[RgbaImage],
+    ) -> Result<(), PackerError> {
+        let directory = path.as_ref().parent().unwrap_or(Path::new(""));
+
+        std::fs::create_dir_all(directory)?;
+
+        for (name, layer) in self.layers.iter().zip(layers) {
+            layer.save(directory.join(name))?;
+        }
+
+        serde_json::to_writer_pretty(
+            BufWriter::new(File::create(path)?),
+            self,
+        )?;
+        Ok(())
+    }
+
+    /// Reads the layer images next to the manifest at path.
+    pub fn load_layers(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<RgbaImage>, PackerError> {
+        let directory = path.as_ref().parent().unwrap_or(Path::new(""));
+
+        self.layers
+            .iter()
+            .map(|name| Ok(image::open(directory.join(name))?.into_rgba8()))
+            .collect()
+    }
+}
+
+/// Packs images into atlas layers at build time the same way an Atlas of
+/// size and mip_levels would, so they load with one upload per layer.
+pub struct AtlasPacker<U = String, Data = i32> {
+    size: u32,
+    padding: u32,
+    border: u32,
+    images: Vec<(U, RgbaImage, Data)>,
+}
+
+impl<U: Clone, Data: Copy> AtlasPacker<U, Data> {
+    /// size is the Atlas extent, the max_texture_dimension_3d limit of
+    /// the device it is loaded on.
+    pub fn new(size: u32, mip_levels: u32) -> Self {
+        Self::with_border(size, mip_levels, 0)
+    }
+
+    /// Packs for an Atlas made with Atlas::with_border.
+    pub fn with_border(size: u32, mip_levels: u32, border: u32) -> Self {
+        let mip_levels = clamp_mip_levels(mip_levels, size);
+
+        Self {
+            size,
+            padding: mip_padding(mip_levels).max(border),
+            border,
+            images: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, name: U, image: RgbaImage, data: Data) {
+        self.images.push((name, image, data));
+    }
+
+    /// Packs the layers. Larger images go first as they are the hardest
+    /// to fit. Layer images are cropped to what is used and named
+    /// name_layer.png.
+    pub fn pack(
+        mut self,
+        name: &str,
+    ) -> Result<(AtlasManifest<U, Data>, Vec<RgbaImage>), PackerError> {
+        let padding = self.padding;
+        let mut layers: Vec<(AtlasAllocator, RgbaImage)> = Vec::new();
+        let mut images = Vec::with_capacity(self.images.len());
+
+        self.images.sort_by_key(|(_, image, _)| {
+            std::cmp::Reverse((image.height(), image.width()))
+        });
+
+        for (key, image, data) in &self.images {
+            let (width, height) =
+                (image.width() + padding * 2, image.height() + padding * 2);
+            let mut found =
+                layers.iter_mut().enumerate().find_map(|(i, (layer, _))| {
+                    layer
+                        .allocate(size2(width as i32, height as i32))
+                        .map(|allocation| (i, allocation))
+                });
+
+            if found.is_none() {
+                let mut layer = AtlasAllocator::new(size2(
+                    self.size as i32,
+                    self.size as i32,
+                ));
+
+                found = layer
+                    .allocate(size2(width as i32, height as i32))
+                    .map(|allocation| (layers.len(), allocation));
+                layers.push((layer, RgbaImage::new(self.size, self.size)));
+            }
+
+            let (layer, allocation) = found.ok_or(PackerError::AtlasFull)?;
+            let x = allocation.rectangle.min.x as u32 + padding;
+            let y = allocation.rectangle.min.y as u32 + padding;
+
+            let padded = RgbaImage::from_raw(
+                width,
+                height,
+                pad_pixels(
+                    image,
+                    image.width(),
+                    image.height(),
+                    4,
+                    padding,
+                    self.border,
+                ),
+            )
+            .unwrap_or_else(|| RgbaImage::new(width, height));
+
+            image::imageops::replace(
+                &mut layers[layer].1,
+                &padded,
+                allocation.rectangle.min.x as i64,
+                allocation.rectangle.min.y as i64,
+            );
+            images.push(PackedImage {
+                name: key.clone(),
+                layer,
+                rect: (x, y, image.width(), image.height()),
+                data: *data,
+            });
+        }
+
+        let layers: Vec<RgbaImage> = layers
+            .into_iter()
+            .enumerate()
+            .map(|(i, (_, layer))| {
+                let used = images.iter().filter(|image| image.layer == i);
+                let (width, height) = used.fold((1, 1), |size, image| {
+                    let (x, y, width, height) = image.rect;
+
+                    (
+                        size.0.max(x + width + padding),
+                        size.1.max(y + height + padding),
+                    )
+                });
+
+                image::imageops::crop_imm(&layer, 0, 0, width, height)
+                    .to_image()
+            })
+            .collect();
+
+        Ok((
+            AtlasManifest {
+                size: self.size,
+                padding,
+                border: self.border,
+                layers: (0..layers.len())
+                    .map(|layer| format!("{name}_{layer}.png"))
+                    .collect(),
+                images,
+            },
+            layers,
+        ))
+    }
+}
+
+impl<Data: Copy + Default> AtlasPacker<String, Data> {
+    /// Adds every image in directory named by its file name, like
+    /// Texture::from_file. Returns how many were added.
+    pub fn add_directory(
+        &mut self,
+        directory: impl AsRef<Path>,
+    ) -> Result<usize, PackerError> {
+        let mut paths = Vec::new();
+
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+
+            if path.is_file() && ImageFormat::from_path(&path).is_ok() {
+                paths.push(path);
+            }
+        }
+
+        // Sorted so the same directory always packs the same.
+        paths.sort();
+
+        for path in &paths {
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default()
+                .to_owned();
+
+            self.add(name, image::open(path)?.into_rgba8(), Data::default());
+        }
+
+        Ok(paths.len())
+    }
+}
@@ -0,0 +1,72 @@
+/// The most mip levels an Atlas can have. Each level doubles the padding
+/// kept around allocations, so more would waste most of the atlas.
+pub const MAX_ATLAS_MIP_LEVELS: u32 = 5;
+
+/// The padding an Atlas with mip_levels keeps around allocations.
+pub fn mip_padding(mip_levels: u32) -> u32 {
+    if mip_levels > 1 {
+        1 << (mip_levels - 1)
+    } else {
+        0
+    }
+}
+
+/// Surrounds the width by height pixels in buffer with padding pixels on
+/// each side. The border pixels nearest the image repeat its edge pixels
+/// and the rest are left transparent.
+pub fn pad_pixels(
+    buffer: &[u8],
+    width: u32,
+    height: u32,
+    pixel_size: u32,
+    padding: u32,
+    border: u32,
+) -> Vec<u8> {
+    let [width, height, pixel_size, padding] =
+        [width, height, pixel_size, padding].map(|value| value as usize);
+    let border = (border as usize).min(padding);
+    let row = width * pixel_size;
+    let padded_row = row + padding * 2 * pixel_size;
+    let mut bytes = vec![0; padded_row * (height + padding * 2)];
+
+    if width == 0 || height == 0 {
+        return bytes;
+    }
+
+    for (y, source) in buffer.chunks_exact(row).take(height).enumerate() {
+        let start = (y + padding) * padded_row + padding * pixel_size;
+        let (first, last) =
+            (&source[..pixel_size], &source[row - pixel_size..]);
+
+        bytes[start..start + row].copy_from_slice(source);
+
+        for x in 1..=border {
+            let left = start - x * pixel_size;
+            let right = start + row + (x - 1) * pixel_size;
+
+            bytes[left..left + pixel_size].copy_from_slice(first);
+            bytes[right..right + pixel_size].copy_from_slice(last);
+        }
+    }
+
+    // The rows above and below repeat the first and last row, corners
+    // included.
+    for y in 1..=border {
+        let first = padding * padded_row;
+        let last = (padding + height - 1) * padded_row;
+
+        bytes
+            .copy_within(first..first + padded_row, (padding - y) * padded_row);
+        bytes.copy_within(
+            last..last + padded_row,
+            (padding + height - 1 + y) * padded_row,
+        );
+    }
+
+    bytes
+}
+
+/// Clamps mip_levels to MAX_ATLAS_MIP_LEVELS and what size allows.
+pub fn clamp_mip_levels(mip_levels: u32, size: u32) -> u32 {
+    mip_levels.clamp(1, MAX_ATLAS_MIP_LEVELS.min(size.ilog2() + 1))
+}